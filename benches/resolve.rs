@@ -0,0 +1,51 @@
+//! Resolve-heavy workloads: thousands of promises registered and resolved per frame.
+//! Compare registries with `cargo bench` and `cargo bench --features dense_registry`.
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use pecs::prelude::*;
+
+#[derive(Resource)]
+struct Count(usize);
+
+fn spawn_chains(mut commands: Commands, count: Res<Count>) {
+    for i in 0..count.0 {
+        commands.add(
+            Promise::from(i)
+                .then(asyn!(state => state.pass()))
+                .then(asyn!(state => state.pass())),
+        );
+    }
+}
+
+fn spawn_all(mut commands: Commands, count: Res<Count>) {
+    let promises: Vec<_> = (0..count.0)
+        .map(|i| Promise::from(i).then(asyn!(s => s.pass())))
+        .collect();
+    commands.add(Promise::from(()).all(promises));
+}
+
+fn app(count: usize, system: fn(Commands, Res<Count>)) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, PecsPlugin));
+    app.insert_resource(Count(count));
+    app.add_systems(Update, system);
+    // warm up registries
+    app.update();
+    app
+}
+
+fn resolve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resolve");
+    for count in [1000, 5000] {
+        group.bench_with_input(BenchmarkId::new("chains", count), &count, |b, &count| {
+            b.iter_batched_ref(|| app(count, spawn_chains), |app| app.update(), BatchSize::LargeInput)
+        });
+        group.bench_with_input(BenchmarkId::new("all", count), &count, |b, &count| {
+            b.iter_batched_ref(|| app(count, spawn_all), |app| app.update(), BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, resolve);
+criterion_main!(benches);
@@ -0,0 +1,148 @@
+//! Thin layer isolating differences between supported Bevy versions.
+//!
+//! The Bevy version is selected with cargo features, exactly one of them
+//! should be enabled:
+//! - `bevy_0_13` (default)
+//! - `bevy_0_14` (requires `default-features = false`)
+//!
+//! The rest of `pecs` accesses Bevy through the [`bevy`] re-export and uses
+//! items from this crate where the Bevy API differs between versions.
+//!
+//! Bevy derive macros locate Bevy through the `bevy` dependency key of the crate
+//! being compiled, so crates depending on Bevy through this layer implement
+//! `Component`/`Resource`/`Event` manually instead of deriving them, using
+//! [`impl_component!`] and [`impl_event!`] where the trait differs between versions.
+#[cfg(all(feature = "bevy_0_13", feature = "bevy_0_14"))]
+compile_error!("pecs: `bevy_0_13` and `bevy_0_14` features are mutually exclusive, use `default-features = false`");
+#[cfg(not(any(feature = "bevy_0_13", feature = "bevy_0_14")))]
+compile_error!("pecs: one of `bevy_0_13` or `bevy_0_14` features should be enabled");
+
+#[cfg(feature = "bevy_0_13")]
+pub use bevy_0_13 as bevy;
+#[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+pub use bevy_0_14 as bevy;
+
+use bevy::app::AppExit;
+
+#[cfg(feature = "bevy_0_13")]
+pub use bevy::ecs::system::Command;
+#[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+pub use bevy::ecs::world::Command;
+
+/// [`AppExit`] event signaling that the app should exit successfully
+pub fn app_exit() -> AppExit {
+    #[cfg(feature = "bevy_0_13")]
+    {
+        AppExit
+    }
+    #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+    {
+        AppExit::Success
+    }
+}
+
+/// Implements table-stored `Component` for the type. Generic parameters
+/// are passed in square brackets: `impl_component!([M: Send + Sync + 'static] Mailbox<M>)`.
+#[cfg(feature = "bevy_0_13")]
+#[macro_export]
+macro_rules! impl_component {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> $crate::bevy::ecs::component::Component for $t {
+            type Storage = $crate::bevy::ecs::component::TableStorage;
+        }
+    };
+    ($t:ty) => {
+        $crate::impl_component!([] $t);
+    };
+}
+
+/// Implements table-stored `Component` for the type. Generic parameters
+/// are passed in square brackets: `impl_component!([M: Send + Sync + 'static] Mailbox<M>)`.
+#[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+#[macro_export]
+macro_rules! impl_component {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> $crate::bevy::ecs::component::Component for $t {
+            const STORAGE_TYPE: $crate::bevy::ecs::component::StorageType =
+                $crate::bevy::ecs::component::StorageType::Table;
+        }
+    };
+    ($t:ty) => {
+        $crate::impl_component!([] $t);
+    };
+}
+
+/// Implements `Event` for the type
+#[cfg(feature = "bevy_0_13")]
+#[macro_export]
+macro_rules! impl_event {
+    ($t:ty) => {
+        impl $crate::bevy::ecs::event::Event for $t {}
+    };
+}
+
+/// Implements `Event` for the type, events are components since Bevy 0.14
+#[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+#[macro_export]
+macro_rules! impl_event {
+    ($t:ty) => {
+        $crate::impl_component!($t);
+        impl $crate::bevy::ecs::event::Event for $t {}
+    };
+}
+
+/// Returns the `color` with alpha channel replaced by `alpha`
+pub fn color_with_alpha(color: bevy::prelude::Color, alpha: f32) -> bevy::prelude::Color {
+    #[cfg(feature = "bevy_0_13")]
+    {
+        color.with_a(alpha)
+    }
+    #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+    {
+        use bevy::color::Alpha;
+        color.with_alpha(alpha)
+    }
+}
+
+/// States that can be changed with `NextState<T>`
+#[cfg(feature = "bevy_0_13")]
+pub use bevy::ecs::schedule::States as FreelyMutableState;
+/// States that can be changed with `NextState<T>`
+#[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+pub use bevy::state::state::FreelyMutableState;
+
+/// The main [`World`][bevy::prelude::World] of the `app`
+pub fn app_world(app: &bevy::prelude::App) -> &bevy::prelude::World {
+    #[cfg(feature = "bevy_0_13")]
+    {
+        &app.world
+    }
+    #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+    {
+        app.world()
+    }
+}
+
+/// The main [`World`][bevy::prelude::World] of the `app`, mutable
+pub fn app_world_mut(app: &mut bevy::prelude::App) -> &mut bevy::prelude::World {
+    #[cfg(feature = "bevy_0_13")]
+    {
+        &mut app.world
+    }
+    #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+    {
+        app.world_mut()
+    }
+}
+
+/// Returns `true` if the asset load `state` is failed
+pub fn load_failed(state: &bevy::asset::LoadState) -> bool {
+    #[cfg(feature = "bevy_0_13")]
+    {
+        matches!(state, bevy::asset::LoadState::Failed)
+    }
+    #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+    {
+        matches!(state, bevy::asset::LoadState::Failed(_))
+    }
+}
@@ -5,7 +5,7 @@ use super::*;
 pub fn exit() -> Promise<(), ()> {
     Promise::register(
         |world, _| {
-            world.resource_mut::<Events<AppExit>>().send(AppExit);
+            world.resource_mut::<Events<AppExit>>().send(pecs_compat::app_exit());
         },
         // can't discard AppExit
         |_, _| {},
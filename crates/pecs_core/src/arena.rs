@@ -0,0 +1,111 @@
+//! Type-erased index of promise registries. Every `Promise<S, R>` type
+//! registered in the world is recorded in [`PromiseArena`], so pending promises
+//! could be enumerated without knowing concrete `(S, R)` pairs. Useful for
+//! headless smoke tests:
+//! ```ignore
+//! #[test]
+//! fn level_flow_completes() {
+//!     let mut app = App::new();
+//!     app.add_plugins((MinimalPlugins, PecsPlugin, LevelPlugin));
+//!     // panics with the list of pending promises if any left after 100 updates
+//!     PromiseArena::assert_no_pending(&mut app, 100);
+//! }
+//! ```
+use std::any::TypeId;
+
+use bevy::utils::HashSet;
+
+use super::*;
+
+struct ArenaEntry {
+    state: &'static str,
+    result: &'static str,
+    promises: fn(&World) -> Vec<PromiseId>,
+}
+
+/// Promise types registered in the world
+#[derive(Default)]
+pub struct PromiseArena {
+    entries: Vec<ArenaEntry>,
+    types: HashSet<TypeId>,
+}
+impl Resource for PromiseArena {}
+
+/// Pending promises of the single `Promise<S, R>` type
+#[derive(Clone, Debug)]
+pub struct PromiseTypeSnapshot {
+    /// Type name of the promise state `S`
+    pub state: &'static str,
+    /// Type name of the promise result `R`
+    pub result: &'static str,
+    /// Pending promises of this type
+    pub promises: Vec<PromiseId>,
+}
+
+impl std::fmt::Display for PromiseTypeSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}, {}>: {} pending", self.state, self.result, self.promises.len())
+    }
+}
+
+impl PromiseArena {
+    /// Pending promises grouped by their types. Types without
+    /// pending promises are included with empty `promises`.
+    pub fn snapshot(world: &World) -> Vec<PromiseTypeSnapshot> {
+        let Some(arena) = world.get_resource::<PromiseArena>() else {
+            return vec![];
+        };
+        arena
+            .entries
+            .iter()
+            .map(|entry| PromiseTypeSnapshot {
+                state: entry.state,
+                result: entry.result,
+                promises: (entry.promises)(world),
+            })
+            .collect()
+    }
+
+    /// Total number of pending promises of all types
+    pub fn pending(world: &World) -> usize {
+        Self::snapshot(world).iter().map(|s| s.promises.len()).sum()
+    }
+
+    /// Run `app` schedule `updates` times and panic if there are pending promises
+    #[track_caller]
+    pub fn assert_no_pending(app: &mut App, updates: usize) {
+        for _ in 0..updates {
+            app.update();
+        }
+        let pending: Vec<_> = Self::snapshot(pecs_compat::app_world(app))
+            .into_iter()
+            .filter(|s| !s.promises.is_empty())
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+        let pending = pending.iter().map(|p| format!("  {p}")).collect::<Vec<_>>().join("\n");
+        panic!("Expected no pending promises after {updates} update(s), found:\n{pending}");
+    }
+}
+
+/// Adds `Promise<S, R>` to the arena index if it is not there yet
+pub(crate) fn index<S: 'static, R: 'static>(world: &mut World) {
+    let mut arena = world.get_resource_or_insert_with(PromiseArena::default);
+    let type_id = TypeId::of::<PromiseRegistry<S, R>>();
+    if !arena.types.insert(type_id) {
+        return;
+    }
+    arena.entries.push(ArenaEntry {
+        state: type_name::<S>(),
+        result: type_name::<R>(),
+        promises: pending_ids::<S, R>,
+    });
+}
+
+fn pending_ids<S: 'static, R: 'static>(world: &World) -> Vec<PromiseId> {
+    world
+        .get_resource::<PromiseRegistry<S, R>>()
+        .map(|registry| registry::ids(&registry.0))
+        .unwrap_or_default()
+}
@@ -0,0 +1,94 @@
+//! Asset loading promises: load the asset and wait until it is ready
+//! instead of polling the [`AssetServer`] in the custom systems.
+//! ```ignore
+//! fn load_level(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::assets::load::<Scene>("level.glb#Scene0")
+//!             .then_ok(asyn!(_, scene, mut commands: Commands => {
+//!                 commands.spawn(SceneBundle { scene, ..default() });
+//!             }))
+//!             .catch(asyn!(_, error => error!("Can't load the level: {error}"))),
+//!     );
+//! }
+//! ```
+use bevy::asset::{AssetPath, UntypedHandle};
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(assets)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that loads the `T` asset from the `path` and resolves with its
+    /// handle when the asset and its dependencies are loaded, or with the error
+    /// if the asset fails to load.
+    pub fn load<T: Asset>(path: impl Into<AssetPath<'static>>) -> Promise<(), Result<Handle<T>, String>> {
+        let path = path.into();
+        Promise::register(
+            move |world, id| {
+                let Some(server) = world.get_resource::<AssetServer>() else {
+                    errors::report(world, id, errors::PecsError::PluginMissing { plugin: "AssetPlugin" });
+                    return;
+                };
+                let handle = server.load::<T>(path).untyped();
+                if !poll_load::<T>(world, id, &handle) {
+                    world
+                        .resource_mut::<AssetWaiters>()
+                        .0
+                        .push((id, handle, poll_load::<T>));
+                }
+            },
+            |world, id| {
+                world
+                    .resource_mut::<AssetWaiters>()
+                    .0
+                    .retain(|(waiter, _, _)| waiter != &id);
+            },
+        )
+    }
+}
+
+pub struct PromiseAssetsPlugin;
+impl Plugin for PromiseAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetWaiters>();
+        app.add_systems(Update, process_asset_waiters);
+    }
+}
+
+/// Resolves the promise if the asset is loaded or failed, returns `true` when resolved
+type AssetPoll = fn(&mut World, PromiseId, &UntypedHandle) -> bool;
+
+/// Promises waiting for the assets to load
+#[derive(Default)]
+pub struct AssetWaiters(Vec<(PromiseId, UntypedHandle, AssetPoll)>);
+impl Resource for AssetWaiters {}
+
+fn poll_load<T: Asset>(world: &mut World, id: PromiseId, handle: &UntypedHandle) -> bool {
+    let server = world.resource::<AssetServer>();
+    let result = if server.is_loaded_with_dependencies(handle.id()) {
+        Ok(handle.clone().typed::<T>())
+    } else if server
+        .get_load_state(handle.id())
+        .is_some_and(|state| pecs_compat::load_failed(&state))
+    {
+        let path = handle.path().map(|path| path.to_string()).unwrap_or_default();
+        Err(format!("failed to load {} from {path}", type_name::<T>()))
+    } else {
+        return false;
+    };
+    promise_resolve::<(), Result<Handle<T>, String>>(world, id, (), result);
+    true
+}
+
+fn process_asset_waiters(world: &mut World) {
+    let waiters = mem::take(&mut world.resource_mut::<AssetWaiters>().0);
+    let mut pending = Vec::with_capacity(waiters.len());
+    for (id, handle, poll) in waiters {
+        if !poll(world, id, &handle) {
+            pending.push((id, handle, poll));
+        }
+    }
+    world.resource_mut::<AssetWaiters>().0.extend(pending);
+}
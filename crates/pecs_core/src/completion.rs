@@ -0,0 +1,112 @@
+//! Prerequisites between otherwise unrelated chains. Take the [`Completion`] handle
+//! of the promise with [`Promise::completion`] and gate other chains on it with
+//! [`after()`][PromiseLikeBase::after] or [`after_all()`][PromiseLikeBase::after_all]:
+//! ```ignore
+//! fn setup(mut commands: Commands) {
+//!     let (load_assets, assets_loaded) = asyn::timeout(1.).completion();
+//!     commands.add(load_assets);
+//!     // the music starts only after the assets are loaded,
+//!     // the chain doesn't care about the loading result
+//!     commands.add(
+//!         Promise::start(asyn!(_ => {
+//!             info!("Start music");
+//!         }))
+//!         .after(&assets_loaded),
+//!     );
+//! }
+//! ```
+use super::*;
+
+#[derive(Default)]
+struct CompletionState {
+    finished: bool,
+    waiters: Vec<PromiseId>,
+}
+
+/// Handle of the promise finishing, created with [`Promise::completion`].
+/// The promise is considered finished when it resolves or is discarded.
+#[derive(Clone, Default)]
+pub struct Completion(Arc<Mutex<CompletionState>>);
+
+impl Completion {
+    /// `true` if the promise is resolved or discarded
+    pub fn is_finished(&self) -> bool {
+        self.0.lock().unwrap().finished
+    }
+
+    /// Creates promise that resolves when the promise is finished
+    pub fn wait(&self) -> Promise<(), ()> {
+        let completion = self.clone();
+        let discard = self.clone();
+        Promise::register(
+            move |world, id| {
+                let finished = {
+                    let mut state = completion.0.lock().unwrap();
+                    if !state.finished {
+                        state.waiters.push(id);
+                    }
+                    state.finished
+                };
+                if finished {
+                    promise_resolve::<(), ()>(world, id, (), ());
+                }
+            },
+            move |_world, id| {
+                discard.0.lock().unwrap().waiters.retain(|waiter| waiter != &id);
+            },
+        )
+    }
+
+    fn finish(&self, world: &mut World) {
+        let waiters = {
+            let mut state = self.0.lock().unwrap();
+            state.finished = true;
+            mem::take(&mut state.waiters)
+        };
+        for waiter in waiters {
+            promise_resolve::<(), ()>(world, waiter, (), ());
+        }
+    }
+}
+
+impl<S: 'static, R: 'static> Promise<S, R> {
+    /// Returns the promise along with the [`Completion`] handle, so other chains could
+    /// wait for it with [`after()`][PromiseLikeBase::after] without consuming its result.
+    pub fn completion(self) -> (Promise<S, R>, Completion) {
+        let completion = Completion::default();
+        let finish = completion.clone();
+        let mut promise = self
+            .map(move |s| (s, finish))
+            .then(asyn!(s, r, mut commands: Commands => {
+                let (state, completion) = s.value;
+                commands.add(move |world: &mut World| completion.finish(world));
+                PromiseResult::Resolve(state, r)
+            }));
+        let discard = promise.discard.take();
+        let finish = completion.clone();
+        promise.discard = Some(Box::new(move |world, id| {
+            finish.finish(world);
+            if let Some(discard) = discard {
+                discard(world, id)
+            }
+        }));
+        (promise, completion)
+    }
+
+    /// Delays the promise start until the `prerequisite` is finished
+    pub(crate) fn after_completion(self, prerequisite: &Completion) -> Promise<S, R> {
+        prerequisite.wait().map(move |_| self).then(asyn!(s => s.value))
+    }
+
+    /// Delays the promise start until every prerequisite is finished
+    pub(crate) fn after_completions<'a, I: IntoIterator<Item = &'a Completion>>(
+        self,
+        prerequisites: I,
+    ) -> Promise<S, R> {
+        let waiters: Vec<_> = prerequisites.into_iter().map(|c| c.wait()).collect();
+        if waiters.is_empty() {
+            return self;
+        }
+        Promise::all(waiters).map(move |_| self).then(asyn!(s => s.value))
+    }
+}
@@ -0,0 +1,81 @@
+//! Heavy computations off the main thread: [`compute()`] runs the closure on the
+//! [`AsyncComputeTaskPool`][bevy::tasks::AsyncComputeTaskPool] and resolves with its
+//! return value, so the handlers don't block the frame:
+//! ```ignore
+//! fn find_path(mut commands: Commands, grid: Res<NavGrid>, agent: Query<(Entity, &Transform), With<Agent>>) {
+//!     let (agent, transform) = agent.single();
+//!     commands.add(
+//!         Promise::from((grid.clone(), transform.translation))
+//!             .then(asyn!(s => {
+//!                 let (grid, from) = s.value;
+//!                 asyn::compute(move || grid.path(from, Vec3::ZERO))
+//!             }))
+//!             .map(move |_| agent)
+//!             .then(asyn!(s, path, mut commands: Commands => {
+//!                 commands.entity(s.value).insert(FollowPath(path));
+//!             })),
+//!     );
+//! }
+//! ```
+//! The closure is polled as the [`Promise::from_future`] future. On wasm the pool
+//! runs the closure on the main thread, between the frames.
+//!
+//! Handlers without system params could run on the pool as well, see
+//! [`then_detached()`][PromiseLikeBase::then_detached].
+use pecs_macro::asyn;
+
+use super::*;
+
+/// Creates promise that runs the `func` on the compute task pool
+/// and resolves with its return value
+pub fn compute<R: 'static + Send, F: 'static + FnOnce() -> R + Send>(func: F) -> Promise<(), R> {
+    Promise::from_future(async move { func() })
+}
+
+thread_local! {
+    /// The empty world the detached handlers run against, one per pool thread
+    static DETACHED_WORLD: RefCell<World> = RefCell::new(World::new());
+}
+
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
+    /// Runs the `func` on the compute task pool, see [`PromiseLikeBase::then_detached`]
+    pub(crate) fn run_detached<S2: 'static + Send, R2: 'static + Send>(
+        self,
+        func: Asyn<(PromiseState<S>, R), (S2, R2), ()>,
+    ) -> Promise<S2, R2> {
+        let AsynBody::System(body) = func.body else {
+            return self
+                .map(move |state| (state, func))
+                .then(asyn!(s, r, world: &mut World => {
+                    let (state, func) = s.value;
+                    let (state, result) = func.run((PromiseState::new(state), r), world);
+                    PromiseResult::Resolve(state, result)
+                }));
+        };
+        self.map(move |state| (state, body)).then(asyn!(s, r => {
+            let (state, body) = s.value;
+            let input = (PromiseState::new(state), r);
+            compute(move || {
+                DETACHED_WORLD.with(|world| {
+                    let world = &mut world.borrow_mut();
+                    let mut system = IntoSystem::into_system(body);
+                    system.initialize(world);
+                    system.run(input, world)
+                })
+            })
+            .then(asyn!(_, output => {
+                let (state, result) = output;
+                PromiseResult::Resolve(state, result)
+            }))
+        }))
+    }
+}
+
+pub trait ComputeOpsExtension<S> {
+    fn compute<R: 'static + Send, F: 'static + FnOnce() -> R + Send>(self, func: F) -> Promise<S, R>;
+}
+impl<S: 'static> ComputeOpsExtension<S> for AsynOps<S> {
+    fn compute<R: 'static + Send, F: 'static + FnOnce() -> R + Send>(self, func: F) -> Promise<S, R> {
+        compute(func).map(|_| self.0)
+    }
+}
@@ -0,0 +1,279 @@
+//! Memory accounting for integration tests. Pending promises are kept in the
+//! world registries (see [`PromiseArena`]), and in debug builds the world also
+//! keeps track of the result slots allocated by pending [`all()`][Promise::all]
+//! combinators. [`assert_no_leaks`] panics if some of them are still alive after
+//! the flow is complete:
+//! ```ignore
+//! #[test]
+//! fn loading_frees_everything() {
+//!     let mut app = App::new();
+//!     app.add_plugins((MinimalPlugins, PecsPlugin, LoadingPlugin));
+//!     for _ in 0..100 {
+//!         app.update();
+//!     }
+//!     pecs::debug::assert_no_leaks(&app.world);
+//! }
+//! ```
+//! In release builds the slots are not tracked.
+//!
+//! [`PecsDebugPlugin`] lists the live promises in the [`LivePromises`] resource every
+//! frame, along with their age and the promises they await, so stuck and leaked chains
+//! could be spotted in the running game. Enable the [`overlay`][PecsDebugPlugin::overlay]
+//! to see the oldest of them on the screen. Set [`warn_slow_after`][PecsDebugPlugin::warn_slow_after]
+//! to report the handlers blocking the frame:
+//! ```ignore
+//! App::new()
+//!     .add_plugins((DefaultPlugins, PecsPlugin))
+//!     .add_plugins(PecsDebugPlugin {
+//!         overlay: true,
+//!         warn_slow_after: Some(Duration::from_millis(5)),
+//!     })
+//!     .run();
+//! ```
+use std::cmp::Reverse;
+use std::fmt;
+
+use super::*;
+use arena::PromiseArena;
+use inspect::PromiseStatus;
+
+/// Result slots owned by pending promises, tracked in debug builds
+#[derive(Default)]
+pub struct PromiseSlots(HashMap<PromiseId, &'static str>);
+impl Resource for PromiseSlots {}
+
+impl PromiseSlots {
+    /// Number of the slots still allocated
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Records the slot of type `T` allocated by the pending promise `owner`
+pub(crate) fn slot_allocated<T>(world: &mut World, owner: PromiseId) {
+    if cfg!(debug_assertions) {
+        world
+            .get_resource_or_insert_with(PromiseSlots::default)
+            .0
+            .insert(owner, type_name::<T>());
+    }
+}
+
+/// Records the slot owned by `owner` is freed
+pub(crate) fn slot_freed(world: &mut World, owner: PromiseId) {
+    if let Some(mut slots) = world.get_resource_mut::<PromiseSlots>() {
+        slots.0.remove(&owner);
+    }
+}
+
+/// Human-readable list of the pending promises and allocated slots of the `world`
+pub fn leaks(world: &World) -> Vec<String> {
+    let mut leaks = vec![];
+    for snapshot in PromiseArena::snapshot(world) {
+        for id in snapshot.promises {
+            leaks.push(format!("promise {id}<{}, {}>", snapshot.state, snapshot.result));
+        }
+    }
+    if let Some(slots) = world.get_resource::<PromiseSlots>() {
+        for (owner, slot) in slots.0.iter() {
+            leaks.push(format!("slot {slot} owned by {owner}"));
+        }
+    }
+    leaks.sort();
+    leaks
+}
+
+/// Panics with the list of leaks if some promises are still pending
+/// or some slots are still allocated in the `world`
+#[track_caller]
+pub fn assert_no_leaks(world: &World) {
+    let leaks = leaks(world);
+    if leaks.is_empty() {
+        return;
+    }
+    let leaks = leaks.iter().map(|l| format!("  {l}")).collect::<Vec<_>>().join("\n");
+    panic!("Expected no leaked promise allocations, found:\n{leaks}");
+}
+
+/// Lists the live promises in [`LivePromises`], see [module docs][self]
+#[derive(Default)]
+pub struct PecsDebugPlugin {
+    /// Show the oldest live promises in the top left corner of the screen
+    pub overlay: bool,
+    /// Warn about the [`Asyn`] bodies running longer than this, see [`PecsSystems::warn_slow_after`]
+    pub warn_slow_after: Option<Duration>,
+}
+impl Plugin for PecsDebugPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(threshold) = self.warn_slow_after {
+            let world = pecs_compat::app_world_mut(app);
+            world.get_resource_or_insert_with(PecsSystems::default).warn_slow_after = Some(threshold);
+        }
+        app.init_resource::<LivePromises>();
+        app.add_systems(Last, collect_live_promises);
+        if self.overlay {
+            app.add_systems(Last, draw_overlay.after(collect_live_promises));
+        }
+    }
+}
+
+/// The promise pending at the end of the frame
+#[derive(Clone, Debug)]
+pub struct LivePromise {
+    pub id: PromiseId,
+    /// Type name of the promise state `S`
+    pub state: &'static str,
+    /// Type name of the promise result `R`
+    pub result: &'static str,
+    /// Time passed since the promise is registered
+    pub age: Duration,
+    /// The promise awaiting this one
+    pub parent: Option<PromiseId>,
+    /// The promise this one awaits
+    pub child: Option<PromiseId>,
+    /// Label of the chain, if [`ChainLabels`][inspect::ChainLabels] are tracked
+    pub label: Option<&'static str>,
+}
+
+impl fmt::Display for LivePromise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let LivePromise { id, state, result, .. } = self;
+        write!(f, "{id}<{state}, {result}> {:.1}s", self.age.as_secs_f32())?;
+        if let Some(label) = self.label {
+            write!(f, " \"{label}\"")?;
+        }
+        if let Some(child) = self.child {
+            write!(f, " awaits {child}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Promises pending at the end of the last frame, collected by [`PecsDebugPlugin`]
+#[derive(Default)]
+pub struct LivePromises {
+    promises: Vec<LivePromise>,
+    registered: HashMap<PromiseId, Instant>,
+}
+impl Resource for LivePromises {}
+
+impl LivePromises {
+    /// Live promises sorted by the age, the oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &LivePromise> {
+        self.promises.iter()
+    }
+
+    pub fn get(&self, id: PromiseId) -> Option<&LivePromise> {
+        self.promises.iter().find(|promise| promise.id == id)
+    }
+
+    /// Live promises pending for longer than `age`, likely stuck or leaked
+    pub fn older_than(&self, age: Duration) -> impl Iterator<Item = &LivePromise> {
+        self.promises.iter().take_while(move |promise| promise.age > age)
+    }
+
+    pub fn len(&self) -> usize {
+        self.promises.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.promises.is_empty()
+    }
+}
+
+/// Records the registration time of the promise if [`LivePromises`] are collected
+pub(crate) fn record(world: &mut World, id: PromiseId, status: PromiseStatus) {
+    let Some(mut live) = world.get_resource_mut::<LivePromises>() else {
+        return;
+    };
+    match status {
+        PromiseStatus::Pending => {
+            live.registered.insert(id, Instant::now());
+        }
+        PromiseStatus::Resolved | PromiseStatus::Discarded => {
+            live.registered.remove(&id);
+        }
+    }
+}
+
+fn collect_live_promises(world: &mut World) {
+    let now = Instant::now();
+    let mut parents = HashMap::new();
+    let mut children = HashMap::new();
+    if let Some(links) = world.get_resource::<AwaitLinks>() {
+        for (parent, (child, _)) in links.0.iter() {
+            parents.insert(*child, *parent);
+            children.insert(*parent, *child);
+        }
+    }
+    let mut registered = mem::take(&mut world.resource_mut::<LivePromises>().registered);
+    let mut promises = vec![];
+    for snapshot in PromiseArena::snapshot(world) {
+        for id in snapshot.promises {
+            promises.push(LivePromise {
+                id,
+                state: snapshot.state,
+                result: snapshot.result,
+                // promises registered before the plugin is added age from the first collection
+                age: now - *registered.entry(id).or_insert(now),
+                parent: parents.get(&id).copied(),
+                child: children.get(&id).copied(),
+                label: inspect::chain_label(world, id),
+            });
+        }
+    }
+    promises.sort_by_key(|promise| Reverse(promise.age));
+    let mut live = world.resource_mut::<LivePromises>();
+    live.promises = promises;
+    live.registered = registered;
+}
+
+/// Live promises shown by the overlay
+const OVERLAY_LINES: usize = 20;
+
+/// Text of the [`PecsDebugPlugin`] overlay
+pub struct PecsDebugOverlay;
+pecs_compat::impl_component!(PecsDebugOverlay);
+
+fn draw_overlay(
+    mut commands: Commands,
+    live: Res<LivePromises>,
+    mut overlay: Query<&mut Text, With<PecsDebugOverlay>>,
+) {
+    let mut text = format!("{} live promises", live.len());
+    for promise in live.iter().take(OVERLAY_LINES) {
+        text.push('\n');
+        text.push_str(&promise.to_string());
+    }
+    if live.len() > OVERLAY_LINES {
+        text.push_str(&format!("\n... and {} more", live.len() - OVERLAY_LINES));
+    }
+    let style = TextStyle {
+        font_size: 14.,
+        color: Color::WHITE,
+        ..default()
+    };
+    match overlay.get_single_mut() {
+        Ok(mut overlay) => *overlay = Text::from_section(text, style),
+        Err(_) => {
+            commands.spawn((
+                TextBundle {
+                    text: Text::from_section(text, style),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(4.),
+                        left: Val::Px(4.),
+                        ..default()
+                    },
+                    z_index: ZIndex::Global(i32::MAX),
+                    ..default()
+                },
+                PecsDebugOverlay,
+            ));
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Promise throughput metrics reported through Bevy diagnostics. Add
+//! [`PromiseDiagnosticsPlugin`] and read the values from [`DiagnosticsStore`]
+//! or print them with `LogDiagnosticsPlugin`:
+//! ```ignore
+//! App::new()
+//!     .add_plugins((DefaultPlugins, PecsPlugin))
+//!     .add_plugins((PromiseDiagnosticsPlugin, LogDiagnosticsPlugin::default()))
+//!     .run();
+//! ```
+//! Every value is measured once per frame.
+//!
+//! Chains could report their own values with [`record_diagnostic()`][Promise::record_diagnostic],
+//! the diagnostic is created on the first use:
+//! ```ignore
+//! const LOGIN_TIME: DiagnosticPath = DiagnosticPath::const_new("game/login_ms");
+//!
+//! commands.add(
+//!     Promise::from(())
+//!         .then_measure(asyn!(state => state.asyn().http().post("https://example.com/login").send()))
+//!         .map_result(|(_, elapsed)| elapsed)
+//!         .record_diagnostic(LOGIN_TIME),
+//! );
+//! ```
+use bevy::diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore, RegisterDiagnostic};
+
+use super::*;
+use external::ExternalResolutions;
+use inspect::PromiseStatus;
+
+/// Promises registered during the frame
+pub const REGISTERED: DiagnosticPath = DiagnosticPath::const_new("pecs/registered");
+/// Promises resolved during the frame
+pub const RESOLVED: DiagnosticPath = DiagnosticPath::const_new("pecs/resolved");
+/// Promises discarded during the frame
+pub const DISCARDED: DiagnosticPath = DiagnosticPath::const_new("pecs/discarded");
+/// [`Asyn`][struct@Asyn] handlers executed during the frame
+pub const HANDLERS: DiagnosticPath = DiagnosticPath::const_new("pecs/handlers");
+/// Promises pending at the end of the frame
+pub const PENDING: DiagnosticPath = DiagnosticPath::const_new("pecs/pending");
+/// Resolutions sent from other threads and waiting for the next frame
+pub const EXTERNAL_QUEUE: DiagnosticPath = DiagnosticPath::const_new("pecs/external_queue");
+
+/// Counters of the current frame, reset when the measurements are taken
+#[derive(Default)]
+pub struct PromiseMetrics {
+    pub registered: usize,
+    pub resolved: usize,
+    pub discarded: usize,
+    pub handlers: usize,
+}
+impl Resource for PromiseMetrics {}
+
+pub struct PromiseDiagnosticsPlugin;
+impl Plugin for PromiseDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PromiseMetrics>();
+        for path in [REGISTERED, RESOLVED, DISCARDED, HANDLERS, PENDING, EXTERNAL_QUEUE] {
+            app.register_diagnostic(Diagnostic::new(path));
+        }
+        app.add_systems(Last, measure_promises);
+    }
+}
+
+/// Counts the promise status change if [`PromiseMetrics`] is collected
+pub(crate) fn count_status(world: &mut World, status: PromiseStatus) {
+    let Some(mut metrics) = world.get_resource_mut::<PromiseMetrics>() else {
+        return;
+    };
+    match status {
+        PromiseStatus::Pending => metrics.registered += 1,
+        PromiseStatus::Resolved => metrics.resolved += 1,
+        PromiseStatus::Discarded => metrics.discarded += 1,
+    }
+}
+
+/// Counts the handler execution if [`PromiseMetrics`] is collected
+pub(crate) fn count_handler(world: &mut World) {
+    if let Some(mut metrics) = world.get_resource_mut::<PromiseMetrics>() {
+        metrics.handlers += 1;
+    }
+}
+
+fn measure_promises(world: &mut World) {
+    let metrics = mem::take(&mut *world.resource_mut::<PromiseMetrics>());
+    let pending = arena::PromiseArena::pending(world);
+    let queue = world
+        .get_resource::<ExternalResolutions>()
+        .map(|r| r.len())
+        .unwrap_or_default();
+    let Some(mut store) = world.get_resource_mut::<DiagnosticsStore>() else {
+        return;
+    };
+    let now = Instant::now();
+    for (path, value) in [
+        (&REGISTERED, metrics.registered),
+        (&RESOLVED, metrics.resolved),
+        (&DISCARDED, metrics.discarded),
+        (&HANDLERS, metrics.handlers),
+        (&PENDING, pending),
+        (&EXTERNAL_QUEUE, queue),
+    ] {
+        if let Some(diagnostic) = store.get_mut(path).filter(|d| d.is_enabled) {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: now,
+                value: value as f64,
+            });
+        }
+    }
+}
+
+/// Numeric values recorded with [`record_diagnostic()`][Promise::record_diagnostic]
+pub trait DiagnosticValue {
+    fn diagnostic_value(&self) -> f64;
+}
+
+macro_rules! impl_diagnostic_value {
+    ($($t:ty),*) => {
+        $(impl DiagnosticValue for $t {
+            fn diagnostic_value(&self) -> f64 {
+                *self as f64
+            }
+        })*
+    };
+}
+impl_diagnostic_value!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Durations are recorded in milliseconds, like the frame time diagnostics
+impl DiagnosticValue for Duration {
+    fn diagnostic_value(&self) -> f64 {
+        self.as_secs_f64() * 1000.
+    }
+}
+
+impl<S: 'static, R: 'static + DiagnosticValue> Promise<S, R> {
+    /// Records the result of the promise into the diagnostic at `path`, creating
+    /// the diagnostic if it is not registered yet. The state and the result pass
+    /// to the next promise as is.
+    pub fn record_diagnostic(self, path: DiagnosticPath) -> Promise<S, R> {
+        self.map(move |state| (state, path))
+            .then(asyn!(s, r, store: Option<ResMut<DiagnosticsStore>> => {
+                let (state, path) = s.value;
+                let Some(mut store) = store else {
+                    return PromiseResult::Resolve(state, r);
+                };
+                if store.get(&path).is_none() {
+                    store.add(Diagnostic::new(path.clone()));
+                }
+                if let Some(diagnostic) = store.get_mut(&path).filter(|d| d.is_enabled) {
+                    diagnostic.add_measurement(DiagnosticMeasurement {
+                        time: Instant::now(),
+                        value: r.diagnostic_value(),
+                    });
+                }
+                PromiseResult::Resolve(state, r)
+            }))
+    }
+}
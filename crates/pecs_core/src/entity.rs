@@ -0,0 +1,158 @@
+//! Entity lifecycle promises: wait for the components of the entity to be added,
+//! changed or removed instead of writing the detection systems:
+//! ```ignore
+//! fn wait_for_target(mut commands: Commands, turret: Query<Entity, With<Turret>>) {
+//!     let turret = turret.single();
+//!     commands.add(
+//!         asyn::entity(turret)
+//!             .component_added::<Target>()
+//!             .map(move |_| turret)
+//!             .then(asyn!(s, target => {
+//!                 info!("Turret locked on {:?}", target.0);
+//!                 s.asyn().entity(s.value).component_removed::<Target>()
+//!             }))
+//!             .then(asyn!(_ => info!("Turret lost the target"))),
+//!     );
+//! }
+//! ```
+//! Additions and changes are detected with the component change ticks, so only
+//! the ones made after the promise starts count. The waiting promises are
+//! discarded when the entity is despawned.
+use bevy::ecs::component::Tick;
+
+use super::*;
+
+/// Creates [`AsynEntity`] for awaiting the component changes of the `entity`
+pub fn entity(entity: Entity) -> AsynEntity {
+    AsynEntity(entity)
+}
+
+pub struct AsynEntity(Entity);
+impl AsynEntity {
+    /// Creates promise that resolves with the clone of the `T` component
+    /// when it is added to the entity.
+    pub fn component_added<T: Component + Clone>(&self) -> Promise<(), T> {
+        wait(self.0, poll_added::<T>)
+    }
+
+    /// Creates promise that resolves with the clone of the `T` component when
+    /// it is changed or added to the entity.
+    pub fn component_changed<T: Component + Clone>(&self) -> Promise<(), T> {
+        wait(self.0, poll_changed::<T>)
+    }
+
+    /// Creates promise that resolves when the `T` component is removed from the
+    /// entity or the entity is despawned, immediately if there is no component.
+    pub fn component_removed<T: Component>(&self) -> Promise<(), ()> {
+        wait(self.0, poll_removed::<T>)
+    }
+}
+
+pub struct StatefulAsynEntity<S>(S, Entity);
+impl<S: 'static> StatefulAsynEntity<S> {
+    /// Stateful version of [`AsynEntity::component_added()`]
+    pub fn component_added<T: Component + Clone>(self) -> Promise<S, T> {
+        AsynEntity(self.1).component_added::<T>().with(self.0)
+    }
+    /// Stateful version of [`AsynEntity::component_changed()`]
+    pub fn component_changed<T: Component + Clone>(self) -> Promise<S, T> {
+        AsynEntity(self.1).component_changed::<T>().with(self.0)
+    }
+    /// Stateful version of [`AsynEntity::component_removed()`]
+    pub fn component_removed<T: Component>(self) -> Promise<S, ()> {
+        AsynEntity(self.1).component_removed::<T>().with(self.0)
+    }
+}
+
+pub trait EntityOpsExtension<S> {
+    fn entity(self, entity: Entity) -> StatefulAsynEntity<S>;
+}
+impl<S: 'static> EntityOpsExtension<S> for AsynOps<S> {
+    fn entity(self, entity: Entity) -> StatefulAsynEntity<S> {
+        StatefulAsynEntity(self.0, entity)
+    }
+}
+
+pub struct PromiseEntityPlugin;
+impl Plugin for PromiseEntityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityWaiters>();
+        app.add_systems(Update, process_entity_waiters);
+    }
+}
+
+/// Resolves the promise if the component is ready, returns `true` when the promise
+/// is resolved or discarded. Changes made after the `since` tick are detected.
+type EntityPoll = fn(&mut World, PromiseId, Entity, Tick) -> bool;
+
+/// Promises waiting for the components of the entities
+#[derive(Default)]
+pub struct EntityWaiters(Vec<(PromiseId, Entity, Tick, EntityPoll)>);
+impl Resource for EntityWaiters {}
+
+fn wait<R: 'static>(entity: Entity, poll: EntityPoll) -> Promise<(), R> {
+    Promise::register(
+        move |world, id| {
+            let since = world.change_tick();
+            if !poll(world, id, entity, since) {
+                world.resource_mut::<EntityWaiters>().0.push((id, entity, since, poll));
+            }
+        },
+        |world, id| {
+            world
+                .resource_mut::<EntityWaiters>()
+                .0
+                .retain(|(waiter, _, _, _)| waiter != &id);
+        },
+    )
+}
+
+/// Resolves the promise with the `T` component if its change ticks pass the `check`
+fn poll_ticks<T: Component + Clone>(
+    world: &mut World,
+    id: PromiseId,
+    entity: Entity,
+    check: impl FnOnce(&bevy::ecs::component::ComponentTicks, Tick) -> bool,
+) -> bool {
+    let this_run = world.read_change_tick();
+    let Some(entity) = world.get_entity(entity) else {
+        promise_discard::<(), T>(world, id);
+        return true;
+    };
+    let component = entity
+        .get_change_ticks::<T>()
+        .filter(|ticks| check(ticks, this_run))
+        .and_then(|_| entity.get::<T>().cloned());
+    let Some(component) = component else {
+        return false;
+    };
+    promise_resolve::<(), T>(world, id, (), component);
+    true
+}
+
+fn poll_added<T: Component + Clone>(world: &mut World, id: PromiseId, entity: Entity, since: Tick) -> bool {
+    poll_ticks::<T>(world, id, entity, |ticks, this_run| ticks.is_added(since, this_run))
+}
+
+fn poll_changed<T: Component + Clone>(world: &mut World, id: PromiseId, entity: Entity, since: Tick) -> bool {
+    poll_ticks::<T>(world, id, entity, |ticks, this_run| ticks.is_changed(since, this_run))
+}
+
+fn poll_removed<T: Component>(world: &mut World, id: PromiseId, entity: Entity, _: Tick) -> bool {
+    if world.get_entity(entity).is_some_and(|entity| entity.contains::<T>()) {
+        return false;
+    }
+    promise_resolve::<(), ()>(world, id, (), ());
+    true
+}
+
+fn process_entity_waiters(world: &mut World) {
+    let waiters = mem::take(&mut world.resource_mut::<EntityWaiters>().0);
+    let mut pending = Vec::with_capacity(waiters.len());
+    for (id, entity, since, poll) in waiters {
+        if !poll(world, id, entity, since) {
+            pending.push((id, entity, since, poll));
+        }
+    }
+    world.resource_mut::<EntityWaiters>().0.extend(pending);
+}
@@ -0,0 +1,107 @@
+//! Internal errors of the promise orchestration. Every error is logged and sent as
+//! [`PecsErrorEvent`], so the app could react to it instead of scraping the logs:
+//! ```ignore
+//! fn fail_on_pecs_errors(mut errors: EventReader<PecsErrorEvent>) {
+//!     for event in errors.read() {
+//!         panic!("{} in {}: {}", event.error.code(), event.promise, event.error);
+//!     }
+//! }
+//! ```
+use std::fmt;
+
+use super::*;
+
+/// Kind of the internal error, [`code()`][PecsError::code] is stable across releases
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PecsError {
+    /// The promise is discarded after it is resolved or discarded
+    DiscardCompleted { signature: String },
+    /// The promise is resolved after it is resolved or discarded, or it is never registered
+    ResolveCompleted { signature: String },
+    /// The awaited promise already has the resolve hook, it is chained somewhere else
+    ResolveAlreadyDefined { signature: String },
+    /// The fallible handler returned `Err`, the chain is discarded
+    HandlerFailed { location: String, message: String },
+    /// The plugin required by the promise is not added
+    PluginMissing { plugin: &'static str },
+    /// The event awaited by the promise is not registered with `add_event`
+    EventNotRegistered { event: &'static str },
+    /// The state switched by the promise is not registered with `init_state`
+    StateNotRegistered { state: &'static str },
+    /// The entity the promise operates on does not exist
+    EntityMissing { entity: Entity },
+    /// The promise lost the [`any_with()`][Promise::any_with] race with
+    /// [`AnyLosers::Reject`] and is discarded
+    AnyLoserRejected { winner: PromiseId },
+}
+
+impl PecsError {
+    /// Stable machine-readable code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            PecsError::DiscardCompleted { .. } => "PECS001",
+            PecsError::ResolveAlreadyDefined { .. } => "PECS002",
+            PecsError::HandlerFailed { .. } => "PECS003",
+            PecsError::PluginMissing { .. } => "PECS004",
+            PecsError::EventNotRegistered { .. } => "PECS005",
+            PecsError::StateNotRegistered { .. } => "PECS006",
+            PecsError::EntityMissing { .. } => "PECS007",
+            PecsError::ResolveCompleted { .. } => "PECS008",
+            PecsError::AnyLoserRejected { .. } => "PECS009",
+        }
+    }
+}
+
+impl fmt::Display for PecsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PecsError::DiscardCompleted { signature } => write!(f, "trying to discard complete {signature}"),
+            PecsError::ResolveCompleted { signature } => write!(f, "trying to resolve complete {signature}"),
+            PecsError::ResolveAlreadyDefined { signature } => {
+                write!(f, "misconfigured {signature}, resolve already defined")
+            }
+            PecsError::HandlerFailed { location, message } => {
+                write!(f, "handler at {location} failed, discarding the chain: {message}")
+            }
+            PecsError::PluginMissing { plugin } => write!(f, "{plugin} is not added"),
+            PecsError::EventNotRegistered { event } => write!(f, "{event} is not registered"),
+            PecsError::StateNotRegistered { state } => write!(f, "{state} is not registered"),
+            PecsError::EntityMissing { entity } => write!(f, "{entity:?} does not exist"),
+            PecsError::AnyLoserRejected { winner } => write!(f, "rejected, {winner} won the race"),
+        }
+    }
+}
+
+impl std::error::Error for PecsError {}
+
+/// Sent when the internal error occurs, requires `PecsPlugin`
+#[derive(Clone, Debug)]
+pub struct PecsErrorEvent {
+    /// The promise the error occurred in
+    pub promise: PromiseId,
+    /// Label of the chain the promise belongs to, if tracked by [`ChainLabels`][inspect::ChainLabels]
+    pub label: Option<&'static str>,
+    pub error: PecsError,
+}
+pecs_compat::impl_event!(PecsErrorEvent);
+
+/// `Promise<S, R>` signature used in the error messages
+pub(crate) fn signature<S: 'static, R: 'static>() -> String {
+    format!("Promise<{}, {}>", type_name::<S>(), type_name::<R>())
+}
+
+/// Logs the `error` of the promise `id` and sends it as [`PecsErrorEvent`]
+pub(crate) fn report(world: &mut World, id: PromiseId, error: PecsError) {
+    let label = inspect::chain_label(world, id);
+    match label {
+        Some(label) => error!("[{}] {id} \"{label}\": {error}", error.code()),
+        None => error!("[{}] {id}: {error}", error.code()),
+    }
+    if let Some(mut events) = world.get_resource_mut::<Events<PecsErrorEvent>>() {
+        events.send(PecsErrorEvent {
+            promise: id,
+            label,
+            error,
+        });
+    }
+}
@@ -0,0 +1,97 @@
+//! Promises counting Bevy events: resolve with the next event of the type, or after
+//! the `n`th event arrives, optionally collecting the events. Only events sent after
+//! the promise is started are counted.
+//! ```ignore
+//! fn boss_fight(mut commands: Commands) {
+//!     commands.add(asyn::event::count::<BossHit>(3).then(asyn!(_ => {
+//!         info!("Boss is defeated after 3 hits");
+//!     })));
+//! }
+//! ```
+use bevy::ecs::event::ManualEventReader;
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(event)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves after `n` events of type `E` are sent
+    pub fn count<E: Event>(n: usize) -> Promise<(), ()> {
+        wait::<E, ()>(n, |_| ()).map_result(|_| ())
+    }
+
+    /// Creates promise that resolves with the next event of type `E`
+    pub fn next<E: Event + Clone>() -> Promise<(), E> {
+        wait::<E, E>(1, E::clone).map_result(|mut events| events.remove(0))
+    }
+
+    /// Creates promise that resolves with the next `n` events of type `E`
+    pub fn collect<E: Event + Clone>(n: usize) -> Promise<(), Vec<E>> {
+        wait::<E, E>(n, E::clone)
+    }
+}
+
+pub struct PromiseEventPlugin;
+impl Plugin for PromiseEventPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventWaiters>();
+        app.add_systems(Update, process_event_waiters);
+    }
+}
+
+/// Reads new events, returns `true` when the promise is resolved
+type EventPoll = Box<dyn FnMut(&mut World, PromiseId) -> bool + Send + Sync>;
+
+/// Promises waiting for the events
+#[derive(Default)]
+pub struct EventWaiters(Vec<(PromiseId, EventPoll)>);
+impl Resource for EventWaiters {}
+
+fn wait<E: Event, T: 'static + Send + Sync>(n: usize, keep: fn(&E) -> T) -> Promise<(), Vec<T>> {
+    Promise::register(
+        move |world, id| {
+            if n == 0 {
+                promise_resolve::<(), Vec<T>>(world, id, (), vec![]);
+                return;
+            }
+            let Some(events) = world.get_resource::<Events<E>>() else {
+                let event = type_name::<E>();
+                errors::report(world, id, errors::PecsError::EventNotRegistered { event });
+                return;
+            };
+            let mut reader: ManualEventReader<E> = events.get_reader_current();
+            let mut kept = Vec::with_capacity(n);
+            world.resource_mut::<EventWaiters>().0.push((
+                id,
+                Box::new(move |world, id| {
+                    let events = world.resource::<Events<E>>();
+                    kept.extend(reader.read(events).take(n - kept.len()).map(keep));
+                    if kept.len() < n {
+                        return false;
+                    }
+                    promise_resolve::<(), Vec<T>>(world, id, (), mem::take(&mut kept));
+                    true
+                }),
+            ));
+        },
+        |world, id| {
+            world
+                .resource_mut::<EventWaiters>()
+                .0
+                .retain(|(waiter, _)| waiter != &id);
+        },
+    )
+}
+
+fn process_event_waiters(world: &mut World) {
+    let waiters = mem::take(&mut world.resource_mut::<EventWaiters>().0);
+    let mut pending = Vec::with_capacity(waiters.len());
+    for (id, mut poll) in waiters {
+        if !poll(world, id) {
+            pending.push((id, poll));
+        }
+    }
+    world.resource_mut::<EventWaiters>().0.extend(pending);
+}
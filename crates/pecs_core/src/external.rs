@@ -0,0 +1,299 @@
+//! Resolve promises from code running outside of the Bevy schedule: another
+//! async runtime (tokio, async-std), audio or network threads. The
+//! [`PromiseRemote`] handle is `Send` and resolves its promise on the next frame.
+//!
+//! Futures of the ecosystem crates could be awaited with [`Promise::from_future`], and
+//! promises could be awaited by the async code with [`Promise::into_future`]:
+//! ```ignore
+//! fn load_profile(mut commands: Commands, db: Res<Database>) {
+//!     let pool = db.pool.clone();
+//!     commands.add(
+//!         Promise::from_future(async move {
+//!             sqlx::query_as::<_, Profile>("SELECT * FROM profiles").fetch_one(&pool).await
+//!         })
+//!         .then(asyn!(_, profile => info!("Loaded {profile:?}"))),
+//!     );
+//! }
+//! ```
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::Task;
+use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+
+use super::*;
+
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise with the [`PromiseRemote`] handle resolving it.
+    /// ```ignore
+    /// fn setup(mut commands: Commands, runtime: Res<TokioRuntime>) {
+    ///     let (promise, remote) = asyn::external::remote::<String>();
+    ///     runtime.spawn(async move {
+    ///         let greeting = server::fetch_greeting().await;
+    ///         remote.resolve(greeting);
+    ///     });
+    ///     commands.add(promise.then(asyn!(_, greeting => {
+    ///         info!("Server says {greeting}");
+    ///     })));
+    /// }
+    /// ```
+    pub fn remote<R: 'static + Send>() -> (Promise<(), R>, PromiseRemote<R>) {
+        let remote = PromiseRemote(Arc::new(Mutex::new(RemoteSlot::Waiting)));
+        let register = remote.clone();
+        let discard = remote.clone();
+        let promise = Promise::register(
+            move |world, id| {
+                let Some(sender) = world.get_resource::<ExternalResolutions>().map(|r| r.sender()) else {
+                    let plugin = "PromiseExternalPlugin";
+                    errors::report(world, id, errors::PecsError::PluginMissing { plugin });
+                    return;
+                };
+                let mut slot = register.0.lock().unwrap();
+                match mem::replace(&mut *slot, RemoteSlot::Done) {
+                    RemoteSlot::Resolved(value) => {
+                        drop(slot);
+                        promise_resolve::<(), R>(world, id, (), value);
+                    }
+                    _ => *slot = RemoteSlot::Registered(id, sender),
+                }
+            },
+            move |_world, _id| {
+                *discard.0.lock().unwrap() = RemoteSlot::Done;
+            },
+        );
+        (promise, remote)
+    }
+}
+
+pub(crate) type ExternalResolve = Box<dyn FnOnce(&mut World) + Send>;
+
+enum RemoteSlot<R> {
+    Waiting,
+    Registered(PromiseId, Sender<ExternalResolve>),
+    Resolved(R),
+    Done,
+}
+
+/// Handle resolving the promise created with [`asyn::external::remote()`][asyn::remote]
+/// from any thread or runtime.
+pub struct PromiseRemote<R>(Arc<Mutex<RemoteSlot<R>>>);
+impl<R> Clone for PromiseRemote<R> {
+    fn clone(&self) -> Self {
+        PromiseRemote(self.0.clone())
+    }
+}
+
+/// Sending half of the [`Promise::channel()`], the [`PromiseRemote`] handle
+pub type PromiseSender<R> = PromiseRemote<R>;
+
+impl<R: 'static + Send> PromiseRemote<R> {
+    /// Resolve the promise with `value`. The promise is resolved by the
+    /// pecs system on the next frame. Does nothing if the promise is
+    /// already resolved or discarded.
+    pub fn resolve(&self, value: R) {
+        let mut slot = self.0.lock().unwrap();
+        match mem::replace(&mut *slot, RemoteSlot::Done) {
+            RemoteSlot::Waiting => *slot = RemoteSlot::Resolved(value),
+            RemoteSlot::Registered(id, sender) => {
+                let _ = sender.send(Box::new(move |world: &mut World| {
+                    if promise_is_pending::<(), R>(world, id) {
+                        promise_resolve::<(), R>(world, id, (), value);
+                    }
+                }));
+            }
+            resolved @ RemoteSlot::Resolved(_) => *slot = resolved,
+            RemoteSlot::Done => {}
+        }
+    }
+    /// `true` if the promise is resolved or discarded and
+    /// the external work could be stopped.
+    pub fn is_done(&self) -> bool {
+        matches!(*self.0.lock().unwrap(), RemoteSlot::Done)
+    }
+}
+
+impl Promise<(), ()> {
+    /// Creates promise that polls the `future` on the [`AsyncComputeTaskPool`] and resolves
+    /// with its output. Discarding the promise drops the future. Requires
+    /// [`PromiseExternalPlugin`], see [`external`][crate::external].
+    pub fn from_future<R: 'static + Send, F: 'static + Future<Output = R> + Send>(future: F) -> Promise<(), R> {
+        future_in(AsyncComputeTaskPool::get(), future)
+    }
+
+    /// Creates promise with the [`PromiseSender`] resolving it from another thread,
+    /// same as [`asyn::external::remote()`][asyn::remote].
+    /// ```ignore
+    /// fn listen_audio(mut commands: Commands, device: Res<AudioDevice>) {
+    ///     let (promise, sender) = Promise::channel::<f32>();
+    ///     device.on_peak(move |level| sender.resolve(level));
+    ///     commands.add(promise.then(asyn!(_, level => info!("Peak level: {level}"))));
+    /// }
+    /// ```
+    pub fn channel<R: 'static + Send>() -> (Promise<(), R>, PromiseSender<R>) {
+        asyn::remote()
+    }
+}
+
+/// Creates promise polling the `future` on the `pool`, see [`Promise::from_future`]
+pub(crate) fn future_in<R: 'static + Send, F: 'static + Future<Output = R> + Send>(
+    pool: &'static TaskPool,
+    future: F,
+) -> Promise<(), R> {
+    let (mut promise, remote) = asyn::remote::<R>();
+    let register = promise.register.take();
+    promise.register = Some(Box::new(move |world, id| {
+        if let Some(register) = register {
+            register(world, id)
+        }
+        let task = pool.spawn(async move { remote.resolve(future.await) });
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(mut futures) = world.get_resource_mut::<FutureTasks>() {
+            futures.0.insert(id, task);
+        }
+        #[cfg(target_arch = "wasm32")]
+        task.detach();
+    }));
+    let discard = promise.discard.take();
+    promise.discard = Some(Box::new(move |world, id| {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(mut futures) = world.get_resource_mut::<FutureTasks>() {
+            futures.0.remove(&id);
+        }
+        if let Some(discard) = discard {
+            discard(world, id)
+        }
+    }));
+    promise
+}
+
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
+    /// Start the promise with `commands` and return the future resolving with its state
+    /// and result, or with `None` if the promise is discarded. The future could be awaited
+    /// by the tasks of `bevy_tasks` pools or by any other runtime.
+    /// ```ignore
+    /// fn sync_save(mut commands: Commands) {
+    ///     let confirmed = asyn::ui::button(confirm).pressed().into_future(&mut commands);
+    ///     IoTaskPool::get()
+    ///         .spawn(async move {
+    ///             if confirmed.await.is_some() {
+    ///                 cloud::upload_save().await;
+    ///             }
+    ///         })
+    ///         .detach();
+    /// }
+    /// ```
+    pub fn into_future(self, commands: &mut Commands) -> PromiseFuture<S, R> {
+        let slot = Arc::new(Mutex::new(FutureSlot {
+            output: None,
+            done: false,
+            waker: None,
+        }));
+        let future = PromiseFuture(slot.clone());
+        let mut promise = self.map(move |state| (state, slot)).then(asyn!(s, r => {
+            let (state, slot) = s.value;
+            FutureSlot::complete(&slot, Some((state, r)));
+        }));
+        let discard = promise.discard.take();
+        let discarded = future.0.clone();
+        promise.discard = Some(Box::new(move |world, id| {
+            if let Some(discard) = discard {
+                discard(world, id)
+            }
+            FutureSlot::complete(&discarded, None);
+        }));
+        commands.add(promise);
+        future
+    }
+}
+
+struct FutureSlot<T> {
+    output: Option<T>,
+    done: bool,
+    waker: Option<Waker>,
+}
+
+impl<T> FutureSlot<T> {
+    fn complete(slot: &Mutex<FutureSlot<T>>, output: Option<T>) {
+        let mut slot = slot.lock().unwrap();
+        if slot.done {
+            return;
+        }
+        slot.output = output;
+        slot.done = true;
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Future of the promise started with [`Promise::into_future`]
+pub struct PromiseFuture<S, R>(Arc<Mutex<FutureSlot<(S, R)>>>);
+
+impl<S, R> Future for PromiseFuture<S, R> {
+    type Output = Option<(S, R)>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.0.lock().unwrap();
+        if slot.done {
+            return Poll::Ready(slot.output.take());
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Tasks polling the futures of [`Promise::from_future`], dropped when the promise is discarded
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+pub struct FutureTasks(HashMap<PromiseId, Task<()>>);
+#[cfg(not(target_arch = "wasm32"))]
+impl Resource for FutureTasks {}
+
+/// Resolutions sent by [`PromiseRemote`] handles
+pub struct ExternalResolutions {
+    sender: Sender<ExternalResolve>,
+    receiver: Receiver<ExternalResolve>,
+}
+impl Resource for ExternalResolutions {}
+impl ExternalResolutions {
+    /// Sender for resolutions produced by other threads, they are
+    /// applied by the pecs system on the next frame
+    pub(crate) fn sender(&self) -> Sender<ExternalResolve> {
+        self.sender.clone()
+    }
+    /// Number of resolutions waiting for the next frame
+    pub(crate) fn len(&self) -> usize {
+        self.receiver.len()
+    }
+}
+impl Default for ExternalResolutions {
+    fn default() -> Self {
+        let (sender, receiver) = unbounded();
+        ExternalResolutions { sender, receiver }
+    }
+}
+
+pub struct PromiseExternalPlugin;
+impl Plugin for PromiseExternalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExternalResolutions>();
+        #[cfg(not(target_arch = "wasm32"))]
+        app.init_resource::<FutureTasks>();
+        app.add_systems(Update, process_external);
+    }
+}
+
+fn process_external(world: &mut World) {
+    let resolutions: Vec<_> = world.resource::<ExternalResolutions>().receiver.try_iter().collect();
+    for resolve in resolutions {
+        resolve(world);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(mut futures) = world.get_resource_mut::<FutureTasks>() {
+        futures.0.retain(|_, task| !task.is_finished());
+    }
+}
@@ -0,0 +1,66 @@
+//! File system promises: read and write the files on the
+//! [`IoTaskPool`][bevy::tasks::IoTaskPool] instead of blocking the frame inside
+//! the `asyn!` handlers:
+//! ```ignore
+//! fn save_game(mut commands: Commands, progress: Res<GameProgress>) {
+//!     let bytes = progress.to_bytes();
+//!     commands.add(
+//!         asyn::fs::write("saves/slot1.sav", bytes)
+//!             .then(asyn!(_, result => match result {
+//!                 Ok(()) => info!("The game is saved"),
+//!                 Err(e) => error!("Can't save the game: {e}"),
+//!             })),
+//!     );
+//! }
+//!
+//! fn load_game(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::fs::read("saves/slot1.sav").then_ok(asyn!(_, bytes, mut progress: ResMut<GameProgress> => {
+//!             *progress = GameProgress::from_bytes(&bytes);
+//!         })),
+//!     );
+//! }
+//! ```
+//! The operations are polled as the [`Promise::from_future`] futures, so they require
+//! [`PromiseExternalPlugin`][crate::external::PromiseExternalPlugin]. There is no file
+//! system on wasm, the promises resolve with the [`io::ErrorKind::Unsupported`] error.
+use std::io;
+use std::path::PathBuf;
+
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(fs)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that reads the whole file at `path` and resolves with its contents
+    pub fn read<P: Into<PathBuf>>(path: P) -> Promise<(), io::Result<Vec<u8>>> {
+        let path = path.into();
+        run(move || std::fs::read(path))
+    }
+
+    /// Creates promise that writes the `bytes` to the file at `path`, replacing its
+    /// contents, and resolves when the file is written
+    pub fn write<P: Into<PathBuf>>(path: P, bytes: Vec<u8>) -> Promise<(), io::Result<()>> {
+        let path = path.into();
+        run(move || std::fs::write(path, bytes))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run<R: 'static + Send, F: 'static + FnOnce() -> io::Result<R> + Send>(func: F) -> Promise<(), io::Result<R>> {
+    external::future_in(bevy::tasks::IoTaskPool::get(), async move { func() })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn run<R: 'static + Send, F: 'static + FnOnce() -> io::Result<R> + Send>(_: F) -> Promise<(), io::Result<R>> {
+    Promise::register(
+        |world, id| {
+            let error = io::Error::new(io::ErrorKind::Unsupported, "file system is not available on wasm");
+            promise_resolve::<(), io::Result<R>>(world, id, (), Err(error));
+        },
+        |_, _| {},
+    )
+}
@@ -0,0 +1,209 @@
+//! Promise groups: mark promises with [`Promise::in_group`] and discard every
+//! pending member of the group with
+//! [`commands.promises().discard_group(group)`][PromiseGroupsCommands::discard_group].
+//!
+//! [`GroupProgress`] counts resolved, pending and failed promises of every group,
+//! so HUD systems could show the progress of background activity:
+//! ```ignore
+//! fn download_progress(progress: Res<GroupProgress>, mut text: Query<&mut Text, With<DownloadsLabel>>) {
+//!     let downloads = progress.get("downloads");
+//!     text.single_mut().sections[0].value = format!("{:.0}%", downloads.fraction() * 100.);
+//! }
+//! ```
+use super::*;
+
+struct Member {
+    promise: PromiseId,
+    pending: fn(&World, PromiseId) -> bool,
+    discard: fn(&mut World, PromiseId),
+}
+
+/// Pending promises by their groups
+#[derive(Default)]
+pub struct PromiseGroups(HashMap<&'static str, Vec<Member>>);
+impl Resource for PromiseGroups {}
+
+impl PromiseGroups {
+    /// Discard every pending promise in the `group`, returns the number of discarded promises
+    pub fn discard(world: &mut World, group: &'static str) -> usize {
+        let Some(members) = world
+            .get_resource_mut::<PromiseGroups>()
+            .and_then(|mut groups| groups.0.remove(group))
+        else {
+            return 0;
+        };
+        let mut discarded = 0;
+        let mut resolved = 0;
+        for member in members {
+            if (member.pending)(world, member.promise) {
+                (member.discard)(world, member.promise);
+                discarded += 1;
+            } else {
+                resolved += 1;
+            }
+        }
+        world
+            .get_resource_or_insert_with(GroupProgress::default)
+            .0
+            .entry(group)
+            .or_default()
+            .resolved += resolved;
+        discarded
+    }
+}
+
+/// Removes settled members of the `group` and counts them as resolved:
+/// discarded members are removed and counted when they are discarded
+fn prune(world: &mut World, group: &'static str, members: &mut Vec<Member>) {
+    let before = members.len();
+    members.retain(|member| (member.pending)(world, member.promise));
+    let mut progress = world.get_resource_or_insert_with(GroupProgress::default);
+    let progress = progress.0.entry(group).or_default();
+    progress.resolved += before - members.len();
+    progress.pending = members.len();
+}
+
+/// Resolved, pending and failed promises of the group
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub resolved: usize,
+    pub pending: usize,
+    /// Promises discarded before they are resolved
+    pub failed: usize,
+}
+
+impl Progress {
+    pub fn total(&self) -> usize {
+        self.resolved + self.pending + self.failed
+    }
+
+    /// Part of the settled promises from `0.` to `1.`, `1.` for the empty group
+    pub fn fraction(&self) -> f32 {
+        if self.total() == 0 {
+            1.
+        } else {
+            (self.resolved + self.failed) as f32 / self.total() as f32
+        }
+    }
+}
+
+/// [`Progress`] of the promise groups. Pending promises are counted when they are
+/// registered, resolved promises are counted at the end of the frame.
+#[derive(Default)]
+pub struct GroupProgress(HashMap<&'static str, Progress>);
+impl Resource for GroupProgress {}
+
+impl GroupProgress {
+    /// Progress of the `group`, empty if the group has never been used
+    pub fn get(&self, group: &str) -> Progress {
+        self.0.get(group).copied().unwrap_or_default()
+    }
+
+    /// Forget the settled promises of the `group`, for example
+    /// when the next batch of downloads starts
+    pub fn reset(&mut self, group: &str) {
+        if let Some(progress) = self.0.get_mut(group) {
+            progress.resolved = 0;
+            progress.failed = 0;
+        }
+    }
+}
+
+pub struct PromiseGroupsPlugin;
+impl Plugin for PromiseGroupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PromiseGroups>();
+        app.init_resource::<GroupProgress>();
+        app.add_systems(Last, update_group_progress);
+    }
+}
+
+fn update_group_progress(world: &mut World) {
+    let mut groups = mem::take(&mut world.resource_mut::<PromiseGroups>().0);
+    for (group, members) in groups.iter_mut() {
+        prune(world, group, members);
+    }
+    world.resource_mut::<PromiseGroups>().0.extend(groups);
+}
+
+impl<S: 'static, R: 'static> Promise<S, R> {
+    /// Add the promise to the `group`, so it could be discarded with the rest of
+    /// the group by [`commands.promises().discard_group(group)`][PromiseGroupsCommands::discard_group]
+    /// ```ignore
+    /// fn start_battle(mut commands: Commands) {
+    ///     commands.add(
+    ///         Promise::repeat((), asyn!(state => {
+    ///             state.asyn().timeout(1.).with_result(Repeat::forever())
+    ///         }))
+    ///         .in_group("battle"),
+    ///     );
+    /// }
+    ///
+    /// fn leave_battle(mut commands: Commands) {
+    ///     commands.promises().discard_group("battle");
+    /// }
+    /// ```
+    pub fn in_group(mut self, group: &'static str) -> Self {
+        let register = self.register.take();
+        let discard = self.discard.take();
+        self.register = Some(Box::new(move |world, id| {
+            let mut groups = world.get_resource_or_insert_with(PromiseGroups::default);
+            let mut members = mem::take(groups.0.entry(group).or_default());
+            members.push(Member {
+                promise: id,
+                pending: promise_is_pending::<S, R>,
+                discard: promise_discard::<S, R>,
+            });
+            prune(world, group, &mut members);
+            world.resource_mut::<PromiseGroups>().0.insert(group, members);
+            if let Some(register) = register {
+                register(world, id)
+            }
+        }));
+        self.discard = Some(Box::new(move |world, id| {
+            if promise_is_pending::<S, R>(world, id) {
+                if let Some(mut groups) = world.get_resource_mut::<PromiseGroups>() {
+                    if let Some(members) = groups.0.get_mut(group) {
+                        members.retain(|member| member.promise != id);
+                    }
+                }
+                let mut progress = world.get_resource_or_insert_with(GroupProgress::default);
+                let progress = progress.0.entry(group).or_default();
+                progress.pending = progress.pending.saturating_sub(1);
+                progress.failed += 1;
+            }
+            if let Some(discard) = discard {
+                discard(world, id)
+            }
+        }));
+        self
+    }
+}
+
+/// Discards every pending promise in the group
+pub struct DiscardGroupCommand(pub &'static str);
+impl Command for DiscardGroupCommand {
+    fn apply(self, world: &mut World) {
+        PromiseGroups::discard(world, self.0);
+    }
+}
+
+pub struct PromiseGroupsCommands<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+}
+impl<'w, 's, 'a> PromiseGroupsCommands<'w, 's, 'a> {
+    /// Discard every pending promise added to the `group` with [`Promise::in_group`]
+    pub fn discard_group(&mut self, group: &'static str) -> &mut Self {
+        self.commands.add(DiscardGroupCommand(group));
+        self
+    }
+}
+
+pub trait PromiseGroupsCommandsExtension<'w, 's> {
+    fn promises<'a>(&'a mut self) -> PromiseGroupsCommands<'w, 's, 'a>;
+}
+impl<'w, 's> PromiseGroupsCommandsExtension<'w, 's> for Commands<'w, 's> {
+    fn promises<'a>(&'a mut self) -> PromiseGroupsCommands<'w, 's, 'a> {
+        PromiseGroupsCommands { commands: self }
+    }
+}
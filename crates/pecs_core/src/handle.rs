@@ -0,0 +1,113 @@
+//! Cancellation of the running chains from outside. [`PromiseHandle`] points to the
+//! outermost promise of the chain, discarding it discards every pending promise
+//! of the chain, including in-flight timers and requests:
+//! ```ignore
+//! #[derive(Component)]
+//! struct Countdown(PromiseHandle);
+//!
+//! fn open_menu(mut commands: Commands) {
+//!     let handle = commands
+//!         .promise(|| ())
+//!         .then(asyn!(state => state.asyn().timeout(10.)))
+//!         .then(asyn!(_ => info!("Too slow!")))
+//!         .handle();
+//!     commands.spawn((MenuBundle::default(), Countdown(handle)));
+//! }
+//!
+//! fn close_menu(mut commands: Commands, menu: Query<(Entity, &Countdown)>) {
+//!     let (entity, countdown) = menu.single();
+//!     commands.promise(countdown.0).discard();
+//!     commands.entity(entity).despawn_recursive();
+//! }
+//! ```
+use super::*;
+
+/// Lightweight handle of the registered chain, see [`handle`][self]
+#[derive(Clone, Copy)]
+pub struct PromiseHandle {
+    id: PromiseId,
+    pending: fn(&World, PromiseId) -> bool,
+    discard: fn(&mut World, PromiseId),
+}
+
+impl PromiseHandle {
+    /// Id of the outermost promise of the chain
+    pub fn id(&self) -> PromiseId {
+        self.id
+    }
+
+    /// Returns `true` if the chain is registered and neither resolved nor discarded yet
+    pub fn is_pending(&self, world: &World) -> bool {
+        (self.pending)(world, self.id)
+    }
+
+    /// Discards the chain if it is still pending, returns `true` if discarded
+    pub fn discard(&self, world: &mut World) -> bool {
+        if !self.is_pending(world) {
+            return false;
+        }
+        (self.discard)(world, self.id);
+        true
+    }
+}
+
+impl std::fmt::Debug for PromiseHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PromiseHandle({})", self.id)
+    }
+}
+
+impl PromiseCommandsArg for PromiseHandle {}
+
+impl<S: 'static, R: 'static> Promise<S, R> {
+    /// Returns the [`PromiseHandle`] the promise could be discarded with after it is registered
+    pub fn handle(&self) -> PromiseHandle {
+        PromiseHandle {
+            id: self.id,
+            pending: promise_is_pending::<S, R>,
+            discard: promise_discard::<S, R>,
+        }
+    }
+}
+
+impl<'w, 's, 'a, S: 'static, R: 'static> PromiseCommands<'w, 's, 'a, Promise<S, R>> {
+    /// Finish the chain and return its [`PromiseHandle`], the chain is registered as usual
+    pub fn handle(self) -> PromiseHandle {
+        self.data.as_ref().unwrap().handle()
+    }
+}
+
+impl<'w, 's, 'a, S: 'static, R: 'static> PromiseChain<'w, 's, 'a, S, R> {
+    /// Finish the chain and return its [`PromiseHandle`], the chain is registered as usual
+    pub fn handle(self) -> PromiseHandle {
+        self.promise.as_ref().unwrap().handle()
+    }
+}
+
+impl<'w, 's, 'a> PromiseCommands<'w, 's, 'a, PromiseHandle> {
+    /// Discard the chain if it is still pending
+    pub fn discard(&mut self) {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let handle = mem::take(&mut self.data).unwrap();
+        commands.add(DiscardPromiseCommand(handle));
+    }
+}
+
+impl<'w, 's> PromiseCommandsExtension<'w, 's, PromiseHandle> for Commands<'w, 's> {
+    /// Create command for discarding the chain by [`PromiseHandle`]
+    fn promise<'a>(&'a mut self, arg: PromiseHandle) -> PromiseCommands<'w, 's, 'a, PromiseHandle> {
+        PromiseCommands {
+            data: Some(arg),
+            commands: Some(self),
+            finally: None,
+        }
+    }
+}
+
+/// Discards the chain by its [`PromiseHandle`] if it is still pending
+pub struct DiscardPromiseCommand(pub PromiseHandle);
+impl Command for DiscardPromiseCommand {
+    fn apply(self, world: &mut World) {
+        self.0.discard(world);
+    }
+}
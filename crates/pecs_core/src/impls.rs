@@ -1,9 +1,10 @@
 use crate::*;
+use std::fmt::Display;
 
 impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
     type Promise<S2: 'static, R2: 'static> = Promise<S2, R2>;
     fn then<S2: 'static, R2: 'static>(mut self, func: Asyn![S, R => S2, R2]) -> Promise<S2, R2> {
-        let id = PromiseId::new();
+        let (id, slot) = PromiseRegistry::<S2, R2>::allocate();
         let discard = mem::take(&mut self.discard);
         let self_id = self.id;
         self.discard = Some(Box::new(move |world, _id| {
@@ -11,27 +12,99 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
         }));
         self.resolve = Some(Box::new(move |world, state, result| {
             let pr = func.run((PromiseState::new(state), result), world).into();
-            match pr {
-                PromiseResult::Resolve(s, r) => promise_resolve::<S2, R2>(world, id, s, r),
-                PromiseResult::Await(mut p) => {
-                    if p.resolve.is_some() {
-                        error!(
-                            "Misconfigured {}<{}, {}>, resolve already defined",
-                            p.id,
-                            type_name::<S2>(),
-                            type_name::<R2>(),
-                        );
-                        return;
-                    }
-                    p.resolve = Some(Box::new(move |world, s, r| {
-                        promise_resolve::<S2, R2>(world, id, s, r);
-                    }));
-                    promise_register::<S2, R2>(world, p);
+            promise_resolve_result(world, id, pr);
+        }));
+        Promise {
+            id,
+            _slot: slot,
+            register: Some(Box::new(move |world, _id| {
+                promise_register::<S, R>(world, self);
+            })),
+            discard: Some(Box::new(move |world, _id| {
+                if let Some(discard) = discard {
+                    discard(world, self_id);
+                }
+                promise_forget::<S, R>(world, self_id);
+            })),
+            resolve: None,
+        }
+    }
+
+    fn then_try<S2: 'static, R2: 'static, E: 'static + Display>(
+        mut self,
+        func: Asyn<(PromiseState<S>, R), Result<impl 'static + Into<PromiseResult<S2, R2>>, E>, impl PromiseParams>,
+    ) -> Promise<S2, R2> {
+        let (id, slot) = PromiseRegistry::<S2, R2>::allocate();
+        let discard = mem::take(&mut self.discard);
+        let self_id = self.id;
+        self.discard = Some(Box::new(move |world, _id| {
+            promise_discard::<S2, R2>(world, id);
+        }));
+        self.resolve = Some(Box::new(move |world, state, result| {
+            let location = func.location;
+            match func.run((PromiseState::new(state), result), world) {
+                Ok(pr) => promise_resolve_result(world, id, pr.into()),
+                Err(e) => {
+                    let error = PecsError::HandlerFailed {
+                        location: location.to_string(),
+                        message: e.to_string(),
+                    };
+                    errors::report(world, id, error);
+                    promise_discard::<S2, R2>(world, id);
                 }
             }
         }));
         Promise {
             id,
+            _slot: slot,
+            register: Some(Box::new(move |world, _id| {
+                promise_register::<S, R>(world, self);
+            })),
+            discard: Some(Box::new(move |world, _id| {
+                if let Some(discard) = discard {
+                    discard(world, self_id);
+                }
+                promise_forget::<S, R>(world, self_id);
+            })),
+            resolve: None,
+        }
+    }
+
+    fn then_detached<S2: 'static + Send, R2: 'static + Send>(
+        self,
+        func: Asyn<(PromiseState<S>, R), (S2, R2), ()>,
+    ) -> Self::Promise<S2, R2>
+    where
+        S: Send,
+        R: Send,
+    {
+        self.run_detached(func)
+    }
+
+    fn then_measure<S2: 'static, R2: 'static>(mut self, func: Asyn![S, R => S2, R2]) -> Promise<S2, (R2, Duration)> {
+        let (id, slot) = PromiseRegistry::<S2, (R2, Duration)>::allocate();
+        let discard = mem::take(&mut self.discard);
+        let self_id = self.id;
+        self.discard = Some(Box::new(move |world, _id| {
+            promise_discard::<S2, (R2, Duration)>(world, id);
+        }));
+        self.resolve = Some(Box::new(move |world, state, result| {
+            let (start, _) = timer::now(world);
+            let pr = match func.run((PromiseState::new(state), result), world).into() {
+                PromiseResult::Resolve(s, r) => PromiseResult::Resolve(s, (r, Duration::ZERO)),
+                PromiseResult::Await(p) => {
+                    PromiseResult::Await(p.map(move |s| (s, start)).then(asyn!(s, r, world: &World => {
+                        let (state, start) = s.value;
+                        let elapsed = Duration::from_secs_f32((timer::now(world).0 - start).max(0.));
+                        PromiseResult::Resolve(state, (r, elapsed))
+                    })))
+                }
+            };
+            promise_resolve_result(world, id, pr);
+        }));
+        Promise {
+            id,
+            _slot: slot,
             register: Some(Box::new(move |world, _id| {
                 promise_register::<S, R>(world, self);
             })),
@@ -39,13 +112,14 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
                 if let Some(discard) = discard {
                     discard(world, self_id);
                 }
+                promise_forget::<S, R>(world, self_id);
             })),
             resolve: None,
         }
     }
 
     fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
-        let id = PromiseId::new();
+        let (id, slot) = PromiseRegistry::<S, R2>::allocate();
         let discard = mem::take(&mut self.discard);
         let self_id = self.id;
         self.discard = Some(Box::new(move |world, _id| {
@@ -57,6 +131,7 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
         }));
         Promise {
             id,
+            _slot: slot,
             register: Some(Box::new(move |world, _id| {
                 promise_register::<S, R>(world, self);
             })),
@@ -64,6 +139,7 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
                 if let Some(discard) = discard {
                     discard(world, self_id);
                 }
+                promise_forget::<S, R>(world, self_id);
             })),
             resolve: None,
         }
@@ -72,7 +148,7 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
         self.map_result(|_| value)
     }
     fn map<S2: 'static, F: 'static + FnOnce(S) -> S2>(mut self, map: F) -> Self::Promise<S2, R> {
-        let id = PromiseId::new();
+        let (id, slot) = PromiseRegistry::<S2, R>::allocate();
         let discard = mem::take(&mut self.discard);
         let self_id = self.id;
         self.discard = Some(Box::new(move |world, _id| {
@@ -84,6 +160,7 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
         }));
         Promise {
             id,
+            _slot: slot,
             register: Some(Box::new(move |world, _id| {
                 promise_register::<S, R>(world, self);
             })),
@@ -91,6 +168,7 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
                 if let Some(discard) = discard {
                     discard(world, self_id);
                 }
+                promise_forget::<S, R>(world, self_id);
             })),
             resolve: None,
         }
@@ -98,6 +176,52 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
     fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R> {
         self.map(|_| state)
     }
+    fn at_least(self, seconds: f32) -> Self::Promise<S, R> {
+        let segment = self.then(asyn!(s, r => PromiseResult::Resolve((), (s.value, r))));
+        Promise::all((segment, timer::timeout(seconds))).then(asyn!(_, r => {
+            let ((state, result), _) = r;
+            PromiseResult::Resolve(state, result)
+        }))
+    }
+    fn after(self, prerequisite: &Completion) -> Self::Promise<S, R> {
+        self.after_completion(prerequisite)
+    }
+    fn after_all<'c, I: IntoIterator<Item = &'c Completion>>(self, prerequisites: I) -> Self::Promise<S, R> {
+        self.after_completions(prerequisites)
+    }
+    fn on_progress(self, func: Asyn![(), f32 => (), ()]) -> Self::Promise<S, R> {
+        self.observe_progress(func)
+    }
+    fn finally(self, func: Asyn![(), Settled<(S, R)> => (), ()]) -> Self::Promise<(), ()> {
+        // the func runs once: either when the chain resolves or when it is discarded
+        let func = Arc::new(Mutex::new(Some(func)));
+        let on_discard = func.clone();
+        let mut promise = self
+            .map(move |state| (state, func))
+            .then(asyn!(s, r, world: &mut World => {
+                let (state, func) = s.value;
+                let Some(func) = func.lock().unwrap().take() else {
+                    return PromiseResult::Resolve((), ());
+                };
+                let settled = Settled::Resolved((state, r));
+                let result: PromiseResult<(), ()> = func.run((PromiseState::new(()), settled), world).into();
+                result
+            }));
+        let discard = promise.discard.take();
+        promise.discard = Some(Box::new(move |world, id| {
+            if let Some(discard) = discard {
+                discard(world, id)
+            }
+            let Some(func) = on_discard.lock().unwrap().take() else {
+                return;
+            };
+            let result: PromiseResult<(), ()> = func.run((PromiseState::new(()), Settled::Discarded), world).into();
+            if let PromiseResult::Await(promise) = result {
+                promise_register::<(), ()>(world, promise);
+            }
+        }));
+        promise
+    }
 }
 impl<S: 'static> PromiseLike<S> for Promise<S, ()> {
     fn then_repeat<R2: 'static>(self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
@@ -106,6 +230,12 @@ impl<S: 'static> PromiseLike<S> for Promise<S, ()> {
             Promise::repeat(state, func)
         }))
     }
+    fn then_fold<A: 'static, R2: 'static>(self, init: A, func: Asyn![S, A => S, Fold<A, R2>]) -> Self::Promise<S, R2> {
+        self.map(|state| (state, init, func)).then(asyn!(s, _ => {
+            let (state, init, func) = s.value;
+            Promise::fold(state, init, func)
+        }))
+    }
     fn all<A: 'static + AllPromises>(self, all: A) -> Self::Promise<S, A::Result> {
         self.map(|s| (s, all)).then(asyn!(state => {
             let (state, all) = state.value;
@@ -131,6 +261,39 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLikeBase<S, ()> for Promis
             promise: Some(Promise::new(new_state(), asyn!(s => s)).then(func)),
         }
     }
+    fn then_try<S2: 'static, R2: 'static, E: 'static + Display>(
+        mut self,
+        func: Asyn<(PromiseState<S>, ()), Result<impl 'static + Into<PromiseResult<S2, R2>>, E>, impl PromiseParams>,
+    ) -> Self::Promise<S2, R2> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(Promise::new(new_state(), asyn!(s => s)).then_try(func)),
+        }
+    }
+    fn then_detached<S2: 'static + Send, R2: 'static + Send>(
+        mut self,
+        func: Asyn<(PromiseState<S>, ()), (S2, R2), ()>,
+    ) -> Self::Promise<S2, R2>
+    where
+        S: Send,
+    {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(Promise::new(new_state(), asyn!(s => s)).run_detached(func)),
+        }
+    }
+    fn then_measure<S2: 'static, R2: 'static>(mut self, func: Asyn![S => S2, R2]) -> Self::Promise<S2, (R2, Duration)> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(Promise::new(new_state(), asyn!(s => s)).then_measure(func)),
+        }
+    }
     fn map_result<R2: 'static, M: 'static + FnOnce(()) -> R2>(mut self, map: M) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
@@ -159,6 +322,46 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLikeBase<S, ()> for Promis
     fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, ()> {
         self.map(|_| state)
     }
+    fn at_least(mut self, seconds: f32) -> Self::Promise<S, ()> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(timer::timeout(seconds).with(new_state())),
+        }
+    }
+    fn after(mut self, prerequisite: &Completion) -> Self::Promise<S, ()> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(prerequisite.wait().with(new_state())),
+        }
+    }
+    fn after_all<'c, I: IntoIterator<Item = &'c Completion>>(mut self, prerequisites: I) -> Self::Promise<S, ()> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(Promise::new(new_state(), asyn!(s => s)).after_completions(prerequisites)),
+        }
+    }
+    fn on_progress(mut self, func: Asyn![(), f32 => (), ()]) -> Self::Promise<S, ()> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(Promise::new(new_state(), asyn!(s => s)).observe_progress(func)),
+        }
+    }
+    fn finally(mut self, func: Asyn![(), Settled<(S, ())> => (), ()]) -> Self::Promise<(), ()> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(Promise::new(new_state(), asyn!(s => s)).finally(func)),
+        }
+    }
 }
 
 impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLike<S> for PromiseCommands<'w, 's, 'a, F> {
@@ -170,6 +373,18 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLike<S> for PromiseCommand
             promise: Some(Promise::repeat(new_state(), func)),
         }
     }
+    fn then_fold<A: 'static, R2: 'static>(
+        mut self,
+        init: A,
+        func: Asyn![S, A => S, Fold<A, R2>],
+    ) -> Self::Promise<S, R2> {
+        let commands = mem::take(&mut self.commands);
+        let new_state = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(Promise::fold(new_state(), init, func)),
+        }
+    }
     fn all<A: 'static + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
@@ -198,6 +413,43 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseComman
             promise: Some(promise.then(func)),
         }
     }
+    fn then_try<S2: 'static, R2: 'static, E: 'static + Display>(
+        mut self,
+        func: Asyn<(PromiseState<S>, R), Result<impl 'static + Into<PromiseResult<S2, R2>>, E>, impl PromiseParams>,
+    ) -> Self::Promise<S2, R2> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.then_try(func)),
+        }
+    }
+    fn then_detached<S2: 'static + Send, R2: 'static + Send>(
+        mut self,
+        func: Asyn<(PromiseState<S>, R), (S2, R2), ()>,
+    ) -> Self::Promise<S2, R2>
+    where
+        S: Send,
+        R: Send,
+    {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.run_detached(func)),
+        }
+    }
+    fn then_measure<S2: 'static, R2: 'static>(
+        mut self,
+        func: Asyn![S, R => S2, R2],
+    ) -> Self::Promise<S2, (R2, Duration)> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.then_measure(func)),
+        }
+    }
     fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
@@ -220,6 +472,46 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseComman
     fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R> {
         self.map(|_| state)
     }
+    fn at_least(mut self, seconds: f32) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.at_least(seconds)),
+        }
+    }
+    fn after(mut self, prerequisite: &Completion) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.after_completion(prerequisite)),
+        }
+    }
+    fn after_all<'c, I: IntoIterator<Item = &'c Completion>>(mut self, prerequisites: I) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.after_completions(prerequisites)),
+        }
+    }
+    fn on_progress(mut self, func: Asyn![(), f32 => (), ()]) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.observe_progress(func)),
+        }
+    }
+    fn finally(mut self, func: Asyn![(), Settled<(S, R)> => (), ()]) -> Self::Promise<(), ()> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.finally(func)),
+        }
+    }
 }
 impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseCommands<'w, 's, 'a, Promise<S, ()>> {
     fn then_repeat<R2: 'static>(mut self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
@@ -230,6 +522,18 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseCommands<'w, 's, 'a, Prom
             promise: Some(promise.then_repeat(func)),
         }
     }
+    fn then_fold<A: 'static, R2: 'static>(
+        mut self,
+        init: A,
+        func: Asyn![S, A => S, Fold<A, R2>],
+    ) -> Self::Promise<S, R2> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.then_fold(init, func)),
+        }
+    }
     fn all<A: 'static + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
@@ -258,6 +562,43 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseChain<
             promise: Some(promise.then(func)),
         }
     }
+    fn then_try<S2: 'static, R2: 'static, E: 'static + Display>(
+        mut self,
+        func: Asyn<(PromiseState<S>, R), Result<impl 'static + Into<PromiseResult<S2, R2>>, E>, impl PromiseParams>,
+    ) -> Self::Promise<S2, R2> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.then_try(func)),
+        }
+    }
+    fn then_detached<S2: 'static + Send, R2: 'static + Send>(
+        mut self,
+        func: Asyn<(PromiseState<S>, R), (S2, R2), ()>,
+    ) -> Self::Promise<S2, R2>
+    where
+        S: Send,
+        R: Send,
+    {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.run_detached(func)),
+        }
+    }
+    fn then_measure<S2: 'static, R2: 'static>(
+        mut self,
+        func: Asyn![S, R => S2, R2],
+    ) -> Self::Promise<S2, (R2, Duration)> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.then_measure(func)),
+        }
+    }
     fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
@@ -280,6 +621,46 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseChain<
     fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R> {
         self.map(|_| state)
     }
+    fn at_least(mut self, seconds: f32) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.at_least(seconds)),
+        }
+    }
+    fn after(mut self, prerequisite: &Completion) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.after_completion(prerequisite)),
+        }
+    }
+    fn after_all<'c, I: IntoIterator<Item = &'c Completion>>(mut self, prerequisites: I) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.after_completions(prerequisites)),
+        }
+    }
+    fn on_progress(mut self, func: Asyn![(), f32 => (), ()]) -> Self::Promise<S, R> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.observe_progress(func)),
+        }
+    }
+    fn finally(mut self, func: Asyn![(), Settled<(S, R)> => (), ()]) -> Self::Promise<(), ()> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.finally(func)),
+        }
+    }
 }
 
 impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()> {
@@ -291,6 +672,18 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()>
             promise: Some(promise.then_repeat(func)),
         }
     }
+    fn then_fold<A: 'static, R2: 'static>(
+        mut self,
+        init: A,
+        func: Asyn![S, A => S, Fold<A, R2>],
+    ) -> Self::Promise<S, R2> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.then_fold(init, func)),
+        }
+    }
     fn all<A: 'static + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
@@ -308,3 +701,247 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()>
         }
     }
 }
+
+impl<S: 'static, T: 'static> PromiseLikeOption<S, T> for Promise<S, Option<T>> {
+    fn then_some<R2: 'static>(self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Option<R2>> {
+        self.map(|state| (state, func)).then(asyn!(s, r => {
+            let (state, func) = s.value;
+            match r {
+                Some(value) => PromiseResult::Await(
+                    Promise::new((state, value), asyn!(s => {
+                        let (state, value) = s.value;
+                        PromiseResult::Resolve(state, value)
+                    }))
+                    .then(func)
+                    .map_result(Some)
+                ),
+                None => PromiseResult::Resolve(state, None),
+            }
+        }))
+    }
+    fn or_else_none(self, func: Asyn![S => S, T]) -> Self::Promise<S, T> {
+        self.map(|state| (state, func)).then(asyn!(s, r => {
+            let (state, func) = s.value;
+            match r {
+                Some(value) => PromiseResult::Resolve(state, value),
+                None => PromiseResult::Await(Promise::new(state, func)),
+            }
+        }))
+    }
+    fn or_default(self, value: T) -> Self::Promise<S, T> {
+        self.map_result(move |r| r.unwrap_or(value))
+    }
+}
+
+impl<'w, 's, 'a, S: 'static, T: 'static> PromiseLikeOption<S, T>
+    for PromiseCommands<'w, 's, 'a, Promise<S, Option<T>>>
+{
+    fn then_some<R2: 'static>(mut self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Option<R2>> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.then_some(func)),
+        }
+    }
+    fn or_else_none(mut self, func: Asyn![S => S, T]) -> Self::Promise<S, T> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.or_else_none(func)),
+        }
+    }
+    fn or_default(mut self, value: T) -> Self::Promise<S, T> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.or_default(value)),
+        }
+    }
+}
+
+impl<'w, 's, 'a, S: 'static, T: 'static> PromiseLikeOption<S, T> for PromiseChain<'w, 's, 'a, S, Option<T>> {
+    fn then_some<R2: 'static>(mut self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Option<R2>> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.then_some(func)),
+        }
+    }
+    fn or_else_none(mut self, func: Asyn![S => S, T]) -> Self::Promise<S, T> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.or_else_none(func)),
+        }
+    }
+    fn or_default(mut self, value: T) -> Self::Promise<S, T> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.or_default(value)),
+        }
+    }
+}
+
+impl<S: 'static, T: 'static, E: 'static> PromiseLikeResult<S, T, E> for Promise<S, Result<T, E>> {
+    fn then_ok<R2: 'static>(self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Result<R2, E>> {
+        self.map(|state| (state, func)).then(asyn!(s, r => {
+            let (state, func) = s.value;
+            match r {
+                Ok(value) => PromiseResult::Await(
+                    Promise::new((state, value), asyn!(s => {
+                        let (state, value) = s.value;
+                        PromiseResult::Resolve(state, value)
+                    }))
+                    .then(func)
+                    .map_result(Ok)
+                ),
+                Err(error) => PromiseResult::Resolve(state, Err(error)),
+            }
+        }))
+    }
+    fn try_then<T2: 'static>(self, func: Asyn![S, T => S, Result<T2, E>]) -> Self::Promise<S, Result<T2, E>> {
+        self.map(|state| (state, func)).then(asyn!(s, r => {
+            let (state, func) = s.value;
+            match r {
+                Ok(value) => PromiseResult::Await(
+                    Promise::new((state, value), asyn!(s => {
+                        let (state, value) = s.value;
+                        PromiseResult::Resolve(state, value)
+                    }))
+                    .then(func)
+                ),
+                Err(error) => PromiseResult::Resolve(state, Err(error)),
+            }
+        }))
+    }
+    fn catch(self, func: Asyn![S, E => S, T]) -> Self::Promise<S, T> {
+        self.map(|state| (state, func)).then(asyn!(s, r => {
+            let (state, func) = s.value;
+            match r {
+                Ok(value) => PromiseResult::Resolve(state, value),
+                Err(error) => PromiseResult::Await(
+                    Promise::new((state, error), asyn!(s => {
+                        let (state, error) = s.value;
+                        PromiseResult::Resolve(state, error)
+                    }))
+                    .then(func)
+                ),
+            }
+        }))
+    }
+    fn or_else<E2: 'static>(self, func: Asyn![S, E => S, Result<T, E2>]) -> Self::Promise<S, Result<T, E2>> {
+        self.map(|state| (state, func)).then(asyn!(s, r => {
+            let (state, func) = s.value;
+            match r {
+                Ok(value) => PromiseResult::Resolve(state, Ok(value)),
+                Err(error) => PromiseResult::Await(
+                    Promise::new((state, error), asyn!(s => {
+                        let (state, error) = s.value;
+                        PromiseResult::Resolve(state, error)
+                    }))
+                    .then(func)
+                ),
+            }
+        }))
+    }
+    fn map_err<E2: 'static, F: 'static + FnOnce(E) -> E2>(self, map: F) -> Self::Promise<S, Result<T, E2>> {
+        self.map_result(move |r| r.map_err(map))
+    }
+}
+
+impl<'w, 's, 'a, S: 'static, T: 'static, E: 'static> PromiseLikeResult<S, T, E>
+    for PromiseCommands<'w, 's, 'a, Promise<S, Result<T, E>>>
+{
+    fn then_ok<R2: 'static>(mut self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Result<R2, E>> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.then_ok(func)),
+        }
+    }
+    fn try_then<T2: 'static>(mut self, func: Asyn![S, T => S, Result<T2, E>]) -> Self::Promise<S, Result<T2, E>> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.try_then(func)),
+        }
+    }
+    fn catch(mut self, func: Asyn![S, E => S, T]) -> Self::Promise<S, T> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.catch(func)),
+        }
+    }
+    fn or_else<E2: 'static>(mut self, func: Asyn![S, E => S, Result<T, E2>]) -> Self::Promise<S, Result<T, E2>> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.or_else(func)),
+        }
+    }
+    fn map_err<E2: 'static, F: 'static + FnOnce(E) -> E2>(mut self, map: F) -> Self::Promise<S, Result<T, E2>> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.map_err(map)),
+        }
+    }
+}
+
+impl<'w, 's, 'a, S: 'static, T: 'static, E: 'static> PromiseLikeResult<S, T, E>
+    for PromiseChain<'w, 's, 'a, S, Result<T, E>>
+{
+    fn then_ok<R2: 'static>(mut self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Result<R2, E>> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.then_ok(func)),
+        }
+    }
+    fn try_then<T2: 'static>(mut self, func: Asyn![S, T => S, Result<T2, E>]) -> Self::Promise<S, Result<T2, E>> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.try_then(func)),
+        }
+    }
+    fn catch(mut self, func: Asyn![S, E => S, T]) -> Self::Promise<S, T> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.catch(func)),
+        }
+    }
+    fn or_else<E2: 'static>(mut self, func: Asyn![S, E => S, Result<T, E2>]) -> Self::Promise<S, Result<T, E2>> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.or_else(func)),
+        }
+    }
+    fn map_err<E2: 'static, F: 'static + FnOnce(E) -> E2>(mut self, map: F) -> Self::Promise<S, Result<T, E2>> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.map_err(map)),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! User input promises. [`InputActivity`] tracks the last keyboard, mouse, touch or
+//! gamepad event, so the flows like attract mode or auto-pause could wait for the
+//! user to be idle:
+//! ```ignore
+//! fn auto_pause(mut commands: Commands) {
+//!     commands.add(asyn::input::idle(30.).then(asyn!(_, _, mut time: ResMut<Time<Virtual>> => {
+//!         info!("Nobody is here, pausing the game");
+//!         time.pause();
+//!     })));
+//! }
+//! ```
+use bevy::{
+    ecs::event::ManualEventReader,
+    input::{
+        gamepad::GamepadEvent,
+        keyboard::KeyboardInput,
+        mouse::{MouseButtonInput, MouseMotion, MouseWheel},
+        touch::TouchInput,
+    },
+};
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(input)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves when there is no user input for `seconds`
+    /// of real time. Any input event restarts the countdown.
+    pub fn idle(seconds: f32) -> Promise<(), ()> {
+        Promise::register(
+            move |world, id| {
+                let started = real_time(world);
+                world.resource_mut::<InputActivity>().idle.push((id, seconds, started));
+            },
+            |world, id| {
+                world
+                    .resource_mut::<InputActivity>()
+                    .idle
+                    .retain(|(waiter, _, _)| waiter != &id);
+            },
+        )
+    }
+}
+
+pub struct PromiseInputPlugin;
+impl Plugin for PromiseInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputActivity>();
+        app.add_systems(Update, (track_input_activity, process_idle).chain());
+    }
+}
+
+/// The last user input time and promises waiting for the user to be idle
+#[derive(Default)]
+pub struct InputActivity {
+    last: Option<f32>,
+    idle: Vec<(PromiseId, f32, f32)>,
+}
+impl Resource for InputActivity {}
+
+impl InputActivity {
+    /// Real time in seconds of the last user input, `None` if there was no input yet
+    pub fn last(&self) -> Option<f32> {
+        self.last
+    }
+}
+
+fn real_time(world: &World) -> f32 {
+    world
+        .get_resource::<Time<Real>>()
+        .map(|time| time.elapsed_seconds())
+        .unwrap_or_default()
+}
+
+fn received<E: Event>(events: Option<Res<Events<E>>>, reader: &mut ManualEventReader<E>) -> bool {
+    events.is_some_and(|events| reader.read(&events).count() > 0)
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn track_input_activity(
+    mut activity: ResMut<InputActivity>,
+    time: Option<Res<Time<Real>>>,
+    keyboard: Option<Res<Events<KeyboardInput>>>,
+    mouse_buttons: Option<Res<Events<MouseButtonInput>>>,
+    mouse_motion: Option<Res<Events<MouseMotion>>>,
+    mouse_wheel: Option<Res<Events<MouseWheel>>>,
+    touches: Option<Res<Events<TouchInput>>>,
+    gamepads: Option<Res<Events<GamepadEvent>>>,
+    mut readers: Local<(
+        ManualEventReader<KeyboardInput>,
+        ManualEventReader<MouseButtonInput>,
+        ManualEventReader<MouseMotion>,
+        ManualEventReader<MouseWheel>,
+        ManualEventReader<TouchInput>,
+        ManualEventReader<GamepadEvent>,
+    )>,
+) {
+    let (keyboard_reader, buttons_reader, motion_reader, wheel_reader, touches_reader, gamepads_reader) = &mut *readers;
+    // every reader should be advanced, so no short-circuiting here
+    let active = [
+        received(keyboard, keyboard_reader),
+        received(mouse_buttons, buttons_reader),
+        received(mouse_motion, motion_reader),
+        received(mouse_wheel, wheel_reader),
+        received(touches, touches_reader),
+        received(gamepads, gamepads_reader),
+    ]
+    .contains(&true);
+    if active {
+        activity.last = Some(time.map(|time| time.elapsed_seconds()).unwrap_or_default());
+    }
+}
+
+fn process_idle(mut commands: Commands, mut activity: ResMut<InputActivity>, time: Option<Res<Time<Real>>>) {
+    let Some(time) = time else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+    let last = activity.last.unwrap_or_default();
+    activity.idle.retain(|(id, seconds, started)| {
+        if now - last.max(*started) < *seconds {
+            return true;
+        }
+        commands.add(PromiseCommand::resolve(*id, ()));
+        false
+    });
+}
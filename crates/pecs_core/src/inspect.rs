@@ -0,0 +1,234 @@
+//! Assertions about promise orchestration state for tests. Insert [`PromiseInspector`]
+//! into the test app, mark interesting promises with [`Promise::labeled`] and check them
+//! with [`expect_pending!`], [`expect_resolved!`] and [`expect_discarded!`]:
+//! ```ignore
+//! #[test]
+//! fn waits_for_timeout() {
+//!     let mut app = App::new();
+//!     app.add_plugins((MinimalPlugins, PecsPlugin));
+//!     app.init_resource::<PromiseInspector>();
+//!     app.add_systems(Startup, |mut commands: Commands| {
+//!         commands.add(asyn::timeout(60.).labeled("timeout"));
+//!     });
+//!     // run the schedule 3 times, then check
+//!     expect_pending!(app, "timeout", 3);
+//! }
+//! ```
+//! The inspector keeps records of every promise registered while it exists,
+//! so it should not be used outside of tests.
+use super::*;
+
+/// Status of the promise recorded by [`PromiseInspector`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromiseStatus {
+    Pending,
+    Resolved,
+    Discarded,
+}
+
+struct PromiseRecord {
+    status: PromiseStatus,
+    label: Option<&'static str>,
+    signature: String,
+}
+
+/// Records status of every promise registered while the resource exists
+#[derive(Default)]
+pub struct PromiseInspector {
+    records: HashMap<PromiseId, PromiseRecord>,
+    labels: HashMap<&'static str, PromiseId>,
+}
+impl Resource for PromiseInspector {}
+
+impl PromiseInspector {
+    /// Status of the last promise registered with `label`,
+    /// `None` if there was no such promise.
+    pub fn status(&self, label: &str) -> Option<PromiseStatus> {
+        self.labels
+            .get(label)
+            .and_then(|id| self.records.get(id))
+            .map(|record| record.status)
+    }
+
+    /// Human-readable list of the pending promises
+    pub fn pending(&self) -> Vec<String> {
+        let mut pending: Vec<_> = self
+            .records
+            .iter()
+            .filter(|(_, record)| record.status == PromiseStatus::Pending)
+            .map(|(id, record)| match record.label {
+                Some(label) => format!("{id} \"{label}\" {}", record.signature),
+                None => format!("{id} {}", record.signature),
+            })
+            .collect();
+        pending.sort();
+        pending
+    }
+
+    /// Run `app` schedule `updates` times and panic if the promise
+    /// labeled with `label` has not the `expected` status.
+    /// Used by [`expect_pending!`], [`expect_resolved!`] and [`expect_discarded!`].
+    #[track_caller]
+    pub fn expect(app: &mut App, label: &str, expected: PromiseStatus, updates: usize) {
+        for _ in 0..updates {
+            app.update();
+        }
+        let Some(inspector) = pecs_compat::app_world(app).get_resource::<PromiseInspector>() else {
+            panic!("Can't inspect promise \"{label}\": PromiseInspector resource is not inserted");
+        };
+        let actual = inspector.status(label);
+        if actual == Some(expected) {
+            return;
+        }
+        let actual = match actual {
+            Some(status) => format!("{status:?}"),
+            None => "not registered".to_string(),
+        };
+        let pending = inspector.pending();
+        let pending = if pending.is_empty() {
+            "  (none)".to_string()
+        } else {
+            pending.iter().map(|p| format!("  {p}")).collect::<Vec<_>>().join("\n")
+        };
+        panic!(
+            "Expected promise \"{label}\" to be {expected:?} after {updates} update(s), but it is {actual}.\n\
+            Pending promises:\n{pending}"
+        );
+    }
+}
+
+/// Labels of the pending promises registered inside the chains marked with
+/// [`Promise::labeled`], including the promises awaited by their handlers.
+/// Tracked only while the resource exists, used to correlate side effects
+/// (like outgoing HTTP requests) with the chains initiating them.
+#[derive(Default)]
+pub struct ChainLabels {
+    labels: HashMap<PromiseId, &'static str>,
+    current: Option<&'static str>,
+}
+impl Resource for ChainLabels {}
+
+impl ChainLabels {
+    /// Label of the chain the pending promise `id` belongs to
+    pub fn get(&self, id: PromiseId) -> Option<&'static str> {
+        self.labels.get(&id).copied()
+    }
+}
+
+/// Label of the chain the pending promise `id` belongs to,
+/// `None` if [`ChainLabels`] are not tracked
+pub fn chain_label(world: &World, id: PromiseId) -> Option<&'static str> {
+    world.get_resource::<ChainLabels>().and_then(|labels| labels.get(id))
+}
+
+/// Runs `register` with the promises registered inside inheriting `label`
+pub(crate) fn in_chain<T>(world: &mut World, label: Option<&'static str>, register: impl FnOnce(&mut World) -> T) -> T {
+    let Some(label) = label else {
+        return register(world);
+    };
+    let Some(mut labels) = world.get_resource_mut::<ChainLabels>() else {
+        return register(world);
+    };
+    let outer = labels.current.replace(label);
+    let result = register(world);
+    world.resource_mut::<ChainLabels>().current = outer;
+    result
+}
+
+pub(crate) fn record<S: 'static, R: 'static>(world: &mut World, id: PromiseId, status: PromiseStatus) {
+    progress::record(world, id, status);
+    debug::record(world, id, status);
+    if let Some(mut labels) = world.get_resource_mut::<ChainLabels>() {
+        match (status, labels.current) {
+            (PromiseStatus::Pending, Some(label)) => {
+                labels.labels.insert(id, label);
+            }
+            (PromiseStatus::Pending, None) => {}
+            _ => {
+                labels.labels.remove(&id);
+            }
+        }
+    }
+    let Some(mut inspector) = world.get_resource_mut::<PromiseInspector>() else {
+        return;
+    };
+    inspector
+        .records
+        .entry(id)
+        .or_insert_with(|| PromiseRecord {
+            status,
+            label: None,
+            signature: format!("<{}, {}>", type_name::<S>(), type_name::<R>()),
+        })
+        .status = status;
+}
+
+impl<S: 'static, R: 'static> Promise<S, R> {
+    /// Mark the promise with `label` so its status could be checked
+    /// with [`expect_pending!`], [`expect_resolved!`] or [`expect_discarded!`].
+    /// Labeled promises are also tracked by the [strict mode](crate::strict),
+    /// the label is inherited by the whole chain when [`ChainLabels`] are tracked.
+    pub fn labeled(mut self, label: &'static str) -> Self {
+        let register = self.register.take();
+        self.register = Some(Box::new(move |world, id| {
+            if let Some(mut inspector) = world.get_resource_mut::<PromiseInspector>() {
+                inspector.labels.insert(label, id);
+                if let Some(record) = inspector.records.get_mut(&id) {
+                    record.label = Some(label);
+                }
+            }
+            strict::track::<S, R>(world, id, label);
+            if let Some(mut labels) = world.get_resource_mut::<ChainLabels>() {
+                labels.labels.insert(id, label);
+            }
+            if let Some(register) = register {
+                in_chain(world, Some(label), |world| register(world, id))
+            }
+        }));
+        self
+    }
+}
+
+/// Assert that the promise labeled with [`Promise::labeled`] is pending,
+/// optionally after running the app schedule `updates` times:
+/// `expect_pending!(app, "label")` or `expect_pending!(app, "label", 3)`
+#[macro_export]
+macro_rules! expect_pending {
+    ($app:expr, $label:expr) => {
+        $crate::expect_pending!($app, $label, 0)
+    };
+    ($app:expr, $label:expr, $updates:expr) => {
+        $crate::inspect::PromiseInspector::expect(&mut $app, $label, $crate::inspect::PromiseStatus::Pending, $updates)
+    };
+}
+
+/// Assert that the promise labeled with [`Promise::labeled`] is resolved,
+/// optionally after running the app schedule `updates` times:
+/// `expect_resolved!(app, "label")` or `expect_resolved!(app, "label", 3)`
+#[macro_export]
+macro_rules! expect_resolved {
+    ($app:expr, $label:expr) => {
+        $crate::expect_resolved!($app, $label, 0)
+    };
+    ($app:expr, $label:expr, $updates:expr) => {
+        $crate::inspect::PromiseInspector::expect(&mut $app, $label, $crate::inspect::PromiseStatus::Resolved, $updates)
+    };
+}
+
+/// Assert that the promise labeled with [`Promise::labeled`] is discarded,
+/// optionally after running the app schedule `updates` times:
+/// `expect_discarded!(app, "label")` or `expect_discarded!(app, "label", 3)`
+#[macro_export]
+macro_rules! expect_discarded {
+    ($app:expr, $label:expr) => {
+        $crate::expect_discarded!($app, $label, 0)
+    };
+    ($app:expr, $label:expr, $updates:expr) => {
+        $crate::inspect::PromiseInspector::expect(
+            &mut $app,
+            $label,
+            $crate::inspect::PromiseStatus::Discarded,
+            $updates,
+        )
+    };
+}
@@ -0,0 +1,181 @@
+//! Awaiting readiness of native file descriptors and sockets, available with
+//! the `io` feature. Sources are watched by the reactor running on a background
+//! thread, ready sources are resolved through the
+//! [external resolutions](crate::external) queue on the next frame. The promise
+//! owns the source while it is watched and passes it back when it is ready:
+//! ```ignore
+//! fn accept(mut commands: Commands) {
+//!     let listener = TcpListener::bind("127.0.0.1:7000").unwrap();
+//!     listener.set_nonblocking(true).unwrap();
+//!     commands.add(
+//!         asyn::io::readable(listener).then(asyn!(_, listener => {
+//!             let (_stream, addr) = listener.unwrap().accept().unwrap();
+//!             info!("Accepted connection from {addr}");
+//!         })),
+//!     );
+//! }
+//! ```
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use crossbeam_channel::Sender;
+use polling::{AsSource, Event, Events, Poller};
+
+use super::*;
+use external::{ExternalResolutions, ExternalResolve};
+
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves with the `source` when it is ready for reading
+    pub fn readable<T: 'static + AsSource + Send>(source: T) -> Promise<(), io::Result<T>> {
+        watch(source, Event::readable)
+    }
+
+    /// Creates promise that resolves with the `source` when it is ready for writing
+    pub fn writable<T: 'static + AsSource + Send>(source: T) -> Promise<(), io::Result<T>> {
+        watch(source, Event::writable)
+    }
+}
+
+/// Called by the reactor thread with `true` when the source is ready,
+/// or with `false` when the promise is discarded
+type Waiter = Box<dyn FnOnce(&Poller, bool) -> Option<ExternalResolve> + Send>;
+
+struct Reactor {
+    poller: Poller,
+    waiters: Mutex<HashMap<usize, Waiter>>,
+    next_key: AtomicUsize,
+    stopped: AtomicBool,
+}
+
+/// Readiness reactor shared with the background thread
+pub struct IoReactor {
+    reactor: Arc<Reactor>,
+    /// Reactor keys of the pending promises
+    keys: HashMap<PromiseId, usize>,
+}
+impl Resource for IoReactor {}
+
+impl Drop for IoReactor {
+    fn drop(&mut self) {
+        self.reactor.stopped.store(true, Ordering::Relaxed);
+        let _ = self.reactor.poller.notify();
+    }
+}
+
+fn watch<T: 'static + AsSource + Send>(source: T, interest: fn(usize) -> Event) -> Promise<(), io::Result<T>> {
+    let mut source = Some(source);
+    Promise::register(
+        move |world, id| {
+            let source = source.take().unwrap();
+            let Some(reactor) = world.get_resource::<IoReactor>().map(|io| io.reactor.clone()) else {
+                errors::report(
+                    world,
+                    id,
+                    errors::PecsError::PluginMissing {
+                        plugin: "PromiseIoPlugin",
+                    },
+                );
+                return;
+            };
+            let key = reactor.next_key.fetch_add(1, Ordering::Relaxed);
+            world.resource_mut::<IoReactor>().keys.insert(id, key);
+            // the lock is held until the waiter is inserted,
+            // so the reactor thread can't miss it
+            let mut waiters = reactor.waiters.lock().unwrap();
+            // SAFETY: the source is owned by the waiter and is deleted
+            // from the poller before it is dropped or passed back
+            if let Err(e) = unsafe { reactor.poller.add(&source.source(), interest(key)) } {
+                drop(waiters);
+                world.resource_mut::<IoReactor>().keys.remove(&id);
+                promise_resolve::<(), io::Result<T>>(world, id, (), Err(e));
+                return;
+            }
+            waiters.insert(
+                key,
+                Box::new(move |poller, ready| {
+                    let _ = poller.delete(&source);
+                    if !ready {
+                        return None;
+                    }
+                    Some(Box::new(move |world: &mut World| {
+                        world.resource_mut::<IoReactor>().keys.remove(&id);
+                        if promise_is_pending::<(), io::Result<T>>(world, id) {
+                            promise_resolve::<(), io::Result<T>>(world, id, (), Ok(source));
+                        }
+                    }))
+                }),
+            );
+        },
+        |world, id| {
+            let Some(mut io) = world.get_resource_mut::<IoReactor>() else {
+                return;
+            };
+            let Some(key) = io.keys.remove(&id) else {
+                return;
+            };
+            let waiter = io.reactor.waiters.lock().unwrap().remove(&key);
+            if let Some(waiter) = waiter {
+                waiter(&io.reactor.poller, false);
+            }
+        },
+    )
+}
+
+fn run(reactor: Arc<Reactor>, sender: Sender<ExternalResolve>) {
+    let mut events = Events::new();
+    while !reactor.stopped.load(Ordering::Relaxed) {
+        events.clear();
+        if let Err(e) = reactor.poller.wait(&mut events, None) {
+            if e.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            error!("Io reactor stopped: {e}");
+            return;
+        }
+        for event in events.iter() {
+            let waiter = reactor.waiters.lock().unwrap().remove(&event.key);
+            if let Some(resolve) = waiter.and_then(|waiter| waiter(&reactor.poller, true)) {
+                let _ = sender.send(resolve);
+            }
+        }
+    }
+}
+
+/// Starts the readiness reactor thread. Ready sources are resolved by
+/// [`PromiseExternalPlugin`][crate::external::PromiseExternalPlugin]
+pub struct PromiseIoPlugin;
+impl Plugin for PromiseIoPlugin {
+    fn build(&self, app: &mut App) {
+        let sender = pecs_compat::app_world_mut(app)
+            .get_resource_or_insert_with(ExternalResolutions::default)
+            .sender();
+        let poller = match Poller::new() {
+            Ok(poller) => poller,
+            Err(e) => {
+                error!("Can't create io poller, io promises will never resolve: {e}");
+                return;
+            }
+        };
+        let reactor = Arc::new(Reactor {
+            poller,
+            waiters: Mutex::new(HashMap::new()),
+            next_key: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+        });
+        let thread_reactor = reactor.clone();
+        if let Err(e) = thread::Builder::new()
+            .name("pecs-io".into())
+            .spawn(move || run(thread_reactor, sender))
+        {
+            error!("Can't start io reactor thread, io promises will never resolve: {e}");
+            return;
+        }
+        app.insert_resource(IoReactor {
+            reactor,
+            keys: HashMap::new(),
+        });
+    }
+}
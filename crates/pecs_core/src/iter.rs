@@ -0,0 +1,60 @@
+//! Frame-sliced iteration: process large collections across several frames
+//! instead of blocking the single frame.
+//! ```ignore
+//! fn spawn_forest(mut commands: Commands) {
+//!     let trees: Vec<Vec3> = generate_tree_positions();
+//!     commands.add(
+//!         asyn::iter::for_each_budgeted(trees, 100, asyn!(position, mut commands: Commands => {
+//!             commands.spawn(TreeBundle::at(position.value));
+//!         }))
+//!         .then(asyn!(_ => info!("The forest is ready"))),
+//!     );
+//! }
+//! ```
+use std::collections::VecDeque;
+
+use pecs_macro::{asyn, asyn_ops};
+
+use super::*;
+use crate::platform::PlatformOpsExtension;
+
+#[asyn_ops(iter)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that runs the `handler` for `per_frame` items each frame, passing
+    /// the item as the handler state, and resolves when all items are processed. The
+    /// first batch is processed when the promise starts. Promises returned by the handler
+    /// are not awaited, they run detached.
+    pub fn for_each_budgeted<T: 'static, I: IntoIterator<Item = T>>(
+        items: I,
+        per_frame: usize,
+        handler: Asyn![T => (), ()],
+    ) -> Promise<(), ()> {
+        let queue: VecDeque<T> = items.into_iter().collect();
+        Promise::repeat(
+            (queue, per_frame.max(1), handler),
+            asyn!(state, world: &mut World => {
+                let (queue, per_frame, handler) = &mut state.value;
+                let batch = (*per_frame).min(queue.len());
+                for item in queue.drain(..batch) {
+                    let result: PromiseResult<(), ()> = handler.run((PromiseState::new(item), ()), world).into();
+                    if let PromiseResult::Await(promise) = result {
+                        promise_register::<(), ()>(world, promise);
+                    }
+                }
+                if queue.is_empty() {
+                    return state.resolve(Repeat::Break(()));
+                }
+                PromiseResult::Await(
+                    state
+                        .asyn()
+                        .platform()
+                        .yield_to_browser()
+                        .with_result(Repeat::Continue),
+                )
+            }),
+        )
+        .map(|_| ())
+    }
+}
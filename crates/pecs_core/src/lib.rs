@@ -1,22 +1,72 @@
 //! Core [`Promise`] functionality.
+#[doc(hidden)]
+pub use pecs_compat::bevy;
+
 use bevy::{
-    ecs::system::{BoxedSystem, Command, StaticSystemParam, SystemParam},
+    ecs::system::{BoxedSystem, StaticSystemParam, SystemParam},
     prelude::*,
-    utils::HashMap,
+    utils::{HashMap, HashSet, Instant},
 };
-use pecs_macro::{asyn, impl_all_promises, impl_any_promises};
+use completion::Completion;
+use errors::PecsError;
+use pecs_compat::Command;
+use pecs_macro::{asyn, impl_all_promises, impl_all_settled_promises, impl_any_ok_promises, impl_any_promises};
+use registry::PromiseMap;
+#[cfg(not(feature = "dense_registry"))]
+use std::thread::{self, ThreadId};
 use std::{
     any::type_name,
     cell::RefCell,
     marker::PhantomData,
     mem,
-    sync::{Arc, RwLock},
-    thread::{self, ThreadId},
+    panic::Location,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 pub mod app;
+pub mod arena;
+pub mod assets;
+pub mod completion;
+pub mod compute;
+pub mod debug;
+pub mod diagnostics;
+pub mod entity;
+pub mod errors;
+pub mod event;
+pub mod external;
+pub mod fs;
+pub mod group;
+pub mod handle;
 mod impls;
+pub mod input;
+pub mod inspect;
+#[cfg(all(feature = "io", not(target_arch = "wasm32")))]
+pub mod io;
+pub mod iter;
+pub mod mailbox;
+#[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+pub mod observe;
+pub mod platform;
+pub mod progress;
+mod registry;
+#[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+pub mod remote;
+pub mod render;
+pub mod resource;
+pub mod scene;
+pub mod shared;
+pub mod snapshot;
+pub mod states;
+pub mod steps;
+pub mod strict;
+pub mod time;
 pub mod timer;
+pub mod tween;
 pub mod ui;
+pub mod validation;
+pub mod value;
+pub mod watchdog;
+pub mod window;
 
 /// Namespace-like stateful container for asyn operations used to simplify
 /// state passing through promise chain. For extending this container with
@@ -71,34 +121,101 @@ pub fn promise_resolve<S: 'static, R: 'static>(world: &mut World, id: PromiseId,
     //     type_name::<S>(),
     //     type_name::<R>(),
     // );
-    let registry = world
+    let resolve = world
         .get_resource_or_insert_with(PromiseRegistry::<S, R>::default)
-        .clone();
-    if let Some(resolve) = {
-        let mut write = registry.0.write().unwrap();
-        let prom = write.get_mut(&id).unwrap();
-        mem::take(&mut prom.resolve)
-    } {
-        resolve(world, state, result)
-    }
-    registry.0.write().unwrap().remove(&id);
+        .0
+        .get_mut(&id)
+        .map(|prom| mem::take(&mut prom.resolve));
+    let Some(resolve) = resolve else {
+        let signature = errors::signature::<S, R>();
+        errors::report(world, id, PecsError::ResolveCompleted { signature });
+        return;
+    };
+    if let Some(resolve) = resolve {
+        let scope = progress::scope_of(world, id);
+        progress::in_scope(world, scope, |world| resolve(world, state, result));
+    }
+    world.resource_mut::<PromiseRegistry<S, R>>().0.remove(&id);
+    if let Some(mut links) = world.get_resource_mut::<AwaitLinks>() {
+        links.0.remove(&id);
+    }
+    inspect::record::<S, R>(world, id, inspect::PromiseStatus::Resolved);
+    diagnostics::count_status(world, inspect::PromiseStatus::Resolved);
     // info!(
     //     "resolved {id}<{}, {}> ({} left)",
     //     type_name::<S>(),
     //     type_name::<R>(),
-    //     registry.0.read().unwrap().len()
+    //     world.resource::<PromiseRegistry<S, R>>().0.len()
     // );
 }
 
+/// Resolves the stateless promises with the `results`. The registry is looked up once for
+/// taking the resolve functions of the batch and once for removing the resolved promises.
+pub fn promise_resolve_many<R: 'static>(world: &mut World, results: Vec<(PromiseId, R)>) {
+    let resolves: Vec<_> = {
+        let mut registry = world.get_resource_or_insert_with(PromiseRegistry::<(), R>::default);
+        let mut seen = HashSet::default();
+        results
+            .into_iter()
+            .map(|(id, result)| {
+                // the repeated ids are reported as resolved twice
+                let resolve = if seen.insert(id) {
+                    registry.0.get_mut(&id).map(|prom| mem::take(&mut prom.resolve))
+                } else {
+                    None
+                };
+                (id, resolve, result)
+            })
+            .collect()
+    };
+    let mut resolved = Vec::with_capacity(resolves.len());
+    for (id, resolve, result) in resolves {
+        let Some(resolve) = resolve else {
+            let signature = errors::signature::<(), R>();
+            errors::report(world, id, PecsError::ResolveCompleted { signature });
+            continue;
+        };
+        // the promise could be discarded by the previous resolves of the batch
+        if !promise_is_pending::<(), R>(world, id) {
+            continue;
+        }
+        if let Some(resolve) = resolve {
+            let scope = progress::scope_of(world, id);
+            progress::in_scope(world, scope, |world| resolve(world, (), result));
+        }
+        resolved.push(id);
+    }
+    {
+        let mut registry = world.resource_mut::<PromiseRegistry<(), R>>();
+        for id in resolved.iter() {
+            registry.0.remove(id);
+        }
+    }
+    if let Some(mut links) = world.get_resource_mut::<AwaitLinks>() {
+        for id in resolved.iter() {
+            links.0.remove(id);
+        }
+    }
+    for id in resolved {
+        inspect::record::<(), R>(world, id, inspect::PromiseStatus::Resolved);
+        diagnostics::count_status(world, inspect::PromiseStatus::Resolved);
+    }
+}
+
 pub fn promise_register<S: 'static, R: 'static>(world: &mut World, mut promise: Promise<S, R>) {
     let id = promise.id;
     // info!("registering {id}");
     let register = promise.register;
     promise.register = None;
-    let registry = world
+    if !world.contains_resource::<PromiseRegistry<S, R>>() {
+        arena::index::<S, R>(world);
+    }
+    world
         .get_resource_or_insert_with(PromiseRegistry::<S, R>::default)
-        .clone();
-    registry.0.write().unwrap().insert(id, promise);
+        .0
+        .insert(id, promise);
+    inspect::record::<S, R>(world, id, inspect::PromiseStatus::Pending);
+    diagnostics::count_status(world, inspect::PromiseStatus::Pending);
     if let Some(register) = register {
         register(world, id)
     }
@@ -106,39 +223,114 @@ pub fn promise_register<S: 'static, R: 'static>(world: &mut World, mut promise:
     //     "registered {id}<{}, {}> ({} left)",
     //     type_name::<S>(),
     //     type_name::<R>(),
-    //     registry.0.read().unwrap().len()
+    //     world.resource::<PromiseRegistry<S, R>>().0.len()
     // );
 }
 
 pub fn promise_discard<S: 'static, R: 'static>(world: &mut World, id: PromiseId) {
     // info!("discarding {id}");
-    let registry = world
+    let discard = world
         .get_resource_or_insert_with(PromiseRegistry::<S, R>::default)
-        .clone();
-    if let Some(discard) = {
-        let mut write = registry.0.write().unwrap();
-        if let Some(prom) = write.get_mut(&id) {
-            mem::take(&mut prom.discard)
-        } else {
-            error!(
-                "Internal promise error: trying to discard complete {id}<{}, {}>",
-                type_name::<S>(),
-                type_name::<R>(),
-            );
-            None
-        }
-    } {
-        discard(world, id);
+        .0
+        .get_mut(&id)
+        .map(|prom| mem::take(&mut prom.discard));
+    match discard {
+        Some(Some(discard)) => discard(world, id),
+        Some(None) => {}
+        None => errors::report(
+            world,
+            id,
+            PecsError::DiscardCompleted {
+                signature: errors::signature::<S, R>(),
+            },
+        ),
+    }
+    if world.resource_mut::<PromiseRegistry<S, R>>().0.remove(&id).is_some() {
+        inspect::record::<S, R>(world, id, inspect::PromiseStatus::Discarded);
+        diagnostics::count_status(world, inspect::PromiseStatus::Discarded);
     }
-    registry.0.write().unwrap().remove(&id);
+    discard_awaited(world, id);
     // info!(
     //     "discarded {id}<{}, {}> ({} left)",
     //     type_name::<S>(),
     //     type_name::<R>(),
-    //     registry.0.read().unwrap().len()
+    //     world.resource::<PromiseRegistry<S, R>>().0.len()
     // );
 }
 
+/// Resolves the promise `id` with the handler result. If the handler returns a promise,
+/// it is registered and linked to `id`, so discarding `id` discards the awaited promise.
+pub(crate) fn promise_resolve_result<S: 'static, R: 'static>(
+    world: &mut World,
+    id: PromiseId,
+    pr: PromiseResult<S, R>,
+) {
+    match pr {
+        PromiseResult::Resolve(s, r) => promise_resolve::<S, R>(world, id, s, r),
+        PromiseResult::Await(mut p) => {
+            if p.resolve.is_some() {
+                let error = PecsError::ResolveAlreadyDefined {
+                    signature: errors::signature::<S, R>(),
+                };
+                errors::report(world, p.id, error);
+                return;
+            }
+            world
+                .get_resource_or_insert_with(AwaitLinks::default)
+                .0
+                .insert(id, (p.id, discard_pending::<S, R>));
+            p.resolve = Some(Box::new(move |world, s, r| {
+                promise_resolve::<S, R>(world, id, s, r);
+            }));
+            let label = inspect::chain_label(world, id);
+            inspect::in_chain(world, label, |world| promise_register::<S, R>(world, p));
+        }
+    }
+}
+
+/// Removes the registered promise without invoking its discard hook, used when
+/// the hook is already invoked by the outer promise of the chain
+pub(crate) fn promise_forget<S: 'static, R: 'static>(world: &mut World, id: PromiseId) {
+    let Some(mut registry) = world.get_resource_mut::<PromiseRegistry<S, R>>() else {
+        return;
+    };
+    if registry.0.remove(&id).is_some() {
+        inspect::record::<S, R>(world, id, inspect::PromiseStatus::Discarded);
+        diagnostics::count_status(world, inspect::PromiseStatus::Discarded);
+    }
+    discard_awaited(world, id);
+}
+
+/// Promises awaited by the pending promises: the key is the awaiting promise,
+/// the value is the awaited promise and the function discarding it
+#[derive(Default)]
+struct AwaitLinks(HashMap<PromiseId, (PromiseId, DiscardAwaited)>);
+type DiscardAwaited = fn(&mut World, PromiseId);
+impl Resource for AwaitLinks {}
+
+fn discard_pending<S: 'static, R: 'static>(world: &mut World, id: PromiseId) {
+    if promise_is_pending::<S, R>(world, id) {
+        promise_discard::<S, R>(world, id);
+    }
+}
+
+/// Discards the promise awaited by the discarded promise `id`
+fn discard_awaited(world: &mut World, id: PromiseId) {
+    let awaited = world
+        .get_resource_mut::<AwaitLinks>()
+        .and_then(|mut links| links.0.remove(&id));
+    if let Some((awaited, discard)) = awaited {
+        discard(world, awaited);
+    }
+}
+
+/// Returns `true` if the promise `id` is registered and not resolved or discarded yet
+pub fn promise_is_pending<S: 'static, R: 'static>(world: &World, id: PromiseId) -> bool {
+    world
+        .get_resource::<PromiseRegistry<S, R>>()
+        .is_some_and(|registry| registry.0.contains_key(&id))
+}
+
 pub trait PromiseParams: 'static + SystemParam + Send + Sync {}
 impl<T: 'static + SystemParam + Send + Sync> PromiseParams for T {}
 
@@ -156,15 +348,40 @@ impl<T: 'static + SystemParam + Send + Sync> PromiseParams for T {}
 ///
 /// The `Asyn` function can take optional parameters of type [`PromiseParams`] which allow the function to access the
 /// same parameters as Bevy systems. These parameters are passed automatically by `pecs`.
+///
+/// Instead of the system parameters, the function can take the exclusive `&mut World` access, for example
+/// to spawn scenes or run schedules. Such functions run without the system machinery:
+/// ```ignore
+/// Promise::from(()).then(asyn!(state, _, world: &mut World => {
+///     world.run_schedule(Reload);
+///     state
+/// }))
+/// ```
 pub struct Asyn<Input, Output: 'static, Params: PromiseParams> {
-    pub marker: PhantomData<Params>,
-    pub body: fn(In<Input>, StaticSystemParam<Params>) -> Output,
+    marker: PhantomData<Params>,
+    body: AsynBody<Input, Output, Params>,
+    /// Where the function is defined, used in diagnostics
+    location: &'static Location<'static>,
 }
+
+/// The function of the [`Asyn`]: the system-like one or the one taking `&mut World`
+enum AsynBody<Input, Output: 'static, Params: PromiseParams> {
+    System(fn(In<Input>, StaticSystemParam<Params>) -> Output),
+    Exclusive(fn(In<Input>, &mut World) -> Output),
+}
+impl<Input, Output: 'static, Params: PromiseParams> Clone for AsynBody<Input, Output, Params> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<Input, Output: 'static, Params: PromiseParams> Copy for AsynBody<Input, Output, Params> {}
+
 impl<Input, Otput: 'static, Params: PromiseParams> Clone for Asyn<Input, Otput, Params> {
     fn clone(&self) -> Self {
         Asyn {
-            body: self.body.clone(),
+            body: self.body,
             marker: self.marker,
+            location: self.location,
         }
     }
 }
@@ -192,14 +409,35 @@ impl<Input, Output: 'static, Params: PromiseParams> Asyn<Input, Output, Params>
     /// [`Into<PromiseResult<S2, R2>>`] trait. The output state and result types
     /// for the resulting `Asyn` function are inferred from the return type of
     /// the `body` function.
+    #[track_caller]
     pub fn new(body: fn(In<Input>, StaticSystemParam<Params>) -> Output) -> Self {
         Asyn {
-            body,
+            body: AsynBody::System(body),
             marker: PhantomData,
+            location: Location::caller(),
         }
     }
-    fn ptr(&self) -> *const fn(In<Input>, StaticSystemParam<Params>) -> Output {
-        self.body as *const fn(In<Input>, StaticSystemParam<Params>) -> Output
+    /// Returns `true` if the function takes `&mut World` instead of the system params
+    pub fn is_exclusive(&self) -> bool {
+        matches!(self.body, AsynBody::Exclusive(_))
+    }
+    fn ptr(&self) -> *const () {
+        match self.body {
+            AsynBody::System(body) => body as *const (),
+            AsynBody::Exclusive(body) => body as *const (),
+        }
+    }
+}
+impl<Input, Output: 'static> Asyn<Input, Output, ()> {
+    /// Creates a new `Asyn` from the function `body` taking `&mut World` instead of the
+    /// system params, the same way as the `asyn!` handlers with `world: &mut World` param.
+    #[track_caller]
+    pub fn new_exclusive(body: fn(In<Input>, &mut World) -> Output) -> Self {
+        Asyn {
+            body: AsynBody::Exclusive(body),
+            marker: PhantomData,
+            location: Location::caller(),
+        }
     }
 }
 impl<Input: 'static, Output: 'static, Params: PromiseParams> Asyn<Input, Output, Params> {
@@ -211,28 +449,76 @@ impl<Input: 'static, Output: 'static, Params: PromiseParams> Asyn<Input, Output,
     /// argument is used to provide access to any necessary `SystemParam`s. The return
     /// value of the `run` method is the output of the system-like function.
     pub fn run(&self, input: Input, world: &mut World) -> Output {
-        let registry = world
-            .get_resource_or_insert_with(SystemRegistry::<Input, Output, Params>::default)
-            .clone();
-        let mut write = registry.0.write().unwrap();
+        let body = match self.body {
+            AsynBody::System(body) => body,
+            AsynBody::Exclusive(exclusive) => {
+                let started = Instant::now();
+                let result = exclusive(In(input), world);
+                diagnostics::count_handler(world);
+                self.warn_if_slow(world, started);
+                return result;
+            }
+        };
+        if !world.contains_resource::<SystemRegistry<Input, Output, Params>>() {
+            world
+                .get_resource_or_insert_with(PecsSystems::default)
+                .evictors
+                .push(SystemRegistry::<Input, Output, Params>::evict);
+        }
+        // the system is taken out of the registry while it runs,
+        // so the body could run handlers of the same signature
         let key = self.clone();
-        let system = write.entry(key).or_insert_with(|| {
-            let mut sys = Box::new(IntoSystem::into_system(self.body));
+        let taken = world
+            .get_resource_or_insert_with(SystemRegistry::<Input, Output, Params>::default)
+            .0
+            .remove(&key);
+        let mut entry = taken.unwrap_or_else(|| {
+            let mut sys = Box::new(IntoSystem::into_system(body));
             sys.initialize(world);
-            sys
+            RegisteredSystem {
+                system: sys,
+                last_run: Instant::now(),
+            }
         });
-        let result = system.run(input, world);
-        system.apply_deferred(world);
+        entry.last_run = Instant::now();
+        let result = entry.system.run(input, world);
+        entry.system.apply_deferred(world);
+        let started = entry.last_run;
+        world
+            .resource_mut::<SystemRegistry<Input, Output, Params>>()
+            .0
+            .insert(key, entry);
+        diagnostics::count_handler(world);
+        self.warn_if_slow(world, started);
         result
     }
+
+    fn warn_if_slow(&self, world: &World, started: Instant) {
+        let Some(threshold) = world.get_resource::<PecsSystems>().and_then(|s| s.warn_slow_after) else {
+            return;
+        };
+        let elapsed = started.elapsed();
+        if elapsed > threshold {
+            warn!(
+                "Slow promise handler at {} took {:.2}ms (threshold {:.2}ms), \
+                move blocking work out of the handler",
+                self.location,
+                elapsed.as_secs_f64() * 1000.,
+                threshold.as_secs_f64() * 1000.,
+            );
+        }
+    }
 }
 
-thread_local!(static PROMISE_LOCAL_ID: std::cell::RefCell<usize>  = RefCell::new(0));
+#[cfg(not(feature = "dense_registry"))]
+thread_local!(static PROMISE_LOCAL_ID: std::cell::RefCell<usize>  = const { RefCell::new(0) });
+#[cfg(not(feature = "dense_registry"))]
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PromiseId {
     thread: ThreadId,
     local: usize,
 }
+#[cfg(not(feature = "dense_registry"))]
 impl PromiseId {
     pub fn new() -> PromiseId {
         PROMISE_LOCAL_ID.with(|id| {
@@ -245,7 +531,14 @@ impl PromiseId {
         })
     }
 }
+#[cfg(not(feature = "dense_registry"))]
+impl Default for PromiseId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+#[cfg(not(feature = "dense_registry"))]
 impl std::fmt::Display for PromiseId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let t = format!("{:?}", self.thread);
@@ -258,6 +551,24 @@ impl std::fmt::Display for PromiseId {
     }
 }
 
+/// Index of the promise in the dense registry of its type with the generation
+/// distinguishing promises reusing the same index. `registry` tells apart the
+/// promises of the different types sharing the index and the generation.
+#[cfg(feature = "dense_registry")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PromiseId {
+    registry: u32,
+    index: u32,
+    generation: u32,
+}
+
+#[cfg(feature = "dense_registry")]
+impl std::fmt::Display for PromiseId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Promise({}:{}v{})", self.registry, self.index, self.generation)
+    }
+}
+
 impl std::fmt::Debug for PromiseId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{self}")
@@ -293,34 +604,98 @@ impl<S: 'static> From<PromiseState<S>> for PromiseResult<S, ()> {
     }
 }
 
-#[derive(Resource)]
-struct PromiseRegistry<S, R>(Arc<RwLock<HashMap<PromiseId, Promise<S, R>>>>);
+/// Registered promises of the `Promise<S, R>` type. Registries are accessed with
+/// `&mut World` only, the promises are taken out of them before invoking the hooks.
+struct PromiseRegistry<S, R>(PromiseMap<Promise<S, R>>);
+impl<S: 'static, R: 'static> Resource for PromiseRegistry<S, R> {}
 impl<S, R> Default for PromiseRegistry<S, R> {
     fn default() -> Self {
-        PromiseRegistry(Arc::new(RwLock::new(HashMap::new())))
+        PromiseRegistry(PromiseMap::new())
     }
 }
-impl<S, R> Clone for PromiseRegistry<S, R> {
-    fn clone(&self) -> Self {
-        PromiseRegistry(self.0.clone())
-    }
+
+struct RegisteredSystem<In, Out> {
+    system: BoxedSystem<In, Out>,
+    last_run: Instant,
 }
 
-#[derive(Resource)]
 struct SystemRegistry<In, Out: 'static, Params: PromiseParams>(
-    Arc<RwLock<HashMap<Asyn<In, Out, Params>, BoxedSystem<In, Out>>>>,
+    HashMap<Asyn<In, Out, Params>, RegisteredSystem<In, Out>>,
 );
-impl<In, Out, Params: PromiseParams> Clone for SystemRegistry<In, Out, Params> {
-    fn clone(&self) -> Self {
-        SystemRegistry(self.0.clone())
-    }
-}
+impl<In: 'static, Out: 'static, Params: PromiseParams> Resource for SystemRegistry<In, Out, Params> {}
 impl<In, Out, Params: PromiseParams> Default for SystemRegistry<In, Out, Params> {
     fn default() -> Self {
-        SystemRegistry(Arc::new(RwLock::new(HashMap::new())))
+        SystemRegistry(HashMap::new())
+    }
+}
+impl<In: 'static, Out: 'static, Params: PromiseParams> SystemRegistry<In, Out, Params> {
+    fn evict(world: &mut World, since: Instant) -> usize {
+        let Some(mut registry) = world.get_resource_mut::<Self>() else {
+            return 0;
+        };
+        let before = registry.0.len();
+        registry.0.retain(|_, entry| entry.last_run >= since);
+        before - registry.0.len()
+    }
+}
+
+/// Housekeeping for systems created for [`Asyn`] functions. Every [`Asyn`] body
+/// runs as a cached Bevy system, so its `Local` state and queries stay alive
+/// until the system is evicted. Evicted systems are recreated with fresh `Local`
+/// state on the next run.
+///
+/// Eviction is disabled by default. Set [`PecsSystems::evict_after`] to evict systems
+/// not used for that long, or call [`PecsSystems::evict_unused`] manually. Pick the
+/// duration longer than chains wait between steps if they rely on `Local` state.
+///
+/// Every [`Asyn`] body invocation taking longer than [`PecsSystems::warn_slow_after`]
+/// is reported with a warning: synchronous work inside handlers (file IO, big loops)
+/// blocks the frame. The guard is disabled by default, enable it with
+/// [`PecsDebugPlugin::warn_slow_after`][debug::PecsDebugPlugin::warn_slow_after].
+#[derive(Default)]
+pub struct PecsSystems {
+    /// Evict systems not used for this long, checked by [`evict_unused_systems`]
+    pub evict_after: Option<Duration>,
+    /// Warn when the single [`Asyn`] body invocation takes longer than this
+    pub warn_slow_after: Option<Duration>,
+    last_check: Option<Instant>,
+    evictors: Vec<fn(&mut World, Instant) -> usize>,
+}
+impl Resource for PecsSystems {}
+
+impl PecsSystems {
+    /// Evict systems of [`Asyn`] functions not used for the `since` duration,
+    /// returns the number of evicted systems.
+    pub fn evict_unused(world: &mut World, since: Duration) -> usize {
+        let Some(evictors) = world.get_resource::<PecsSystems>().map(|s| s.evictors.clone()) else {
+            return 0;
+        };
+        let Some(since) = Instant::now().checked_sub(since) else {
+            return 0;
+        };
+        evictors.iter().map(|evict| evict(world, since)).sum()
     }
 }
 
+/// Evicts unused [`Asyn`] systems when [`PecsSystems::evict_after`] is set
+pub fn evict_unused_systems(world: &mut World) {
+    let Some(mut systems) = world.get_resource_mut::<PecsSystems>() else {
+        return;
+    };
+    let Some(evict_after) = systems.evict_after else {
+        return;
+    };
+    let now = Instant::now();
+    if systems
+        .last_check
+        .is_some_and(|last| now.duration_since(last) < evict_after)
+    {
+        return;
+    }
+    systems.last_check = Some(now);
+    PecsSystems::evict_unused(world, evict_after);
+}
+
 /// An enumeration used to control the behavior of a loop in a [`repeat(asyn!(...))`][Promise::repeat] construct.
 ///
 /// A loop constructed with [`Promise::repeat()`] can be continued or broken by reolving promise with either
@@ -341,6 +716,51 @@ impl Repeat<()> {
     }
 }
 
+/// An enumeration used to control the accumulating loop constructed with [`Promise::fold()`].
+///
+/// Every iteration receives the accumulator as a result and resolves with either
+/// `Fold::Continue(acc)` to run the next iteration with the new accumulator, or
+/// `Fold::Break(result)` to stop the loop with the given `result`.
+pub enum Fold<A, R> {
+    /// A variant indicating that the loop should continue with the given accumulator.
+    Continue(A),
+    /// A variant indicating that the loop should break with the given result.
+    Break(R),
+}
+
+/// A handle for breaking [`Promise::repeat_with_handle()`] loop from the outside.
+///
+/// The break is not applied immediately: the currently awaited iteration completes
+/// as usual, and the loop resolves with the requested result right before the next
+/// iteration starts. This allows long-running loops to shut down gracefully instead
+/// of being discarded in the middle of an await.
+pub struct RepeatHandle<R>(Arc<Mutex<Option<R>>>);
+impl<R> Clone for RepeatHandle<R> {
+    fn clone(&self) -> Self {
+        RepeatHandle(self.0.clone())
+    }
+}
+impl<R> RepeatHandle<R> {
+    fn new() -> Self {
+        RepeatHandle(Arc::new(Mutex::new(None)))
+    }
+    /// Request the loop to break with the `result` at the next iteration boundary.
+    /// If the break was already requested, the `result` replaces the previous one.
+    pub fn request_break(&self, result: R) {
+        *self.0.lock().unwrap() = Some(result);
+    }
+    /// Returns `true` if the break was requested and the loop is not stopped yet.
+    pub fn is_break_requested(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+    fn take(&self) -> Option<R> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+type PromiseHook = Box<dyn FnOnce(&mut World, PromiseId)>;
+type PromiseResolve<S, R> = Box<dyn FnOnce(&mut World, S, R)>;
+
 /// A promise represents a value that may not be available yet, but will be in the future.
 ///
 /// The promise's state is of type `S`, and the result type is `R`. The state represents the
@@ -362,10 +782,14 @@ impl Repeat<()> {
 /// the next promise state/result arguments are inferred from the result of the previous promise.
 pub struct Promise<S, R> {
     id: PromiseId,
-    register: Option<Box<dyn FnOnce(&mut World, PromiseId)>>,
-    discard: Option<Box<dyn FnOnce(&mut World, PromiseId)>>,
-    resolve: Option<Box<dyn FnOnce(&mut World, S, R)>>,
+    /// Keeps the index of the `id` allocated until the promise is dropped
+    _slot: registry::Slot,
+    register: Option<PromiseHook>,
+    discard: Option<PromiseHook>,
+    resolve: Option<PromiseResolve<S, R>>,
 }
+// promises are passed through `Commands` and stored in the registry resources,
+// the hooks are only invoked with `&mut World` and share their state through `Arc<Mutex<_>>`
 unsafe impl<S, R> Send for Promise<S, R> {}
 unsafe impl<S, R> Sync for Promise<S, R> {}
 
@@ -433,9 +857,10 @@ impl<S: 'static, R: 'static> Promise<S, R> {
     /// }
     /// ```
     pub fn new<D: 'static>(default_state: D, func: Asyn![D => S, R]) -> Promise<S, R> {
-        let id = PromiseId::new();
+        let (id, slot) = PromiseRegistry::<S, R>::allocate();
         Promise {
             id,
+            _slot: slot,
             resolve: None,
             discard: None,
             register: Some(Box::new(move |world, id| {
@@ -446,22 +871,7 @@ impl<S: 'static, R: 'static> Promise<S, R> {
                 // system.apply_buffers(world);
                 // let pr = world.run_promise_system(func, PromiseState::new(default_state)).into();
                 let pr = func.run((PromiseState::new(default_state), ()), world).into();
-                match pr {
-                    PromiseResult::Resolve(s, r) => promise_resolve::<S, R>(world, id, s, r),
-                    PromiseResult::Await(mut p) => {
-                        if p.resolve.is_some() {
-                            error!(
-                                "Misconfigured {}<{}, {}>, resolve already defined",
-                                p.id,
-                                type_name::<S>(),
-                                type_name::<R>(),
-                            );
-                            return;
-                        }
-                        p.resolve = Some(Box::new(move |world, s, r| promise_resolve::<S, R>(world, id, s, r)));
-                        promise_register::<S, R>(world, p);
-                    }
-                }
+                promise_resolve_result(world, id, pr);
             })),
         }
     }
@@ -536,14 +946,56 @@ impl<S: 'static, R: 'static> Promise<S, R> {
         on_invoke: F,
         on_discard: D,
     ) -> Promise<S, R> {
+        let (id, slot) = PromiseRegistry::<S, R>::allocate();
         Promise {
-            id: PromiseId::new(),
+            id,
+            _slot: slot,
             resolve: None,
             register: Some(Box::new(on_invoke)),
             discard: Some(Box::new(on_discard)),
         }
     }
 
+    /// Create new [`Promise<S, R>`] which body is constructed by `func` only when
+    /// the promise's turn comes in the chain. Chains are built eagerly, so expensive
+    /// promises wrapped with `lazy` are never constructed if the chain is discarded
+    /// before reaching them (for example when racing with [`any()`][Promise::any]).
+    /// ```ignore
+    /// fn setup(mut commands: Commands) {
+    ///     commands.add(Promise::any((
+    ///         asyn::timeout(1.),
+    ///         Promise::lazy(|| {
+    ///             info!("Building the request only if it is needed");
+    ///             asyn::http::get("https://bevyengine.org").send()
+    ///         }),
+    ///     )));
+    /// }
+    /// ```
+    pub fn lazy<F: 'static + FnOnce() -> Promise<S, R>>(func: F) -> Promise<S, R> {
+        let inner = Arc::new(Mutex::new(None));
+        let discard_inner = inner.clone();
+        Promise::register(
+            move |world, id| {
+                let mut promise = func();
+                if promise.resolve.is_some() {
+                    let error = PecsError::ResolveAlreadyDefined {
+                        signature: errors::signature::<S, R>(),
+                    };
+                    errors::report(world, promise.id, error);
+                    return;
+                }
+                *inner.lock().unwrap() = Some(promise.id);
+                promise.resolve = Some(Box::new(move |world, s, r| promise_resolve::<S, R>(world, id, s, r)));
+                promise_register::<S, R>(world, promise);
+            },
+            move |world, _id| {
+                if let Some(inner) = discard_inner.lock().unwrap().take() {
+                    promise_discard::<S, R>(world, inner);
+                }
+            },
+        )
+    }
+
     /// Create new [`Promise<S, R>`] from default `S` state and  [`Asyn!`][struct@Asyn]`[D => S,`[`Repeat<R>`]`]`
     /// function. `S` and `R` infers from the [`Asyn`][struct@Asyn] function body.
     ///
@@ -566,6 +1018,89 @@ impl<S: 'static, R: 'static> Promise<S, R> {
             }),
         )
     }
+
+    /// Create new [`Promise<S, R>`] from `S` state, `init` accumulator and
+    /// [`Asyn!`][struct@Asyn]`[S, A => S,`[`Fold<A, R>`]`]` function. The function
+    /// receives the accumulator as a result.
+    ///
+    /// If `func` resolves with [`Fold::Continue(acc)`] it executes one more time with `acc`.
+    /// If `func` resolves with [`Fold::Break(result)`], the loop stops and
+    /// `result` passes to the next promise.
+    /// ```ignore
+    /// // poll the server until the job is done, collecting the partial results
+    /// Promise::fold((), vec![], asyn!(_, mut parts => {
+    ///     asyn::http::get("https://example.com/job").send_json::<Job>().map_result(move |job| match job {
+    ///         Ok(Job { part, done: false }) => {
+    ///             parts.push(part);
+    ///             Fold::Continue(parts)
+    ///         }
+    ///         Ok(Job { part, done: true }) => {
+    ///             parts.push(part);
+    ///             Fold::Break(Ok(parts))
+    ///         }
+    ///         Err(e) => Fold::Break(Err(e)),
+    ///     })
+    /// }))
+    /// ```
+    pub fn fold<A: 'static>(state: S, init: A, func: Asyn![S, A => S, Fold<A, R>]) -> Promise<S, R> {
+        let next = func.clone();
+        Promise::new(state, asyn!(s => s))
+            .with_result(init)
+            .then(func)
+            .map(|state| (state, next))
+            .then(asyn!(s, r => {
+                let (state, next) = s.value;
+                match r {
+                    Fold::Continue(acc) => PromiseResult::Await(Promise::fold(state, acc, next)),
+                    Fold::Break(result) => PromiseResult::Resolve(state, result)
+                }
+            }))
+    }
+
+    /// Same as [`Promise::repeat()`], but also returns the [`RepeatHandle<R>`] that
+    /// can be used by external systems to gracefully break the loop with
+    /// [`handle.request_break(result)`][RepeatHandle::request_break].
+    /// ```ignore
+    /// #[derive(Resource)]
+    /// struct Autosave(RepeatHandle<()>);
+    ///
+    /// fn setup(mut commands: Commands) {
+    ///     let (promise, handle) = Promise::repeat_with_handle((), asyn!(state => {
+    ///         info!("Saving..");
+    ///         state.asyn().timeout(60.).with_result(Repeat::Continue)
+    ///     }));
+    ///     commands.add(promise);
+    ///     commands.insert_resource(Autosave(handle));
+    /// }
+    ///
+    /// fn stop_autosave(autosave: Res<Autosave>) {
+    ///     autosave.0.request_break(());
+    /// }
+    /// ```
+    pub fn repeat_with_handle(state: S, func: Asyn![S => S, Repeat<R>]) -> (Promise<S, R>, RepeatHandle<R>) {
+        let handle = RepeatHandle::new();
+        (Promise::repeat_handled(state, func, handle.clone()), handle)
+    }
+
+    fn repeat_handled(state: S, func: Asyn![S => S, Repeat<R>], handle: RepeatHandle<R>) -> Promise<S, R> {
+        Promise::new(
+            (state, func, handle),
+            asyn!(s => {
+                let (state, func, handle) = s.value;
+                if let Some(result) = handle.take() {
+                    return PromiseResult::Resolve(state, result);
+                }
+                let next = func.clone();
+                PromiseResult::Await(Promise::new(state, func).map(|state| (state, next, handle)).then(asyn!(s, r => {
+                    let (state, next, handle) = s.value;
+                    match r {
+                        Repeat::Continue => PromiseResult::Await(Promise::repeat_handled(state, next, handle)),
+                        Repeat::Break(result) => PromiseResult::Resolve(state, result)
+                    }
+                })))
+            }),
+        )
+    }
 }
 
 impl<R: 'static> Promise<(), R> {
@@ -582,9 +1117,155 @@ impl Promise<(), ()> {
     pub fn any<T: AnyPromises>(any: T) -> Promise<(), T::Result> {
         any.register()
     }
+    /// Same as [`any()`][Promise::any], but the losing promises are handled
+    /// according to `losers` instead of being discarded.
+    /// ```ignore
+    /// // the hint stays on the screen for 3 seconds even if the player skips it
+    /// Promise::any_with(AnyLosers::Detach, (asyn::ui::button(skip).pressed(), show_hint(3.)))
+    /// ```
+    pub fn any_with<T: AnyWithPromises>(losers: AnyLosers, any: T) -> Promise<(), T::Result> {
+        any.register_with(losers)
+    }
+    /// Creates promise that resolves when all promises are resolved. Results are
+    /// assembled in the input order, but the promises (and `then` handlers chained
+    /// to them) resolve in the order their operations complete. Use
+    /// [`Promises::all_ordered`] if handler side effects should run in the input order,
+    /// or [`Promise::all_limited`] to bound the number of the running promises.
     pub fn all<T: AllPromises>(any: T) -> Promise<(), T::Result> {
         any.register()
     }
+    /// Same as [`all()`][Promise::all] for the vector of promises, but at most `limit`
+    /// of them are registered at the same time, see [`Promises::all_limited`].
+    /// ```ignore
+    /// let requests: Vec<_> = urls.iter().map(|url| asyn::http::get(url).send()).collect();
+    /// // no more than 8 requests at once
+    /// Promise::all_limited(requests, 8)
+    /// ```
+    pub fn all_limited<S: 'static, R: 'static>(promises: Vec<Promise<S, R>>, limit: usize) -> Promise<(), Vec<(S, R)>> {
+        Promises(promises).all_limited(limit)
+    }
+    /// Creates promise that resolves with the first `Ok` result, the rest promises are
+    /// discarded. Failed promises are ignored until all of them fail, then the promise
+    /// resolves with all the errors in the input order. For vectors it is the same as
+    /// [`Promises::race_ok`].
+    /// ```ignore
+    /// Promise::any_ok((
+    ///     asyn::http::get("https://mirror1.example.com/level.zip").send(),
+    ///     asyn::http::get("https://mirror2.example.com/level.zip").send(),
+    /// ))
+    /// .then(asyn!(_, result => match result {
+    ///     Ok((Some(response), _) | (_, Some(response))) => info!("Downloaded {} bytes", response.bytes.len()),
+    ///     Ok(_) => unreachable!(),
+    ///     Err((e1, e2)) => error!("Both mirrors failed: {e1}, {e2}"),
+    /// }))
+    /// ```
+    pub fn any_ok<T: AnyOkPromises>(any: T) -> Promise<(), T::Result> {
+        any.register()
+    }
+    /// Creates promise that resolves when every promise is settled: resolved or
+    /// discarded. Unlike [`all()`][Promise::all], the discarded promises don't stall
+    /// the result, their outcome is [`Settled::Discarded`].
+    /// ```ignore
+    /// Promise::all_settled((load_profile(), load_friends()))
+    ///     .then(asyn!(_, (profile, friends) => {
+    ///         if let Settled::Resolved(friends) = friends {
+    ///             info!("{} friends online", friends.len());
+    ///         }
+    ///     }))
+    /// ```
+    pub fn all_settled<T: AllSettledPromises>(all: T) -> Promise<(), T::Result> {
+        all.register()
+    }
+    /// Creates `n` parallel chains from the `factory` function, each started with its
+    /// index as a state. Resolves when all workers are resolved, with the results in
+    /// the index order along with the worker index.
+    /// ```ignore
+    /// fn generate(mut commands: Commands) {
+    ///     commands.add(
+    ///         Promise::spawn_workers(8, asyn!(index => {
+    ///             let chunk = *index;
+    ///             generate_chunk(chunk)
+    ///         }))
+    ///         .then(asyn!(_, chunks => {
+    ///             for (index, _, chunk) in chunks {
+    ///                 info!("Chunk #{index} generated with {} tiles", chunk.len());
+    ///             }
+    ///         })),
+    ///     );
+    /// }
+    /// ```
+    pub fn spawn_workers<S: 'static, R: 'static>(
+        n: usize,
+        factory: Asyn![usize => S, R],
+    ) -> Promise<(), Vec<(usize, S, R)>> {
+        Promise::workers(n, factory, None)
+    }
+    /// Same as [`spawn_workers()`][Promise::spawn_workers], but every worker joins
+    /// the `group`, so the progress of the workers is available in [`GroupProgress`][group::GroupProgress]
+    /// and all of them could be discarded at once.
+    pub fn spawn_workers_in_group<S: 'static, R: 'static>(
+        group: &'static str,
+        n: usize,
+        factory: Asyn![usize => S, R],
+    ) -> Promise<(), Vec<(usize, S, R)>> {
+        Promise::workers(n, factory, Some(group))
+    }
+    /// Creates promise that runs `func` for every item of `items` strictly in sequence,
+    /// passing the item as the state. The next item is processed when the promise
+    /// returned by the previous `func` resolves. Resolves with the results in the
+    /// input order when the items are exhausted. Unlike [`all()`][Promise::all], the
+    /// operations never run concurrently.
+    /// ```ignore
+    /// // one request per second
+    /// Promise::for_each(urls, asyn!(url => {
+    ///     url.asyn().timeout(1.).then(asyn!(url, _ => asyn::http::get(url.value).send()))
+    /// }))
+    /// .then(asyn!(_, responses => info!("Received {} responses", responses.len())))
+    /// ```
+    pub fn for_each<T: 'static, S2: 'static, R2: 'static, I: IntoIterator<Item = T>>(
+        items: I,
+        func: Asyn![T => S2, R2],
+    ) -> Promise<(), Vec<R2>> {
+        let queue: std::collections::VecDeque<T> = items.into_iter().collect();
+        Promise::repeat(
+            (queue, Vec::new(), func),
+            asyn!(state => {
+                let Some(item) = state.value.0.pop_front() else {
+                    let results = mem::take(&mut state.value.1);
+                    return state.resolve(Repeat::Break(results));
+                };
+                let func = state.value.2.clone();
+                PromiseResult::Await(Promise::new(item, func).map(move |_| state.value).then(asyn!(state, result => {
+                    state.value.1.push(result);
+                    state.resolve(Repeat::<Vec<R2>>::Continue)
+                })))
+            }),
+        )
+        .map(|_| ())
+    }
+    fn workers<S: 'static, R: 'static>(
+        n: usize,
+        factory: Asyn![usize => S, R],
+        group: Option<&'static str>,
+    ) -> Promise<(), Vec<(usize, S, R)>> {
+        if n == 0 {
+            return Promise::register(
+                |world, id| promise_resolve::<(), Vec<(usize, S, R)>>(world, id, (), vec![]),
+                |_, _| {},
+            );
+        }
+        (0..n)
+            .map(|idx| {
+                let worker = Promise::new(idx, factory.clone()).map_result(move |r| (idx, r));
+                match group {
+                    Some(group) => worker.in_group(group),
+                    None => worker,
+                }
+            })
+            .promise()
+            .all()
+            .map_result(|results| results.into_iter().map(|(s, (idx, r))| (idx, s, r)).collect())
+    }
 }
 
 pub struct PromiseCommand<R> {
@@ -604,6 +1285,25 @@ impl<R: 'static + Send + Sync> Command for PromiseCommand<R> {
     }
 }
 
+/// Command resolving many stateless promises at once, see [`promise_resolve_many()`]
+pub struct PromiseBatch<R> {
+    results: Vec<(PromiseId, R)>,
+}
+
+impl<R> PromiseBatch<R> {
+    pub fn resolve_many<I: IntoIterator<Item = (PromiseId, R)>>(results: I) -> Self {
+        PromiseBatch {
+            results: results.into_iter().collect(),
+        }
+    }
+}
+
+impl<R: 'static + Send + Sync> Command for PromiseBatch<R> {
+    fn apply(self, world: &mut World) {
+        promise_resolve_many::<R>(world, self.results);
+    }
+}
+
 impl<R: 'static, S: 'static> Command for Promise<S, R> {
     fn apply(self, world: &mut World) {
         promise_register::<S, R>(world, self)
@@ -640,6 +1340,26 @@ impl<'w, 's, 'a, T> Drop for PromiseCommands<'w, 's, 'a, T> {
     }
 }
 
+pub struct PromiseBatchCommands<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+}
+impl<'w, 's, 'a> PromiseBatchCommands<'w, 's, 'a> {
+    /// Resolve the promises with the results of the `iter` in a single [`PromiseBatch`] command
+    pub fn resolve_many<R: 'static + Send + Sync, I: IntoIterator<Item = (PromiseId, R)>>(self, iter: I) {
+        self.commands.add(PromiseBatch::resolve_many(iter));
+    }
+}
+
+pub trait PromiseBatchCommandsExtension<'w, 's> {
+    fn promise_batch<'a>(&'a mut self) -> PromiseBatchCommands<'w, 's, 'a>;
+}
+impl<'w, 's> PromiseBatchCommandsExtension<'w, 's> for Commands<'w, 's> {
+    /// Create commands for resolving many promises by [`PromiseId`] at once
+    fn promise_batch<'a>(&'a mut self) -> PromiseBatchCommands<'w, 's, 'a> {
+        PromiseBatchCommands { commands: self }
+    }
+}
+
 pub trait PromiseCommandsExtension<'w, 's, T> {
     fn promise<'a>(&'a mut self, promise: T) -> PromiseCommands<'w, 's, 'a, T>;
 }
@@ -762,6 +1482,24 @@ impl<S: 'static> PromiseState<S> {
         AsynOps(self.value)
     }
 
+    /// Same as [`asyn()`][PromiseState::asyn], but doesn't consume the state:
+    /// the ops are created with the clone of the state value, so the state
+    /// could still be used in the handler body. Note that the clone is passed
+    /// to the next promise, changes made to the state afterwards are lost.
+    /// ```ignore
+    /// asyn!(state => {
+    ///     let wait = state.asyn_ref().timeout(state.delay);
+    ///     info!("Waiting {}s before {}", state.delay, state.name);
+    ///     wait
+    /// })
+    /// ```
+    pub fn asyn_ref(&self) -> AsynOps<S>
+    where
+        S: Clone,
+    {
+        AsynOps(self.value.clone())
+    }
+
     /// Create a new `PromiseResult` with the given result.
     pub fn resolve<R>(self, result: R) -> PromiseResult<S, R> {
         PromiseResult::Resolve(self.value, result)
@@ -791,15 +1529,38 @@ impl<S: 'static> PromiseState<S> {
         Promise::repeat(self.value, func)
     }
 
+    /// Start a new accumulating loop with the `init` accumulator, see [`Promise::fold()`].
+    pub fn fold<A: 'static, R2: 'static>(self, init: A, func: Asyn![S, A => S, Fold<A, R2>]) -> Promise<S, R2> {
+        Promise::fold(self.value, init, func)
+    }
+
     /// Combine the current promise chain with the given promises using the [`AnyPromises`] trait.
     pub fn any<A: AnyPromises>(self, any: A) -> Promise<S, A::Result> {
         any.register().with(self.value)
     }
 
+    /// Same as [`any()`][PromiseState::any], but the losing promises are
+    /// handled according to `losers`, see [`Promise::any_with`].
+    pub fn any_with<A: AnyWithPromises>(self, losers: AnyLosers, any: A) -> Promise<S, A::Result> {
+        any.register_with(losers).with(self.value)
+    }
+
     /// Combine the current promise chain with the given promises using the `AllPromises` trait.
     pub fn all<A: AllPromises>(self, all: A) -> Promise<S, A::Result> {
         all.register().with(self.value)
     }
+
+    /// Combine the current promise chain with the given promises using the [`AnyOkPromises`] trait,
+    /// see [`Promise::any_ok`].
+    pub fn any_ok<A: AnyOkPromises>(self, any: A) -> Promise<S, A::Result> {
+        any.register().with(self.value)
+    }
+
+    /// Combine the current promise chain with the given promises using the [`AllSettledPromises`]
+    /// trait, see [`Promise::all_settled`].
+    pub fn all_settled<A: AllSettledPromises>(self, all: A) -> Promise<S, A::Result> {
+        all.register().with(self.value)
+    }
 }
 
 impl<S: std::fmt::Display> std::fmt::Display for PromiseState<S> {
@@ -826,82 +1587,116 @@ impl<T: std::fmt::Debug> std::fmt::Debug for PromiseState<T> {
     }
 }
 
-pub struct MutPtr<T>(*mut T);
-unsafe impl<T> Send for MutPtr<T> {}
-unsafe impl<T> Sync for MutPtr<T> {}
-impl<T> Clone for MutPtr<T> {
-    fn clone(&self) -> Self {
-        MutPtr(self.0)
-    }
-}
-
-impl<T> MutPtr<T> {
-    pub fn new(value: T) -> MutPtr<T> {
-        let b = Box::new(value);
-        MutPtr(Box::leak(b) as *mut T)
-    }
-    pub fn get(&mut self) -> T {
-        if self.0.is_null() {
-            panic!("Ups.")
-        }
-        let b = unsafe { Box::from_raw(self.0) };
-        self.0 = std::ptr::null_mut();
-        *b
-    }
-    pub fn get_ref(&self) -> &T {
-        if self.0.is_null() {
-            panic!("Ups.");
-        }
-        unsafe { self.0.as_ref().unwrap() }
-    }
-    pub fn get_mut(&mut self) -> &mut T {
-        if self.0.is_null() {
-            panic!("Ups.");
-        }
-        unsafe { self.0.as_mut().unwrap() }
-    }
-    pub fn is_valid(&self) -> bool {
-        !self.0.is_null()
-    }
+/// Results of the promises aggregated by [`all()`][Promise::all], taken when the last
+/// promise resolves and cleared when the outer promise is discarded
+type AllSlot<T> = Arc<Mutex<Option<T>>>;
+
+/// What happens to the losing promises when one of [`AnyPromises`] resolves
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnyLosers {
+    /// Discard the losers immediately, running their discard hooks
+    #[default]
+    Cancel,
+    /// Keep the losers running as detached fire-and-forget chains: handlers chained
+    /// to them still run, their results are dropped. The losers are discarded only if
+    /// the `any` promise itself is discarded before some promise wins.
+    Detach,
+    /// Discard the losers like [`Cancel`][AnyLosers::Cancel], reporting each of them as
+    /// [`PecsError::AnyLoserRejected`] first, so the app could react to the flows that
+    /// lost with [`PecsErrorEvent`][errors::PecsErrorEvent] and the chain labels
+    Reject,
 }
 
 pub trait AnyPromises {
     type Result: 'static;
     fn register(self) -> Promise<(), Self::Result>;
 }
+/// [`AnyPromises`] handling the losers according to [`AnyLosers`], see [`Promise::any_with`]
+pub trait AnyWithPromises: AnyPromises {
+    fn register_with(self, losers: AnyLosers) -> Promise<(), Self::Result>;
+}
 pub trait AllPromises {
     type Result: 'static;
     fn register(self) -> Promise<(), Self::Result>;
 }
+pub trait AnyOkPromises {
+    type Result: 'static;
+    fn register(self) -> Promise<(), Self::Result>;
+}
+pub trait AllSettledPromises {
+    type Result: 'static;
+    fn register(self) -> Promise<(), Self::Result>;
+}
+
+/// Outcome of the promise passed to [`Promise::all_settled`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Settled<R> {
+    /// The promise resolved with the result
+    Resolved(R),
+    /// The promise was discarded before it resolved
+    #[default]
+    Discarded,
+}
+
+impl<R> Settled<R> {
+    /// Returns the result if the promise resolved
+    pub fn resolved(self) -> Option<R> {
+        match self {
+            Settled::Resolved(result) => Some(result),
+            Settled::Discarded => None,
+        }
+    }
+    pub fn is_resolved(&self) -> bool {
+        matches!(self, Settled::Resolved(_))
+    }
+    pub fn is_discarded(&self) -> bool {
+        matches!(self, Settled::Discarded)
+    }
+}
 
 impl<S: 'static, R: 'static> AnyPromises for Vec<Promise<S, R>> {
     type Result = (S, R);
     fn register(self) -> Promise<(), Self::Result> {
+        self.register_with(AnyLosers::Cancel)
+    }
+}
+
+impl<S: 'static, R: 'static> AnyWithPromises for Vec<Promise<S, R>> {
+    fn register_with(self, losers: AnyLosers) -> Promise<(), Self::Result> {
         let ids: Vec<PromiseId> = self.iter().map(|p| p.id).collect();
         let discard_ids = ids.clone();
         Promise::register(
             move |world, any_id| {
-                let mut idx = 0usize;
-                for promise in self {
+                for (idx, promise) in self.into_iter().enumerate() {
                     let ids = ids.clone();
                     promise_register(
                         world,
-                        promise.map(move |s| (s, any_id, idx, ids)).then(asyn!(|s, r| {
-                            let (state, any_id, idx, ids) = s.value;
+                        promise.map(move |s| (s, any_id, idx, ids, losers)).then(asyn!(|s, r| {
+                            let (state, any_id, idx, ids, losers) = s.value;
                             Promise::<(), ()>::register(
-                                move |world, _id| {
-                                    for (i, id) in ids.iter().enumerate() {
-                                        if i != idx {
-                                            promise_discard::<S, R>(world, *id);
+                                move |world, id| {
+                                    // detached losers resolve after the winner
+                                    if promise_is_pending::<(), (S, R)>(world, any_id) {
+                                        if losers != AnyLosers::Detach {
+                                            for (i, id) in ids.iter().enumerate() {
+                                                if i == idx {
+                                                    continue;
+                                                }
+                                                if losers == AnyLosers::Reject {
+                                                    let error = PecsError::AnyLoserRejected { winner: any_id };
+                                                    errors::report(world, *id, error);
+                                                }
+                                                promise_discard::<S, R>(world, *id);
+                                            }
                                         }
+                                        promise_resolve::<(), (S, R)>(world, any_id, (), (state, r));
                                     }
-                                    promise_resolve::<(), (S, R)>(world, any_id, (), (state, r))
+                                    promise_resolve::<(), ()>(world, id, (), ());
                                 },
                                 |_, _| {},
                             )
                         })),
                     );
-                    idx += 1;
                 }
             },
             move |world, _| {
@@ -918,35 +1713,125 @@ impl<S: 'static, R: 'static> AllPromises for Vec<Promise<S, R>> {
     fn register(self) -> Promise<(), Self::Result> {
         let ids: Vec<PromiseId> = self.iter().map(|p| p.id).collect();
         let size = ids.len();
+        let value: AllSlot<Vec<Option<(S, R)>>> = Arc::new(Mutex::new(Some((0..size).map(|_| None).collect())));
+        let slot = value.clone();
         Promise::register(
             move |world, any_id| {
-                let value: Vec<Option<(S, R)>> = (0..size).map(|_| None).collect();
-                let value = MutPtr::new(value);
-                let mut idx = 0usize;
-                for promise in self {
+                debug::slot_allocated::<Vec<Option<(S, R)>>>(world, any_id);
+                for (idx, promise) in self.into_iter().enumerate() {
                     let value = value.clone();
                     promise_register(
                         world,
                         promise.map(move |s| (s, any_id, idx, value)).then(asyn!(|s, r| {
-                            let (s, any_id, idx, mut value) = s.value;
+                            let (s, any_id, idx, value) = s.value;
                             Promise::<(), ()>::register(
-                                move |world, _id| {
-                                    value.get_mut()[idx] = Some((s, r));
-                                    if value.get_ref().iter().all(|v| v.is_some()) {
-                                        let value = value.get().into_iter().map(|v| v.unwrap()).collect();
-                                        promise_resolve::<(), Vec<(S, R)>>(world, any_id, (), value)
+                                move |world, id| {
+                                    let values = {
+                                        let mut slot = value.lock().unwrap();
+                                        if let Some(values) = slot.as_mut() {
+                                            values[idx] = Some((s, r));
+                                        }
+                                        match slot.as_ref() {
+                                            Some(values) if values.iter().all(Option::is_some) => slot.take(),
+                                            _ => None,
+                                        }
+                                    };
+                                    if let Some(values) = values {
+                                        let values = values.into_iter().map(|v| v.unwrap()).collect();
+                                        debug::slot_freed(world, any_id);
+                                        promise_resolve::<(), Vec<(S, R)>>(world, any_id, (), values)
                                     }
+                                    promise_resolve::<(), ()>(world, id, (), ());
                                 },
                                 |_, _| {},
                             )
                         })),
                     );
-                    idx += 1;
+                }
+            },
+            move |world, any_id| {
+                for id in ids {
+                    // the promises resolved so far are not registered anymore
+                    if promise_is_pending::<S, R>(world, id) {
+                        promise_discard::<S, R>(world, id);
+                    }
+                }
+                // drop the results of the promises resolved so far
+                slot.lock().unwrap().take();
+                debug::slot_freed(world, any_id);
+            },
+        )
+    }
+}
+
+impl<S: 'static, T: 'static, E: 'static> AnyOkPromises for Vec<Promise<S, Result<T, E>>> {
+    type Result = RaceOk<S, T, E>;
+    fn register(self) -> Promise<(), Self::Result> {
+        Promises(self).race_ok()
+    }
+}
+
+/// Outcomes of the settled promises, `None` is cleared when the outer promise is discarded
+type SettledSlots<T> = Arc<Mutex<Option<Vec<Option<Settled<T>>>>>>;
+
+/// Records the `outcome` of the promise `idx` and resolves `all_id` when every promise is settled
+fn settle<T: 'static>(world: &mut World, all_id: PromiseId, slots: &SettledSlots<T>, idx: usize, outcome: Settled<T>) {
+    let mut lock = slots.lock().unwrap();
+    let Some(outcomes) = lock.as_mut() else {
+        return;
+    };
+    outcomes[idx] = Some(outcome);
+    if outcomes.iter().any(Option::is_none) {
+        return;
+    }
+    let result: Vec<Settled<T>> = lock.take().unwrap().into_iter().map(Option::unwrap).collect();
+    drop(lock);
+    promise_resolve::<(), Vec<Settled<T>>>(world, all_id, (), result);
+}
+
+impl<S: 'static, R: 'static> AllSettledPromises for Vec<Promise<S, R>> {
+    type Result = Vec<Settled<(S, R)>>;
+    fn register(self) -> Promise<(), Self::Result> {
+        let ids: Vec<PromiseId> = self.iter().map(|p| p.id).collect();
+        let slots: SettledSlots<(S, R)> = Arc::new(Mutex::new(Some((0..ids.len()).map(|_| None).collect())));
+        let discard_slots = slots.clone();
+        Promise::register(
+            move |world, all_id| {
+                if self.is_empty() {
+                    promise_resolve::<(), Self::Result>(world, all_id, (), vec![]);
+                    return;
+                }
+                for (idx, promise) in self.into_iter().enumerate() {
+                    let resolved = slots.clone();
+                    let discarded = slots.clone();
+                    let mut promise = promise.map(move |s| (s, all_id, idx, resolved)).then(asyn!(|s, r| {
+                        let (s, all_id, idx, slots) = s.value;
+                        Promise::<(), ()>::register(
+                            move |world, id| {
+                                settle(world, all_id, &slots, idx, Settled::Resolved((s, r)));
+                                promise_resolve::<(), ()>(world, id, (), ());
+                            },
+                            |_, _| {},
+                        )
+                    }));
+                    // the chain is discarded from outside, or by discarding the inner promise
+                    let discard = promise.discard.take();
+                    promise.discard = Some(Box::new(move |world, id| {
+                        if let Some(discard) = discard {
+                            discard(world, id);
+                        }
+                        settle(world, all_id, &discarded, idx, Settled::Discarded);
+                    }));
+                    promise_register(world, promise);
                 }
             },
             move |world, _| {
+                // the outer promise is discarded, the outcomes are not collected anymore
+                discard_slots.lock().unwrap().take();
                 for id in ids {
-                    promise_discard::<S, R>(world, id);
+                    if promise_is_pending::<S, R>(world, id) {
+                        promise_discard::<S, R>(world, id);
+                    }
                 }
             },
         )
@@ -955,6 +1840,8 @@ impl<S: 'static, R: 'static> AllPromises for Vec<Promise<S, R>> {
 
 impl_any_promises! { 8 }
 impl_all_promises! { 8 }
+impl_any_ok_promises! { 8 }
+impl_all_settled_promises! { 8 }
 
 #[macro_export]
 /// Generates signature an [`Asyn`][struct@Asyn] function wrapper. It allows you to specify
@@ -976,6 +1863,273 @@ impl<S: 'static, R: 'static> Promises<S, R> {
     pub fn all(self) -> Promise<(), Vec<(S, R)>> {
         PromiseState::new(()).all(self.0)
     }
+    /// Resolves with the outcomes of all promises, see [`Promise::all_settled`]
+    pub fn all_settled(self) -> Promise<(), Vec<Settled<(S, R)>>> {
+        PromiseState::new(()).all_settled(self.0)
+    }
+    /// Creates promise that waits for all promises to resolve and then runs
+    /// `handler` for each result sequentially, in the input order. The next
+    /// handler starts when the promise returned by the previous one resolves.
+    /// Resolves with the handler results in the input order.
+    ///
+    /// Unlike chaining handlers to each promise before [`all()`][Promises::all],
+    /// side effects of the handlers never interleave and don't depend on
+    /// which operation completes first.
+    /// ```ignore
+    /// fn load_levels(mut commands: Commands) {
+    ///     commands.add(
+    ///         ["level1", "level2", "level3"]
+    ///             .into_iter()
+    ///             .map(|name| asyn::http::get(format!("https://example.com/{name}")).send())
+    ///             .promise()
+    ///             // levels are spawned as level1, level2, level3
+    ///             .all_ordered(asyn!(_, response, mut commands: Commands => {
+    ///                 commands.spawn(parse_level(response));
+    ///             })),
+    ///     );
+    /// }
+    /// ```
+    pub fn all_ordered<S2: 'static, R2: 'static>(self, handler: Asyn![S, R => S2, R2]) -> Promise<(), Vec<(S2, R2)>> {
+        self.all().map(move |_| handler).then(asyn!(handler, results => {
+            let queue: std::collections::VecDeque<(S, R)> = results.into();
+            Promise::repeat((queue, Vec::new(), handler.value), asyn!(state => {
+                let (mut queue, mut done, handler) = state.value;
+                let Some((s, r)) = queue.pop_front() else {
+                    let results = mem::take(&mut done);
+                    return PromiseResult::Resolve((queue, done, handler), Repeat::Break(results));
+                };
+                let next = handler.clone();
+                PromiseResult::Await(
+                    Promise::from(s)
+                        .with_result(r)
+                        .then(next)
+                        .map(move |s2| (s2, queue, done, handler))
+                        .then(asyn!(state, r2 => {
+                            let (s2, queue, mut done, handler) = state.value;
+                            done.push((s2, r2));
+                            PromiseResult::Resolve((queue, done, handler), Repeat::Continue)
+                        })),
+                )
+            }))
+            .map(|_| ())
+        }))
+    }
+
+    /// Creates promise that resolves when all promises are resolved, but keeps at
+    /// most `limit` of them registered at the same time. The next promise starts when
+    /// one of the running promises resolves. Resolves with the results in the input order.
+    /// ```ignore
+    /// fn download(mut commands: Commands) {
+    ///     commands.add(
+    ///         (0..100)
+    ///             .map(|i| asyn::http::get(format!("https://example.com/tile/{i}")).send())
+    ///             .promise()
+    ///             // no more than 4 requests at once
+    ///             .all_limited(4)
+    ///             .then(asyn!(_, tiles => {
+    ///                 info!("Downloaded {} tiles", tiles.len());
+    ///             })),
+    ///     );
+    /// }
+    /// ```
+    pub fn all_limited(self, limit: usize) -> Promise<(), Vec<(S, R)>> {
+        let size = self.0.len();
+        let batch = Arc::new(Mutex::new(LimitedBatch {
+            queue: self.0.into_iter().enumerate().collect(),
+            results: (0..size).map(|_| None).collect(),
+            running: vec![],
+        }));
+        let discard_batch = batch.clone();
+        Promise::register(
+            move |world, all_id| {
+                if size == 0 {
+                    promise_resolve::<(), Vec<(S, R)>>(world, all_id, (), vec![]);
+                    return;
+                }
+                for _ in 0..limit.max(1) {
+                    LimitedBatch::start_next(world, all_id, batch.clone());
+                }
+            },
+            move |world, _| {
+                let running = mem::take(&mut discard_batch.lock().unwrap().running);
+                for id in running {
+                    promise_discard::<S, R>(world, id);
+                }
+            },
+        )
+    }
+
+    /// Creates promise that resolves when all promises are resolved, with the
+    /// results keyed by the promise states.
+    /// ```ignore
+    /// ["en", "de", "fr"]
+    ///     .into_iter()
+    ///     .map(|lang| asyn::http::get(format!("https://example.com/{lang}.ftl")).send().with(lang))
+    ///     .promise()
+    ///     .all_keyed()
+    ///     .then(asyn!(_, translations => {
+    ///         let german = &translations["de"];
+    ///     }))
+    /// ```
+    pub fn all_keyed(self) -> Promise<(), HashMap<S, R>>
+    where
+        S: Eq + std::hash::Hash,
+    {
+        self.all().map_result(|results| results.into_iter().collect())
+    }
+
+    /// Creates promise that resolves with the first resolved promise along with
+    /// its index in the input order. The rest of the promises are discarded.
+    pub fn any_indexed(self) -> Promise<(), (usize, S, R)> {
+        Promises(
+            self.0
+                .into_iter()
+                .enumerate()
+                .map(|(idx, promise)| promise.map_result(move |r| (idx, r)))
+                .collect(),
+        )
+        .any()
+        .map_result(|(s, (idx, r))| (idx, s, r))
+    }
+}
+
+impl<S: 'static, T: 'static, E: 'static> Promises<S, Result<T, E>> {
+    /// Creates promise that resolves with the first `Ok` result, the rest of the
+    /// promises are discarded. If every promise resolves with `Err`, resolves
+    /// with all the errors in the input order.
+    /// ```ignore
+    /// ["https://mirror1.example.com", "https://mirror2.example.com"]
+    ///     .into_iter()
+    ///     .map(|mirror| asyn::http::get(format!("{mirror}/patch.bin")).send())
+    ///     .promise()
+    ///     .race_ok()
+    ///     .then(asyn!(_, result => match result {
+    ///         Ok((_, response)) => info!("Downloaded {} bytes", response.bytes.len()),
+    ///         Err(errors) => error!("All mirrors failed: {errors:?}"),
+    ///     }))
+    /// ```
+    pub fn race_ok(self) -> Promise<(), RaceOk<S, T, E>> {
+        let size = self.0.len();
+        let race = Arc::new(Mutex::new(Race {
+            running: self.0.iter().map(|p| p.id).collect(),
+            errors: (0..size).map(|_| None).collect(),
+        }));
+        let discard_race = race.clone();
+        Promise::register(
+            move |world, race_id| {
+                if size == 0 {
+                    promise_resolve::<(), RaceOk<S, T, E>>(world, race_id, (), Err(vec![]));
+                    return;
+                }
+                for (idx, promise) in self.0.into_iter().enumerate() {
+                    let race = race.clone();
+                    promise_register(
+                        world,
+                        promise.map(move |s| (s, race_id, idx, race)).then(asyn!(|s, r| {
+                            let (s, race_id, idx, race) = s.value;
+                            Promise::<(), ()>::register(
+                                move |world, id| {
+                                    match r {
+                                        Ok(value) => {
+                                            let pending = race.lock().unwrap().take_pending();
+                                            for (i, id) in pending {
+                                                if i != idx {
+                                                    promise_discard::<S, Result<T, E>>(world, id);
+                                                }
+                                            }
+                                            promise_resolve::<(), RaceOk<S, T, E>>(world, race_id, (), Ok((s, value)));
+                                        }
+                                        Err(e) => {
+                                            let mut race = race.lock().unwrap();
+                                            race.errors[idx] = Some((s, e));
+                                            if race.errors.iter().all(|e| e.is_some()) {
+                                                race.running.clear();
+                                                let errors =
+                                                    mem::take(&mut race.errors).into_iter().flatten().collect();
+                                                drop(race);
+                                                promise_resolve::<(), RaceOk<S, T, E>>(world, race_id, (), Err(errors));
+                                            }
+                                        }
+                                    }
+                                    promise_resolve::<(), ()>(world, id, (), ());
+                                },
+                                |_, _| {},
+                            )
+                        })),
+                    );
+                }
+            },
+            move |world, _| {
+                let pending = discard_race.lock().unwrap().take_pending();
+                for (_, id) in pending {
+                    promise_discard::<S, Result<T, E>>(world, id);
+                }
+            },
+        )
+    }
+}
+
+/// Result of [`Promises::race_ok()`]: the first `Ok` value with its state,
+/// or all the errors with their states in the input order
+pub type RaceOk<S, T, E> = Result<(S, T), Vec<(S, E)>>;
+
+struct Race<S, E> {
+    running: Vec<PromiseId>,
+    errors: Vec<Option<(S, E)>>,
+}
+
+impl<S, E> Race<S, E> {
+    /// Takes `(index, id)` of the promises not resolved with error yet
+    fn take_pending(&mut self) -> Vec<(usize, PromiseId)> {
+        mem::take(&mut self.running)
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| self.errors[*idx].is_none())
+            .collect()
+    }
+}
+
+struct LimitedBatch<S, R> {
+    queue: std::collections::VecDeque<(usize, Promise<S, R>)>,
+    results: Vec<Option<(S, R)>>,
+    running: Vec<PromiseId>,
+}
+
+impl<S: 'static, R: 'static> LimitedBatch<S, R> {
+    fn start_next(world: &mut World, all_id: PromiseId, batch: Arc<Mutex<Self>>) {
+        let Some((idx, promise)) = batch.lock().unwrap().queue.pop_front() else {
+            return;
+        };
+        let promise_id = promise.id;
+        batch.lock().unwrap().running.push(promise_id);
+        promise_register(
+            world,
+            promise
+                .map(move |s| (s, all_id, idx, promise_id, batch))
+                .then(asyn!(|s, r| {
+                    let (s, all_id, idx, promise_id, batch) = s.value;
+                    Promise::<(), ()>::register(
+                        move |world, id| {
+                            let finished = {
+                                let mut write = batch.lock().unwrap();
+                                write.running.retain(|running| running != &promise_id);
+                                write.results[idx] = Some((s, r));
+                                write.results.iter().all(|r| r.is_some())
+                            };
+                            if finished {
+                                let results = mem::take(&mut batch.lock().unwrap().results);
+                                let results = results.into_iter().flatten().collect();
+                                promise_resolve::<(), Vec<(S, R)>>(world, all_id, (), results);
+                            } else {
+                                LimitedBatch::start_next(world, all_id, batch);
+                            }
+                            promise_resolve::<(), ()>(world, id, (), ());
+                        },
+                        |_, _| {},
+                    )
+                })),
+        );
+    }
 }
 
 pub trait PromisesExtension<S: 'static, R: 'static> {
@@ -997,6 +2151,66 @@ where
     /// `S2` and `R2` infers from the `func` body
     fn then<S2: 'static, R2: 'static>(self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, R2>;
 
+    /// Same as [`then()`][PromiseLikeBase::then], but the `func` returns `Result` with the
+    /// usual handler output in `Ok`, so `?` could be used inside the body. If the `func`
+    /// returns `Err`, the error is logged and the rest of the chain is discarded.
+    /// ```ignore
+    /// commands
+    ///     .promise(|| ())
+    ///     .then(asyn!(state => state.asyn().http().get("https://example.com/level").send()))
+    ///     .then_try(asyn!(state, response => {
+    ///         let response = response?;
+    ///         let level = response.text().ok_or("Level is not utf-8")?.parse::<u32>().map_err(|e| e.to_string())?;
+    ///         Ok::<_, String>(state.resolve(level))
+    ///     }))
+    ///     .then(asyn!(_, level => {
+    ///         info!("Loaded level {level}");
+    ///     }));
+    /// ```
+    fn then_try<S2: 'static, R2: 'static, E: 'static + std::fmt::Display>(
+        self,
+        func: Asyn<(PromiseState<S>, R), Result<impl 'static + Into<PromiseResult<S2, R2>>, E>, impl PromiseParams>,
+    ) -> Self::Promise<S2, R2>;
+
+    /// Same as [`then()`][PromiseLikeBase::then], but the result is passed further together
+    /// with the time spent between the `func` invocation and resolving of the promise it
+    /// awaits. The time is measured with the [timers clock][timer], so it is zero if
+    /// `func` resolves immediately.
+    /// ```ignore
+    /// commands
+    ///     .promise(|| ())
+    ///     .then_measure(asyn!(state => state.asyn().http().get("https://example.com").send()))
+    ///     .then(asyn!(_, (response, elapsed) => {
+    ///         info!("Request took {elapsed:?}");
+    ///     }));
+    /// ```
+    fn then_measure<S2: 'static, R2: 'static>(self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, (R2, Duration)>;
+
+    /// Same as [`then()`][PromiseLikeBase::then], but the `func` runs on the compute task
+    /// pool. The `func` should not take system params, it runs against the empty world
+    /// off the main thread. Handlers taking `world: &mut World` run on the main thread
+    /// as usual. The `func` returns the `(state, result)` to resolve with: the promises
+    /// are not sent off the main thread, so it can't return one to await.
+    /// ```ignore
+    /// commands
+    ///     .promise(|| ())
+    ///     .then(asyn!(_ => asyn::fs::read("terrain.bin")))
+    ///     .then_detached(asyn!(_, bytes => ((), Heightmap::decode(&bytes.unwrap()))))
+    ///     .then(asyn!(_, heightmap, mut commands: Commands => {
+    ///         commands.insert_resource(heightmap);
+    ///     }));
+    /// ```
+    /// Every call builds the system of the `func` and spawns the task, the empty world
+    /// is created once per pool thread and reused. It pays off for the handlers doing
+    /// the work worth a task, short ones are faster with [`then()`][PromiseLikeBase::then].
+    fn then_detached<S2: 'static + Send, R2: 'static + Send>(
+        self,
+        func: Asyn<(PromiseState<S>, R), (S2, R2), ()>,
+    ) -> Self::Promise<S2, R2>
+    where
+        S: Send,
+        R: Send;
+
     /// Create new [`PromiseLike<S, R>`] from previouse promise with result mapped by `map` from `R` to `R2`
     fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(self, map: F) -> Self::Promise<S, R2>;
 
@@ -1008,6 +2222,56 @@ where
 
     /// Create new [`PromiseLike<S2, R>`] from previouse promise with state replaced with `S2`
     fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R>;
+
+    /// Create new [`PromiseLike<S, R>`] that resolves not earlier than `seconds` after the
+    /// current promise starts. If the current promise resolves early, the resolution is
+    /// padded with the timer, so loading screens and UI hints don't flash.
+    /// ```ignore
+    /// commands
+    ///     .promise(move || loading_screen)
+    ///     .then(asyn!(state => state.asyn().http().get("https://example.com").send()))
+    ///     .at_least(1.5)
+    ///     .then(asyn!(state, _response, mut commands: Commands => {
+    ///         commands.entity(state.value).despawn_recursive();
+    ///     }));
+    /// ```
+    fn at_least(self, seconds: f32) -> Self::Promise<S, R>;
+
+    /// Create new [`PromiseLike<S, R>`] which starts only after the `prerequisite`
+    /// promise is finished. The result of the prerequisite is not consumed, see
+    /// [`Promise::completion`].
+    fn after(self, prerequisite: &Completion) -> Self::Promise<S, R>;
+
+    /// Create new [`PromiseLike<S, R>`] which starts only after every
+    /// promise of the `prerequisites` is finished
+    fn after_all<'c, I: IntoIterator<Item = &'c Completion>>(self, prerequisites: I) -> Self::Promise<S, R>;
+
+    /// Create new [`PromiseLike<S, R>`] which runs the [`Asyn![(), f32 => (), ()]`][Asyn!] func
+    /// with the progress reported by the handlers of the chain with
+    /// [`PromiseProgress::report`][progress::PromiseProgress::report].
+    /// The func receives the last progress reported during the frame, see [`progress`].
+    fn on_progress(self, func: Asyn![(), f32 => (), ()]) -> Self::Promise<S, R>;
+
+    /// Create new [`PromiseLike<(), ()>`] which runs the [`Asyn![(), Settled<(S, R)> => (), ()]`][Asyn!]
+    /// func once the chain is settled: with [`Settled::Resolved`] state and result when the
+    /// chain resolves, or with [`Settled::Discarded`] when some promise of the chain is
+    /// discarded, so the cleanup runs even if the chain is cancelled mid-way.
+    /// ```ignore
+    /// commands.add(
+    ///     Promise::from(popup)
+    ///         .then(asyn!(state => state.asyn().timeout(5.)))
+    ///         .finally(asyn!(_, settled, mut locks: ResMut<InputLocks> => {
+    ///             if settled.is_discarded() {
+    ///                 warn!("The popup is closed early");
+    ///             }
+    ///             locks.release("popup");
+    ///         }))
+    ///         .bound_to_state(GameState::Menu),
+    /// );
+    /// ```
+    /// Promises returned by the func are awaited if the chain resolves and run detached
+    /// if it is discarded.
+    fn finally(self, func: Asyn![(), Settled<(S, R)> => (), ()]) -> Self::Promise<(), ()>;
 }
 
 pub trait PromiseLike<S: 'static>
@@ -1021,9 +2285,250 @@ where
     /// `result` passes to the next promise.
     fn then_repeat<R2: 'static>(self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2>;
 
+    /// Create new [`PromiseLike<S, R2>`] from the accumulating loop started with `init`
+    /// accumulator, see [`Promise::fold()`]. `R2` infers from the `func` body.
+    fn then_fold<A: 'static, R2: 'static>(self, init: A, func: Asyn![S, A => S, Fold<A, R2>]) -> Self::Promise<S, R2>;
+
     /// Create a new promise that resolves when all promises in the `all` parameter have resolved.
     fn all<A: 'static + AllPromises>(self, all: A) -> Self::Promise<S, A::Result>;
 
     /// Create a new promise that resolves when any of the promises in the `any` parameter have resolved.
     fn any<A: 'static + AnyPromises>(self, any: A) -> Self::Promise<S, A::Result>;
 }
+
+pub trait PromiseLikeOption<S: 'static, T: 'static>
+where
+    Self: Sized + PromiseLikeBase<S, Option<T>>,
+{
+    /// Schedule the [`Asyn![S, T => S, R2]`][Asyn!] func invocation only if the current promise
+    /// resolves with `Some(value)`, `value` is passed to the `func` as result. If the current
+    /// promise resolves with `None`, `func` is skipped and the state passes through with `None`.
+    fn then_some<R2: 'static>(self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Option<R2>>;
+
+    /// Schedule the [`Asyn![S => S, T]`][Asyn!] func invocation only if the current promise
+    /// resolves with `None`. `Some(value)` is unwrapped and passes to the next promise as is.
+    fn or_else_none(self, func: Asyn![S => S, T]) -> Self::Promise<S, T>;
+
+    /// Create new [`PromiseLike<S, T>`] from previouse promise with `None` result replaced by `value`
+    fn or_default(self, value: T) -> Self::Promise<S, T>;
+}
+
+/// Error channel of the promise chain: promises that resolve with `Result<T, E>`
+/// short-circuit on `Err` instead of threading the error through every handler.
+/// ```ignore
+/// commands
+///     .promise(|| ())
+///     .then(asyn!(state => state.asyn().http().get("https://example.com/level").send()))
+///     .map_err(|e| format!("Request failed: {e}"))
+///     .then_ok(asyn!(state, response => state.resolve(response.bytes.len())))
+///     .catch(asyn!(state, error => {
+///         warn!("{error}");
+///         state.resolve(0)
+///     }))
+///     .then(asyn!(_, size => info!("Level size: {size}")));
+/// ```
+pub trait PromiseLikeResult<S: 'static, T: 'static, E: 'static>
+where
+    Self: Sized + PromiseLikeBase<S, Result<T, E>>,
+{
+    /// Schedule the [`Asyn![S, T => S, R2]`][Asyn!] func invocation only if the current promise
+    /// resolves with `Ok(value)`, `value` is passed to the `func` as result. If the current
+    /// promise resolves with `Err(error)`, `func` is skipped and the error passes through.
+    fn then_ok<R2: 'static>(self, func: Asyn![S, T => S, R2]) -> Self::Promise<S, Result<R2, E>>;
+
+    /// Same as [`then_ok()`][PromiseLikeResult::then_ok], but the `func` may fail with
+    /// the error of the same type, like `?` does. The first `Err` skips the following
+    /// `try_then` steps and reaches the trailing [`catch()`][PromiseLikeResult::catch]:
+    /// ```ignore
+    /// commands
+    ///     .promise(|| ())
+    ///     .then(asyn!(_ => asyn::fs::read("config.ron")))
+    ///     .try_then(asyn!(state, bytes => state.resolve(String::from_utf8(bytes).map_err(io::Error::other))))
+    ///     .try_then(asyn!(state, text => state.resolve(ron::from_str::<Config>(&text).map_err(io::Error::other))))
+    ///     .then_ok(asyn!(_, config, mut commands: Commands => commands.insert_resource(config)))
+    ///     .catch(asyn!(_, error => error!("Can't load the config: {error}")));
+    /// ```
+    fn try_then<T2: 'static>(self, func: Asyn![S, T => S, Result<T2, E>]) -> Self::Promise<S, Result<T2, E>>;
+
+    /// Schedule the [`Asyn![S, E => S, T]`][Asyn!] func invocation only if the current promise
+    /// resolves with `Err(error)`, the `func` recovers from the error. `Ok(value)` is unwrapped
+    /// and passes to the next promise as is.
+    fn catch(self, func: Asyn![S, E => S, T]) -> Self::Promise<S, T>;
+
+    /// Same as [`catch()`][PromiseLikeResult::catch], but the `func` may fail again
+    /// with the error of the other type.
+    fn or_else<E2: 'static>(self, func: Asyn![S, E => S, Result<T, E2>]) -> Self::Promise<S, Result<T, E2>>;
+
+    /// Create new [`PromiseLike<S, Result<T, E2>>`] from previouse promise with error mapped by `map` from `E` to `E2`
+    fn map_err<E2: 'static, F: 'static + FnOnce(E) -> E2>(self, map: F) -> Self::Promise<S, Result<T, E2>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use errors::PecsErrorEvent;
+
+    /// Number of the discard hooks of the [`pending()`] promises invoked
+    #[derive(Default)]
+    struct Discarded(usize);
+    impl Resource for Discarded {}
+
+    /// Never resolves on its own, counts the discards in [`Discarded`]
+    fn pending() -> Promise<(), ()> {
+        Promise::register(|_, _| {}, |world, _| world.resource_mut::<Discarded>().0 += 1)
+    }
+
+    /// Resolved by the test with [`promise_resolve`]
+    fn manual() -> Promise<(), ()> {
+        Promise::register(|_, _| {}, |_, _| {})
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Discarded>();
+        world.init_resource::<Events<PecsErrorEvent>>();
+        world
+    }
+
+    fn errors(world: &mut World) -> Vec<PecsErrorEvent> {
+        world.resource_mut::<Events<PecsErrorEvent>>().drain().collect()
+    }
+
+    #[derive(Default)]
+    struct Runs(Vec<u32>);
+    impl Resource for Runs {}
+
+    type CountdownParams = (Commands<'static, 'static>, ResMut<'static, Runs>);
+
+    /// Runs itself again from the command applied before the system is put back
+    /// into the [`SystemRegistry`]
+    fn countdown(In(value): In<u32>, params: StaticSystemParam<CountdownParams>) {
+        let (mut commands, mut runs) = params.into_inner();
+        runs.0.push(value);
+        if value > 0 {
+            commands.add(move |world: &mut World| Asyn::new(countdown).run(value - 1, world));
+        }
+    }
+
+    #[test]
+    fn asyn_runs_nested_asyn_of_same_signature() {
+        let mut world = World::new();
+        world.init_resource::<Runs>();
+        Asyn::new(countdown).run(2, &mut world);
+        assert_eq!(world.resource::<Runs>().0, vec![2, 1, 0]);
+        let registry = world.resource::<SystemRegistry<u32, (), CountdownParams>>();
+        assert_eq!(registry.0.len(), 1);
+    }
+
+    #[test]
+    fn discarding_chain_discards_awaited_promise() {
+        let mut world = world();
+        let chain = Promise::start(asyn!(_ => pending())).then(asyn!(_ => {}));
+        let id = chain.id;
+        promise_register(&mut world, chain);
+        promise_discard::<(), ()>(&mut world, id);
+        assert_eq!(world.resource::<Discarded>().0, 1);
+        assert!(errors(&mut world).is_empty());
+        debug::assert_no_leaks(&world);
+    }
+
+    #[test]
+    fn any_cancels_awaiting_losers() {
+        let mut world = world();
+        let winner = manual();
+        let winner_id = winner.id;
+        let loser = Promise::start(asyn!(_ => pending()));
+        promise_register(&mut world, Promise::any(vec![winner, loser]));
+        promise_resolve::<(), ()>(&mut world, winner_id, (), ());
+        assert_eq!(world.resource::<Discarded>().0, 1);
+        assert!(errors(&mut world).is_empty());
+        debug::assert_no_leaks(&world);
+    }
+
+    #[test]
+    fn any_with_reject_reports_losers() {
+        let mut world = world();
+        let winner = manual();
+        let winner_id = winner.id;
+        let loser = pending();
+        let loser_id = loser.id;
+        let any = Promise::any_with(AnyLosers::Reject, (winner, loser));
+        let any_id = any.id;
+        promise_register(&mut world, any);
+        promise_resolve::<(), ()>(&mut world, winner_id, (), ());
+        let errors = errors(&mut world);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].promise, loser_id);
+        assert_eq!(errors[0].error, PecsError::AnyLoserRejected { winner: any_id });
+        assert_eq!(world.resource::<Discarded>().0, 1);
+        debug::assert_no_leaks(&world);
+    }
+
+    #[test]
+    fn any_with_detach_keeps_losers() {
+        let mut world = world();
+        let winner = manual();
+        let winner_id = winner.id;
+        let loser = manual();
+        let loser_id = loser.id;
+        promise_register(&mut world, Promise::any_with(AnyLosers::Detach, vec![winner, loser]));
+        promise_resolve::<(), ()>(&mut world, winner_id, (), ());
+        assert!(promise_is_pending::<(), ()>(&world, loser_id));
+        promise_resolve::<(), ()>(&mut world, loser_id, (), ());
+        assert!(errors(&mut world).is_empty());
+        debug::assert_no_leaks(&world);
+    }
+
+    #[test]
+    fn discarding_partially_resolved_all_frees_slot() {
+        let mut world = world();
+        let first = manual();
+        let first_id = first.id;
+        let all = Promise::all(vec![first, pending(), pending()]);
+        let all_id = all.id;
+        promise_register(&mut world, all);
+        promise_resolve::<(), ()>(&mut world, first_id, (), ());
+        assert_eq!(world.resource::<debug::PromiseSlots>().len(), 1);
+        promise_discard::<(), Vec<((), ())>>(&mut world, all_id);
+        assert_eq!(world.resource::<Discarded>().0, 2);
+        assert!(errors(&mut world).is_empty());
+        debug::assert_no_leaks(&world);
+    }
+
+    #[test]
+    fn discarding_partially_resolved_tuple_all_frees_slot() {
+        let mut world = world();
+        let first = manual();
+        let first_id = first.id;
+        let all = Promise::all((first, pending(), pending()));
+        let all_id = all.id;
+        promise_register(&mut world, all);
+        promise_resolve::<(), ()>(&mut world, first_id, (), ());
+        assert_eq!(world.resource::<debug::PromiseSlots>().len(), 1);
+        promise_discard::<(), ((), (), ())>(&mut world, all_id);
+        assert_eq!(world.resource::<Discarded>().0, 2);
+        assert!(errors(&mut world).is_empty());
+        debug::assert_no_leaks(&world);
+    }
+
+    #[test]
+    fn then_detached_runs_exclusive_handler_on_main_thread() {
+        let mut world = world();
+        world.init_resource::<Runs>();
+        let source = manual();
+        let source_id = source.id;
+        let chain = source
+            .then_detached(asyn!(_, _, world: &mut World => {
+                world.resource_mut::<Runs>().0.push(1);
+                ((), 2)
+            }))
+            .then(asyn!(_, result, mut runs: ResMut<Runs> => {
+                runs.0.push(result);
+            }));
+        promise_register(&mut world, chain);
+        promise_resolve::<(), ()>(&mut world, source_id, (), ());
+        assert_eq!(world.resource::<Runs>().0, vec![1, 2]);
+        assert!(errors(&mut world).is_empty());
+        debug::assert_no_leaks(&world);
+    }
+}
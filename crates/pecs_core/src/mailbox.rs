@@ -0,0 +1,127 @@
+//! Actor-like messaging: entities receive messages sent with
+//! [`commands.send_to(entity, message)`][MailboxCommandsExtension::send_to]
+//! by awaiting [`recv(entity)`][recv] promises
+use std::collections::VecDeque;
+
+use super::*;
+
+/// Holds messages of type `M` sent to the entity and promises waiting for them.
+/// Inserted automatically on first [`send_to`][MailboxCommandsExtension::send_to]
+/// or [`recv`] call.
+pub struct Mailbox<M: 'static + Send + Sync> {
+    messages: VecDeque<M>,
+    receivers: VecDeque<PromiseId>,
+}
+pecs_compat::impl_component!([M: 'static + Send + Sync] Mailbox<M>);
+
+impl<M: 'static + Send + Sync> Default for Mailbox<M> {
+    fn default() -> Self {
+        Mailbox {
+            messages: VecDeque::new(),
+            receivers: VecDeque::new(),
+        }
+    }
+}
+
+impl<M: 'static + Send + Sync> Mailbox<M> {
+    /// Number of messages waiting to be received
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// Creates promise that resolves with the next message of type `M` sent to the `entity`.
+/// Messages are delivered in the order they were sent, one message per `recv()`.
+/// ```ignore
+/// fn setup(mut commands: Commands) {
+///     let actor = commands.spawn_empty().id();
+///     commands.add(Promise::repeat(actor, asyn!(state => {
+///         let actor = state.value;
+///         state.asyn().recv::<String>(actor).then(asyn!(state, message => {
+///             info!("{:?} received {message}", state.value);
+///             state.resolve(Repeat::Continue)
+///         }))
+///     })));
+///     commands.send_to(actor, "Hello!".to_string());
+/// }
+/// ```
+pub fn recv<M: 'static + Send + Sync>(entity: Entity) -> Promise<(), M> {
+    Promise::register(
+        move |world, id| {
+            let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+                errors::report(world, id, errors::PecsError::EntityMissing { entity });
+                return;
+            };
+            let mut mailbox = match entity_mut.get_mut::<Mailbox<M>>() {
+                Some(mailbox) => mailbox,
+                None => {
+                    entity_mut.insert(Mailbox::<M>::default());
+                    entity_mut.get_mut::<Mailbox<M>>().unwrap()
+                }
+            };
+            let Some(message) = mailbox.messages.pop_front() else {
+                mailbox.receivers.push_back(id);
+                return;
+            };
+            promise_resolve::<(), M>(world, id, (), message);
+        },
+        move |world, id| {
+            if let Some(mut mailbox) = world.get_mut::<Mailbox<M>>(entity) {
+                mailbox.receivers.retain(|r| r != &id);
+            }
+        },
+    )
+}
+
+pub trait MailboxOpsExtension<S> {
+    fn recv<M: 'static + Send + Sync>(self, entity: Entity) -> Promise<S, M>;
+}
+impl<S: 'static> MailboxOpsExtension<S> for AsynOps<S> {
+    fn recv<M: 'static + Send + Sync>(self, entity: Entity) -> Promise<S, M> {
+        recv(entity).map(|_| self.0)
+    }
+}
+
+/// Delivers `message` to the [`Mailbox<M>`] of the `entity`
+pub struct SendToCommand<M> {
+    entity: Entity,
+    message: M,
+}
+
+impl<M: 'static + Send + Sync> Command for SendToCommand<M> {
+    fn apply(self, world: &mut World) {
+        let entity = self.entity;
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            warn!("Can't send {} to {entity:?}: entity does not exist", type_name::<M>());
+            return;
+        };
+        let mut mailbox = match entity_mut.get_mut::<Mailbox<M>>() {
+            Some(mailbox) => mailbox,
+            None => {
+                entity_mut.insert(Mailbox::<M>::default());
+                entity_mut.get_mut::<Mailbox<M>>().unwrap()
+            }
+        };
+        let Some(id) = mailbox.receivers.pop_front() else {
+            mailbox.messages.push_back(self.message);
+            return;
+        };
+        promise_resolve::<(), M>(world, id, (), self.message);
+    }
+}
+
+pub trait MailboxCommandsExtension {
+    /// Send `message` to the `entity` [`Mailbox<M>`]. The first promise
+    /// waiting for `M` with [`recv`] resolves with the message, or
+    /// the message is queued until the next `recv` if there are no waiters.
+    fn send_to<M: 'static + Send + Sync>(&mut self, entity: Entity, message: M);
+}
+
+impl<'w, 's> MailboxCommandsExtension for Commands<'w, 's> {
+    fn send_to<M: 'static + Send + Sync>(&mut self, entity: Entity, message: M) {
+        self.add(SendToCommand { entity, message });
+    }
+}
@@ -0,0 +1,56 @@
+//! Observer promises, available with the `bevy_0_14` feature only: the default Bevy 0.13
+//! build has no observers and doesn't include the module. [`observe()`] spawns the observer
+//! of the entity and resolves with the payload of the first [`Trigger`] it receives,
+//! so the observer-based event flows could be awaited in the promise chains:
+//! ```ignore
+//! #[derive(Event, Clone)]
+//! struct Exploded {
+//!     radius: f32,
+//! }
+//!
+//! fn arm_bomb(mut commands: Commands, bomb: Query<Entity, With<Bomb>>) {
+//!     commands.add(
+//!         asyn::observe::<Exploded>(bomb.single())
+//!             .then(asyn!(_, explosion => info!("Boom! Radius: {}", explosion.radius))),
+//!     );
+//! }
+//! ```
+//! The observer is despawned when it fires or when the promise is discarded.
+use super::*;
+
+/// Creates promise that observes the `entity` and resolves with the payload
+/// of the first `E` triggered for it
+pub fn observe<E: Event + Clone>(entity: Entity) -> Promise<(), E> {
+    let observer = Arc::new(Mutex::new(None));
+    let spawned = observer.clone();
+    Promise::register(
+        move |world, id| {
+            let mut waiter = Some(id);
+            let system = move |trigger: Trigger<E>, mut commands: Commands| {
+                // the observer could fire again before it is despawned
+                let Some(id) = waiter.take() else {
+                    return;
+                };
+                commands.add(PromiseCommand::resolve(id, trigger.event().clone()));
+                commands.entity(trigger.observer()).despawn();
+            };
+            let entity = world.spawn(Observer::new(system).with_entity(entity)).id();
+            *spawned.lock().unwrap() = Some(entity);
+        },
+        move |world, _| {
+            let observer = observer.lock().unwrap().take();
+            if let Some(entity) = observer.and_then(|entity| world.get_entity_mut(entity)) {
+                entity.despawn();
+            }
+        },
+    )
+}
+
+pub trait ObserveOpsExtension<S> {
+    fn observe<E: Event + Clone>(self, entity: Entity) -> Promise<S, E>;
+}
+impl<S: 'static> ObserveOpsExtension<S> for AsynOps<S> {
+    fn observe<E: Event + Clone>(self, entity: Entity) -> Promise<S, E> {
+        observe(entity).map(|_| self.0)
+    }
+}
@@ -0,0 +1,58 @@
+//! Platform event loop promises. Promises resolved during the frame keep running their
+//! handlers synchronously, so a long chain could block the browser main thread for a
+//! while. [`yield_to_browser()`][asyn::yield_to_browser] splits the chain between frames:
+//! ```ignore
+//! fn generate(mut commands: Commands) {
+//!     commands.add(Promise::repeat(0, asyn!(chunk => {
+//!         generate_chunk(*chunk);
+//!         *chunk += 1;
+//!         if *chunk == 64 {
+//!             return chunk.resolve(Repeat::Break(()));
+//!         }
+//!         // let the browser handle input and rendering before the next chunk
+//!         PromiseResult::Await(chunk.asyn().platform().yield_to_browser().with_result(Repeat::Continue))
+//!     })));
+//! }
+//! ```
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(platform)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves at the start of the next frame. On wasm the
+    /// frames are driven by `requestAnimationFrame`, so the browser gets the
+    /// control back before the promise resolves. On other platforms it just
+    /// yields to the next frame.
+    pub fn yield_to_browser() -> Promise<(), ()> {
+        Promise::register(
+            |world, id| {
+                world.resource_mut::<YieldWaiters>().0.push(id);
+            },
+            |world, id| {
+                world.resource_mut::<YieldWaiters>().0.retain(|waiter| waiter != &id);
+            },
+        )
+    }
+}
+
+pub struct PromisePlatformPlugin;
+impl Plugin for PromisePlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<YieldWaiters>();
+        app.add_systems(First, process_yields);
+    }
+}
+
+/// Promises waiting for the next frame
+#[derive(Default)]
+pub struct YieldWaiters(Vec<PromiseId>);
+impl Resource for YieldWaiters {}
+
+fn process_yields(mut commands: Commands, mut waiters: ResMut<YieldWaiters>) {
+    for id in waiters.0.drain(..) {
+        commands.add(PromiseCommand::resolve(id, ()));
+    }
+}
@@ -0,0 +1,205 @@
+//! Progress reporting: handlers of the chain report the progress with
+//! [`PromiseProgress::report`], the chain observed with
+//! [`on_progress()`][PromiseLikeBase::on_progress] receives it without
+//! side-channel resources:
+//! ```ignore
+//! fn download_levels(mut commands: Commands) {
+//!     commands
+//!         .promise(|| 0)
+//!         .then_repeat(asyn!(state, mut progress: ResMut<PromiseProgress> => {
+//!             progress.report(state.value as f32 / 10.);
+//!             state.value += 1;
+//!             if state.value > 10 {
+//!                 return state.resolve(Repeat::Break(()));
+//!             }
+//!             let url = format!("https://example.com/level{}", state.value);
+//!             PromiseResult::Await(state.asyn().http().get(url).send().with_result(Repeat::Continue))
+//!         }))
+//!         .on_progress(asyn!(_, progress, mut bar: Query<&mut Style, With<ProgressBar>> => {
+//!             bar.single_mut().width = Val::Percent(progress * 100.);
+//!         }))
+//!         .then(asyn!(_ => info!("All levels are downloaded")));
+//! }
+//! ```
+use super::*;
+
+/// Progress reported by the handlers of the chains observed with
+/// [`on_progress()`][PromiseLikeBase::on_progress]
+#[derive(Default)]
+pub struct PromiseProgress {
+    /// Observed chain of every pending promise registered inside the observed chains
+    scopes: HashMap<PromiseId, PromiseId>,
+    /// Observed chain of the running handler
+    current: Option<PromiseId>,
+    /// The last progress reported by the chains since the previous dispatch
+    reported: HashMap<PromiseId, f32>,
+    /// Listener loops of the observed chains
+    listeners: HashMap<PromiseId, PromiseId>,
+    /// Listener promises waiting for the next report
+    waiters: HashMap<PromiseId, PromiseId>,
+}
+impl Resource for PromiseProgress {}
+
+impl PromiseProgress {
+    /// Report the `progress` of the chain the running handler belongs to. The listener
+    /// receives the last progress reported during the frame. Ignored outside of the
+    /// observed chains.
+    pub fn report(&mut self, progress: f32) {
+        if let Some(scope) = self.current {
+            self.reported.insert(scope, progress);
+        }
+    }
+}
+
+pub struct PromiseProgressPlugin;
+impl Plugin for PromiseProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PromiseProgress>();
+        app.add_systems(Last, dispatch_progress);
+    }
+}
+
+impl<S: 'static, R: 'static> Promise<S, R> {
+    /// Runs `func` with the progress reported by the handlers of the promise chain
+    pub(crate) fn observe_progress(mut self, func: Asyn![(), f32 => (), ()]) -> Promise<S, R> {
+        let scope = self.id;
+        let register = self.register.take();
+        self.register = Some(Box::new(move |world, id| {
+            let Some(mut progress) = world.get_resource_mut::<PromiseProgress>() else {
+                errors::report(
+                    world,
+                    id,
+                    errors::PecsError::PluginMissing {
+                        plugin: "PromiseProgressPlugin",
+                    },
+                );
+                if let Some(register) = register {
+                    register(world, id)
+                }
+                return;
+            };
+            let listener = listen(scope, func);
+            progress.scopes.insert(scope, scope);
+            progress.listeners.insert(scope, listener.id);
+            promise_register::<(), ()>(world, listener);
+            in_scope(world, Some(scope), |world| {
+                if let Some(register) = register {
+                    register(world, id)
+                }
+            });
+        }));
+        let mut promise = self.map(move |s| (s, scope)).then(asyn!(s, r, world: &mut World => {
+            let (state, scope) = s.value;
+            stop(world, scope);
+            PromiseResult::Resolve(state, r)
+        }));
+        let discard = promise.discard.take();
+        promise.discard = Some(Box::new(move |world, id| {
+            stop(world, scope);
+            if let Some(discard) = discard {
+                discard(world, id)
+            }
+        }));
+        promise
+    }
+}
+
+/// Observed chain the pending promise `id` belongs to
+pub(crate) fn scope_of(world: &World, id: PromiseId) -> Option<PromiseId> {
+    world
+        .get_resource::<PromiseProgress>()
+        .and_then(|progress| progress.scopes.get(&id).copied())
+}
+
+/// Runs `f` with the reports and registered promises attributed to the `scope`
+pub(crate) fn in_scope<T>(world: &mut World, scope: Option<PromiseId>, f: impl FnOnce(&mut World) -> T) -> T {
+    let Some(mut progress) = world.get_resource_mut::<PromiseProgress>() else {
+        return f(world);
+    };
+    let outer = mem::replace(&mut progress.current, scope);
+    let result = f(world);
+    world.resource_mut::<PromiseProgress>().current = outer;
+    result
+}
+
+pub(crate) fn record(world: &mut World, id: PromiseId, status: inspect::PromiseStatus) {
+    let Some(mut progress) = world.get_resource_mut::<PromiseProgress>() else {
+        return;
+    };
+    match (status, progress.current) {
+        (inspect::PromiseStatus::Pending, Some(scope)) => {
+            progress.scopes.insert(id, scope);
+        }
+        (inspect::PromiseStatus::Pending, None) => {}
+        _ => {
+            progress.scopes.remove(&id);
+        }
+    }
+}
+
+/// Creates promise that resolves with the next progress reported by the `scope`
+fn next(scope: PromiseId) -> Promise<(), f32> {
+    Promise::register(
+        move |world, id| {
+            world.resource_mut::<PromiseProgress>().waiters.insert(scope, id);
+        },
+        move |world, _| {
+            world.resource_mut::<PromiseProgress>().waiters.remove(&scope);
+        },
+    )
+}
+
+/// Creates the loop running `func` with every progress reported by the `scope`
+fn listen(scope: PromiseId, func: Asyn![(), f32 => (), ()]) -> Promise<(), ()> {
+    Promise::repeat(
+        (scope, func),
+        asyn!(s => {
+            let scope = s.value.0;
+            next(scope).map(move |_| s.value).then(asyn!(s, progress, world: &mut World => {
+                let result: PromiseResult<(), ()> = s.value.1.run((PromiseState::new(()), progress), world).into();
+                if let PromiseResult::Await(promise) = result {
+                    promise_register::<(), ()>(world, promise);
+                }
+                s.resolve(Repeat::<()>::Continue)
+            }))
+        }),
+    )
+    .map(|_| ())
+}
+
+/// Delivers the last report of the `scope` and stops its listener
+fn stop(world: &mut World, scope: PromiseId) {
+    let Some(mut progress) = world.get_resource_mut::<PromiseProgress>() else {
+        return;
+    };
+    let last = progress.reported.remove(&scope);
+    let waiter = progress.waiters.remove(&scope);
+    let listener = progress.listeners.remove(&scope);
+    if let (Some(last), Some(waiter)) = (last, waiter) {
+        promise_resolve::<(), f32>(world, waiter, (), last);
+    }
+    if let Some(listener) = listener {
+        if promise_is_pending::<(), ()>(world, listener) {
+            promise_discard::<(), ()>(world, listener);
+        }
+    }
+}
+
+fn dispatch_progress(world: &mut World) {
+    let mut progress = world.resource_mut::<PromiseProgress>();
+    let reported = mem::take(&mut progress.reported);
+    let mut ready = Vec::with_capacity(reported.len());
+    for (scope, value) in reported {
+        match progress.waiters.remove(&scope) {
+            Some(waiter) => ready.push((waiter, value)),
+            // the listener is busy, deliver the report on the next frame
+            None if progress.listeners.contains_key(&scope) => {
+                progress.reported.insert(scope, value);
+            }
+            None => {}
+        }
+    }
+    for (waiter, value) in ready {
+        promise_resolve::<(), f32>(world, waiter, (), value);
+    }
+}
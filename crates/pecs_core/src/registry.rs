@@ -0,0 +1,213 @@
+//! Storage for registered promises. By default promises are stored in the
+//! `HashMap<PromiseId, _>`. With the `dense_registry` feature [`PromiseId`] holds
+//! an index into the dense arena with the generation, and registries are plain
+//! vectors indexed by it: lookups don't hash and freed indices are reused.
+//!
+//! Every `Promise<S, R>` type has its own index allocator, so the vectors only grow
+//! to the number of the promises of their type alive at once. The index is released
+//! when the promise is dropped: after it is resolved or discarded, or if it is never
+//! registered at all.
+use super::*;
+
+#[cfg(not(feature = "dense_registry"))]
+pub(crate) type PromiseMap<V> = HashMap<PromiseId, V>;
+
+/// Ids of the promises stored in the `map`
+#[cfg(not(feature = "dense_registry"))]
+pub(crate) fn ids<V>(map: &PromiseMap<V>) -> Vec<PromiseId> {
+    map.keys().copied().collect()
+}
+
+/// Keeps the index of the [`PromiseId`] allocated while the promise is alive
+#[cfg(not(feature = "dense_registry"))]
+pub(crate) struct Slot;
+
+#[cfg(not(feature = "dense_registry"))]
+impl<S, R> PromiseRegistry<S, R> {
+    /// Id of the new `Promise<S, R>` with its [`Slot`]
+    pub(crate) fn allocate() -> (PromiseId, Slot) {
+        (PromiseId::new(), Slot)
+    }
+}
+
+#[cfg(feature = "dense_registry")]
+pub(crate) use dense::*;
+
+#[cfg(feature = "dense_registry")]
+mod dense {
+    use std::any::TypeId;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct IdAllocator {
+        generations: Vec<u32>,
+        free: Vec<u32>,
+    }
+
+    /// Index allocator of the `Promise<S, R>` type, `registry` tells the ids
+    /// of the different types apart
+    struct Allocator {
+        registry: u32,
+        ids: Mutex<IdAllocator>,
+    }
+
+    /// Allocators of the promise types, looked up once per type and thread
+    static ALLOCATORS: Mutex<Vec<(TypeId, &'static Allocator)>> = Mutex::new(Vec::new());
+
+    thread_local! {
+        static CACHED: RefCell<HashMap<TypeId, &'static Allocator>> = RefCell::new(HashMap::default());
+    }
+
+    fn allocator<S: 'static, R: 'static>() -> &'static Allocator {
+        let type_id = TypeId::of::<PromiseRegistry<S, R>>();
+        CACHED.with(|cached| {
+            *cached.borrow_mut().entry(type_id).or_insert_with(|| {
+                let mut allocators = ALLOCATORS.lock().unwrap();
+                if let Some((_, allocator)) = allocators.iter().find(|(id, _)| id == &type_id) {
+                    return *allocator;
+                }
+                let allocator: &'static Allocator = Box::leak(Box::new(Allocator {
+                    registry: allocators.len() as u32,
+                    ids: Mutex::new(IdAllocator::default()),
+                }));
+                allocators.push((type_id, allocator));
+                allocator
+            })
+        })
+    }
+
+    impl<S: 'static, R: 'static> PromiseRegistry<S, R> {
+        /// Returns the id of the new `Promise<S, R>` with its [`Slot`]
+        pub(crate) fn allocate() -> (PromiseId, Slot) {
+            let allocator = allocator::<S, R>();
+            let mut ids = allocator.ids.lock().unwrap();
+            let (index, generation) = match ids.free.pop() {
+                Some(index) => (index, ids.generations[index as usize]),
+                None => {
+                    ids.generations.push(0);
+                    (ids.generations.len() as u32 - 1, 0)
+                }
+            };
+            let id = PromiseId {
+                registry: allocator.registry,
+                index,
+                generation,
+            };
+            (id, Slot { allocator, index })
+        }
+    }
+
+    /// Keeps the index of the [`PromiseId`] allocated while the promise is alive,
+    /// releases it for reuse with the next generation when the promise is dropped
+    pub(crate) struct Slot {
+        allocator: &'static Allocator,
+        index: u32,
+    }
+
+    impl Drop for Slot {
+        fn drop(&mut self) {
+            let mut ids = self.allocator.ids.lock().unwrap();
+            let generation = &mut ids.generations[self.index as usize];
+            *generation = generation.wrapping_add(1);
+            ids.free.push(self.index);
+        }
+    }
+
+    /// Ids of the promises stored in the `map`
+    pub(crate) fn ids<V>(map: &PromiseMap<V>) -> Vec<PromiseId> {
+        map.ids()
+    }
+
+    /// Vec-backed map with the subset of `HashMap<PromiseId, V>` api used by registries.
+    /// The map holds the ids of the single promise type, all of them share the `registry`.
+    pub(crate) struct PromiseMap<V>(Vec<Option<(PromiseId, V)>>);
+
+    impl<V> Default for PromiseMap<V> {
+        fn default() -> Self {
+            PromiseMap(Vec::new())
+        }
+    }
+
+    impl<V> PromiseMap<V> {
+        pub(crate) fn new() -> Self {
+            Self::default()
+        }
+        pub(crate) fn insert(&mut self, id: PromiseId, value: V) {
+            let index = id.index as usize;
+            if index >= self.0.len() {
+                self.0.resize_with(index + 1, || None);
+            }
+            self.0[index] = Some((id, value));
+        }
+        pub(crate) fn contains_key(&self, id: &PromiseId) -> bool {
+            matches!(self.0.get(id.index as usize), Some(Some((stored, _))) if stored == id)
+        }
+        pub(crate) fn get_mut(&mut self, id: &PromiseId) -> Option<&mut V> {
+            match self.0.get_mut(id.index as usize) {
+                Some(Some((stored, value))) if stored == id => Some(value),
+                _ => None,
+            }
+        }
+        pub(crate) fn ids(&self) -> Vec<PromiseId> {
+            self.0.iter().flatten().map(|(id, _)| *id).collect()
+        }
+        pub(crate) fn remove(&mut self, id: &PromiseId) -> Option<V> {
+            let slot = self.0.get_mut(id.index as usize)?;
+            if !matches!(slot, Some((stored, _)) if stored == id) {
+                return None;
+            }
+            slot.take().map(|(_, value)| value)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn promise<R: 'static>() -> Promise<(), R> {
+            Promise::register(|_, _| {}, |_, _| {})
+        }
+
+        #[test]
+        fn dropped_promise_index_is_reused() {
+            struct Reused;
+            let first = promise::<Reused>();
+            let stale = first.id;
+            // never registered, released on drop
+            drop(first);
+            let second = promise::<Reused>();
+            assert_eq!(second.id.index, stale.index);
+            assert_eq!(second.id.generation, stale.generation + 1);
+            assert_ne!(second.id, stale);
+        }
+
+        #[test]
+        fn stale_id_does_not_match_reused_index() {
+            struct Stale;
+            let first = promise::<Stale>();
+            let stale = first.id;
+            drop(first);
+            let second = promise::<Stale>();
+            let id = second.id;
+            let mut map = PromiseMap::new();
+            map.insert(id, second);
+            assert!(map.contains_key(&id));
+            assert!(!map.contains_key(&stale));
+            assert!(map.get_mut(&stale).is_none());
+            assert!(map.remove(&stale).is_none());
+            assert!(map.remove(&id).is_some());
+            assert!(map.ids().is_empty());
+        }
+
+        #[test]
+        fn promise_types_allocate_indices_independently() {
+            struct Many;
+            struct Single;
+            let many: Vec<_> = (0..16).map(|_| promise::<Many>()).collect();
+            let single = promise::<Single>();
+            assert_eq!(single.id.index, 0);
+            assert!(many.iter().all(|promise| promise.id != single.id));
+        }
+    }
+}
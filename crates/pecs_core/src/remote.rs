@@ -0,0 +1,192 @@
+//! Development channel for resolving promises from outside of the app,
+//! available with the `remote` feature. Promises created with
+//! [`asyn::remote::value()`][asyn::value] are resolved by their labels with
+//! [reflected](bevy::reflect) values sent as
+//! [RON](https://github.com/ron-rs/ron) over the TCP connection:
+//! ```text
+//! $ echo 'login (name: "admin", token: "42")' | nc 127.0.0.1 15702
+//! ok
+//! ```
+//! Each line of the connection is `<label> <ron payload>`, the response line is
+//! `ok` or `error: <reason>`. The channel is not authenticated, use it in dev builds only.
+use std::any::TypeId;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use bevy::reflect::{serde::TypedReflectDeserializer, FromReflect, GetTypeRegistration, TypePath};
+use serde::de::DeserializeSeed;
+
+use super::*;
+
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves with the value of type `R` sent to the `label`
+    /// through the remote channel. Useful for simulating server responses or user
+    /// input while developing the flow:
+    /// ```ignore
+    /// #[derive(Reflect, Default)]
+    /// struct Login {
+    ///     name: String,
+    ///     token: String,
+    /// }
+    ///
+    /// fn setup(mut commands: Commands) {
+    ///     commands.add(
+    ///         Promise::start(asyn!(_ => {
+    ///             asyn::remote::value::<Login>("login")
+    ///         }))
+    ///         .then(asyn!(_, login => {
+    ///             info!("Logged in as {}", login.name);
+    ///         })),
+    ///     );
+    /// }
+    /// ```
+    pub fn value<R: FromReflect + GetTypeRegistration + TypePath>(label: &'static str) -> Promise<(), R> {
+        Promise::register(
+            move |world, id| {
+                world.resource::<AppTypeRegistry>().write().register::<R>();
+                let mut remote = world.resource_mut::<RemotePromises>();
+                if remote.waiters.contains_key(label) {
+                    warn!("Remote label \"{label}\" is already used, {id} replaces the previous promise");
+                }
+                remote.waiters.insert(label, (id, resolve_reflected::<R>));
+            },
+            move |world, id| {
+                let mut remote = world.resource_mut::<RemotePromises>();
+                if remote.waiters.get(label).is_some_and(|(waiter, _)| waiter == &id) {
+                    remote.waiters.remove(label);
+                }
+            },
+        )
+    }
+}
+
+type RemoteResolve = fn(&mut World, PromiseId, &str) -> Result<(), String>;
+
+struct RemoteRequest {
+    label: String,
+    payload: String,
+    reply: Sender<String>,
+}
+
+/// Promises waiting for the values from the remote channel
+pub struct RemotePromises {
+    waiters: HashMap<&'static str, (PromiseId, RemoteResolve)>,
+    requests: Mutex<Receiver<RemoteRequest>>,
+}
+impl Resource for RemotePromises {}
+
+/// Listens for remote values on the `address`, `127.0.0.1:15702` by default
+pub struct PromiseRemotePlugin {
+    pub address: String,
+}
+impl Default for PromiseRemotePlugin {
+    fn default() -> Self {
+        PromiseRemotePlugin {
+            address: "127.0.0.1:15702".to_string(),
+        }
+    }
+}
+
+impl Plugin for PromiseRemotePlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = channel();
+        match TcpListener::bind(&self.address) {
+            Ok(listener) => {
+                info!("Listening for remote promise values on {}", self.address);
+                thread::spawn(move || {
+                    for stream in listener.incoming().flatten() {
+                        let sender = sender.clone();
+                        thread::spawn(move || handle_connection(stream, sender));
+                    }
+                });
+            }
+            Err(e) => error!("Can't listen for remote promise values on {}: {e}", self.address),
+        }
+        app.insert_resource(RemotePromises {
+            waiters: HashMap::new(),
+            requests: Mutex::new(receiver),
+        });
+        app.add_systems(Update, process_remote_requests);
+    }
+}
+
+fn handle_connection(stream: TcpStream, sender: Sender<RemoteRequest>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (label, payload) = line.split_once(char::is_whitespace).unwrap_or((line, "()"));
+        let (reply, response) = channel();
+        let request = RemoteRequest {
+            label: label.to_string(),
+            payload: payload.trim().to_string(),
+            reply,
+        };
+        if sender.send(request).is_err() {
+            return;
+        }
+        let response = response.recv().unwrap_or_else(|_| "error: app is closed".to_string());
+        if writeln!(writer, "{response}").is_err() {
+            return;
+        }
+    }
+}
+
+fn process_remote_requests(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource::<RemotePromises>()
+        .requests
+        .lock()
+        .unwrap()
+        .try_iter()
+        .collect();
+    for request in requests {
+        let Some(&(id, resolve)) = world.resource::<RemotePromises>().waiters.get(request.label.as_str()) else {
+            let _ = request
+                .reply
+                .send(format!("error: no promise waits for \"{}\"", request.label));
+            continue;
+        };
+        // the promise keeps waiting if the payload is invalid
+        let response = match resolve(world, id, &request.payload) {
+            Ok(()) => "ok".to_string(),
+            Err(e) => format!("error: {e}"),
+        };
+        let _ = request.reply.send(response);
+    }
+}
+
+fn resolve_reflected<R: FromReflect + GetTypeRegistration + TypePath>(
+    world: &mut World,
+    id: PromiseId,
+    payload: &str,
+) -> Result<(), String> {
+    let value = {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let Some(registration) = registry.get(TypeId::of::<R>()) else {
+            return Err(format!("{} is not registered", R::type_path()));
+        };
+        let mut deserializer = ron::Deserializer::from_str(payload).map_err(|e| e.to_string())?;
+        let reflected = TypedReflectDeserializer::new(registration, &registry)
+            .deserialize(&mut deserializer)
+            .map_err(|e| e.to_string())?;
+        R::from_reflect(&*reflected).ok_or_else(|| format!("Can't convert payload to {}", R::type_path()))?
+    };
+    world
+        .resource_mut::<RemotePromises>()
+        .waiters
+        .retain(|_, (waiter, _)| waiter != &id);
+    promise_resolve::<(), R>(world, id, (), value);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Camera viewport promises: wait for the camera to get a valid render target
+//! before placing UI or computing projections, and for its viewport to change.
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(render)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves with the logical viewport size of the `camera`
+    /// when it has a valid target with non-zero size, immediately if it already has.
+    /// ```ignore
+    /// fn setup(mut commands: Commands) {
+    ///     let camera = commands.spawn(Camera2dBundle::default()).id();
+    ///     commands.add(asyn::render::camera_ready(camera).then(asyn!(_, size => {
+    ///         info!("Placing the HUD into {size}");
+    ///     })));
+    /// }
+    /// ```
+    pub fn camera_ready(camera: Entity) -> Promise<(), Vec2> {
+        Promise::register(
+            move |world, id| {
+                if let Some(size) = viewport_size(world, camera) {
+                    promise_resolve::<(), Vec2>(world, id, (), size);
+                    return;
+                }
+                world.resource_mut::<CameraWaiters>().ready.push((id, camera));
+            },
+            |world, id| {
+                world
+                    .resource_mut::<CameraWaiters>()
+                    .ready
+                    .retain(|(waiter, _)| waiter != &id);
+            },
+        )
+    }
+
+    /// Creates promise that resolves with the new logical viewport size of the
+    /// `camera` when it differs from the size the promise is started with.
+    pub fn viewport_resized(camera: Entity) -> Promise<(), Vec2> {
+        Promise::register(
+            move |world, id| {
+                let size = viewport_size(world, camera);
+                world.resource_mut::<CameraWaiters>().resized.push((id, camera, size));
+            },
+            |world, id| {
+                world
+                    .resource_mut::<CameraWaiters>()
+                    .resized
+                    .retain(|(waiter, _, _)| waiter != &id);
+            },
+        )
+    }
+}
+
+pub struct PromiseRenderPlugin;
+impl Plugin for PromiseRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraWaiters>();
+        app.add_systems(Update, process_cameras);
+    }
+}
+
+/// Promises waiting for the camera viewports
+#[derive(Default)]
+pub struct CameraWaiters {
+    ready: Vec<(PromiseId, Entity)>,
+    resized: Vec<(PromiseId, Entity, Option<Vec2>)>,
+}
+impl Resource for CameraWaiters {}
+
+/// Logical viewport size of the `camera` if it is valid and non-zero
+fn viewport_size(world: &World, camera: Entity) -> Option<Vec2> {
+    world.get::<Camera>(camera).and_then(valid_size)
+}
+
+fn valid_size(camera: &Camera) -> Option<Vec2> {
+    camera.logical_viewport_size().filter(|size| size.x > 0. && size.y > 0.)
+}
+
+fn process_cameras(mut commands: Commands, mut waiters: ResMut<CameraWaiters>, cameras: Query<&Camera>) {
+    waiters.ready.retain(|(id, camera)| {
+        let Some(size) = cameras.get(*camera).ok().and_then(valid_size) else {
+            return true;
+        };
+        commands.add(PromiseCommand::resolve(*id, size));
+        false
+    });
+    waiters.resized.retain(
+        |(id, camera, started)| match cameras.get(*camera).ok().and_then(valid_size) {
+            Some(size) if Some(size) != *started => {
+                commands.add(PromiseCommand::resolve(*id, size));
+                false
+            }
+            _ => true,
+        },
+    );
+}
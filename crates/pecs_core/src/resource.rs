@@ -0,0 +1,114 @@
+//! Resource lifecycle promises: wait for a resource to be inserted, changed or
+//! removed instead of polling for it in the repeat loops.
+//! ```ignore
+//! fn bootstrap(mut commands: Commands) {
+//!     commands.add(asyn::resource::inserted::<Config>().then(asyn!(_, config => {
+//!         info!("Starting with {} players", config.players);
+//!     })));
+//! }
+//!
+//! fn apply_settings(mut commands: Commands) {
+//!     commands.add(asyn::resource::changed::<Settings>().then(asyn!(_, settings, mut windows: Query<&mut Window> => {
+//!         windows.single_mut().mode = settings.window_mode;
+//!     })));
+//! }
+//! ```
+use bevy::ecs::component::Tick;
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(resource)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves with the clone of the `T` resource when it is
+    /// inserted, immediately if it already exists.
+    pub fn inserted<T: Resource + Clone>() -> Promise<(), T> {
+        wait(poll_inserted::<T>)
+    }
+
+    /// Creates promise that resolves with the clone of the `T` resource when it is
+    /// changed or inserted after the promise starts.
+    pub fn changed<T: Resource + Clone>() -> Promise<(), T> {
+        wait(poll_changed::<T>)
+    }
+
+    /// Creates promise that resolves when the `T` resource is removed,
+    /// immediately if it doesn't exist.
+    pub fn removed<T: Resource>() -> Promise<(), ()> {
+        wait(poll_removed::<T>)
+    }
+}
+
+pub struct PromiseResourcePlugin;
+impl Plugin for PromiseResourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ResourceWaiters>();
+        app.add_systems(Update, process_resource_waiters);
+    }
+}
+
+/// Resolves the promise if the resource is ready, returns `true` when resolved.
+/// Changes made after the `since` tick are detected.
+type ResourcePoll = fn(&mut World, PromiseId, Tick) -> bool;
+
+/// Promises waiting for the resources
+#[derive(Default)]
+pub struct ResourceWaiters(Vec<(PromiseId, Tick, ResourcePoll)>);
+impl Resource for ResourceWaiters {}
+
+fn wait<R: 'static>(poll: ResourcePoll) -> Promise<(), R> {
+    Promise::register(
+        move |world, id| {
+            let since = world.change_tick();
+            if !poll(world, id, since) {
+                world.resource_mut::<ResourceWaiters>().0.push((id, since, poll));
+            }
+        },
+        |world, id| {
+            world
+                .resource_mut::<ResourceWaiters>()
+                .0
+                .retain(|(waiter, _, _)| waiter != &id);
+        },
+    )
+}
+
+fn poll_inserted<T: Resource + Clone>(world: &mut World, id: PromiseId, _: Tick) -> bool {
+    let Some(resource) = world.get_resource::<T>().cloned() else {
+        return false;
+    };
+    promise_resolve::<(), T>(world, id, (), resource);
+    true
+}
+
+fn poll_changed<T: Resource + Clone>(world: &mut World, id: PromiseId, since: Tick) -> bool {
+    let this_run = world.read_change_tick();
+    let changed = world
+        .get_resource_change_ticks::<T>()
+        .is_some_and(|ticks| ticks.is_changed(since, this_run));
+    if !changed {
+        return false;
+    }
+    poll_inserted::<T>(world, id, since)
+}
+
+fn poll_removed<T: Resource>(world: &mut World, id: PromiseId, _: Tick) -> bool {
+    if world.contains_resource::<T>() {
+        return false;
+    }
+    promise_resolve::<(), ()>(world, id, (), ());
+    true
+}
+
+fn process_resource_waiters(world: &mut World) {
+    let waiters = mem::take(&mut world.resource_mut::<ResourceWaiters>().0);
+    let mut pending = Vec::with_capacity(waiters.len());
+    for (id, since, poll) in waiters {
+        if !poll(world, id, since) {
+            pending.push((id, since, poll));
+        }
+    }
+    world.resource_mut::<ResourceWaiters>().0.extend(pending);
+}
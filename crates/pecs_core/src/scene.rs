@@ -0,0 +1,509 @@
+//! Scene transitions: fade the screen out, switch the state, wait
+//! for the preloaded assets and fade the screen back in.
+//!
+//! [`asyn::scene::spawn()`][asyn::spawn] spawns the scene and resolves with its root
+//! entity when the scene instance is ready:
+//! ```ignore
+//! fn spawn_level(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::scene::spawn("level.glb#Scene0")
+//!             .then_ok(asyn!(_, root, mut loading: ResMut<LoadingScreen> => {
+//!                 loading.hide();
+//!                 info!("The level is spawned at {root:?}");
+//!             }))
+//!             .catch(asyn!(_, error => error!("Can't spawn the level: {error}"))),
+//!     );
+//! }
+//! ```
+use std::fmt;
+
+use bevy::{
+    asset::{AssetPath, UntypedHandle},
+    scene::SceneInstance,
+};
+use pecs_compat::FreelyMutableState;
+
+use super::*;
+
+pub mod asyn {
+    use super::*;
+
+    /// Creates [`Transition`] to the `state`. Use [`Transition::with_fade`]
+    /// and [`Transition::preload`] to configure it.
+    /// ```ignore
+    /// fn start_level(mut commands: Commands, assets: Res<AssetServer>) {
+    ///     let level: Handle<Scene> = assets.load("level2.glb#Scene0");
+    ///     commands.add(
+    ///         Promise::start(asyn!(_ => {
+    ///             asyn::scene::transition(GameState::Level2)
+    ///                 .with_fade(0.5)
+    ///                 .preload(level)
+    ///         }))
+    ///         .then_ok(asyn!(_, _ => {
+    ///             info!("Welcome to the level 2");
+    ///         }))
+    ///         .catch(asyn!(_, error => error!("Can't start the level 2: {error}"))),
+    ///     );
+    /// }
+    /// ```
+    pub fn transition<T: FreelyMutableState>(state: T) -> Transition<T> {
+        Transition {
+            state,
+            fade: 0.,
+            color: Color::BLACK,
+            preload: vec![],
+        }
+    }
+
+    /// Creates promise that spawns full-screen overlay and fades it from
+    /// transparent to `color` in `duration` seconds. Resolves with the overlay
+    /// entity which should be passed to [`fade_in`] later.
+    pub fn fade_out(duration: f32, color: Color) -> Promise<(), Entity> {
+        Promise::register(
+            move |world, id| {
+                world.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Percent(100.),
+                            height: Val::Percent(100.),
+                            ..default()
+                        },
+                        background_color: pecs_compat::color_with_alpha(color, 0.).into(),
+                        z_index: ZIndex::Global(i32::MAX),
+                        ..default()
+                    },
+                    ScreenFade {
+                        promise: id,
+                        from: 0.,
+                        to: 1.,
+                        duration,
+                        elapsed: 0.,
+                    },
+                ));
+            },
+            discard_fade,
+        )
+    }
+
+    /// Creates promise that spawns the `scene` with [`SceneBundle`] and resolves with
+    /// the root entity when the scene instance is ready, or with the error if the
+    /// scene fails to load. Discarding the promise despawns the root entity.
+    pub fn spawn(scene: impl Into<SceneSource>) -> Promise<(), Result<Entity, SceneError>> {
+        let scene = scene.into();
+        Promise::register(
+            move |world, id| {
+                let handle = match scene {
+                    SceneSource::Handle(handle) => handle,
+                    SceneSource::Path(path) => {
+                        let Some(server) = world.get_resource::<AssetServer>() else {
+                            errors::report(world, id, errors::PecsError::PluginMissing { plugin: "AssetPlugin" });
+                            let error = SceneError::AssetPluginMissing;
+                            promise_resolve::<(), Result<Entity, SceneError>>(world, id, (), Err(error));
+                            return;
+                        };
+                        server.load(path)
+                    }
+                };
+                let root = world
+                    .spawn(SceneBundle {
+                        scene: handle,
+                        ..default()
+                    })
+                    .id();
+                world.resource_mut::<SceneSpawns>().0.push((id, root));
+            },
+            |world, id| {
+                let mut spawns = world.resource_mut::<SceneSpawns>();
+                let Some(index) = spawns.0.iter().position(|(waiter, _)| waiter == &id) else {
+                    return;
+                };
+                let (_, root) = spawns.0.remove(index);
+                despawn_with_children_recursive(world, root);
+            },
+        )
+    }
+
+    /// Creates promise that fades the `overlay` created by [`fade_out`]
+    /// to transparent in `duration` seconds and despawns it.
+    pub fn fade_in(overlay: Entity, duration: f32) -> Promise<(), ()> {
+        Promise::<(), Entity>::register(
+            move |world, id| {
+                let Some(mut entity) = world.get_entity_mut(overlay) else {
+                    warn!("Can't fade in {id}: overlay {overlay:?} does not exist");
+                    promise_resolve::<(), Entity>(world, id, (), overlay);
+                    return;
+                };
+                entity.insert(ScreenFade {
+                    promise: id,
+                    from: 1.,
+                    to: 0.,
+                    duration,
+                    elapsed: 0.,
+                });
+            },
+            discard_fade,
+        )
+        .with_result(())
+    }
+}
+
+pub struct PromiseScenePlugin;
+impl Plugin for PromiseScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Waiters>();
+        app.init_resource::<SceneSpawns>();
+        app.add_systems(Update, (process_fades, process_waiters, process_scene_spawns));
+    }
+}
+
+/// The scene spawned by [`asyn::spawn`]: the asset path or the scene handle
+pub enum SceneSource {
+    Path(AssetPath<'static>),
+    Handle(Handle<Scene>),
+}
+
+impl From<Handle<Scene>> for SceneSource {
+    fn from(handle: Handle<Scene>) -> Self {
+        SceneSource::Handle(handle)
+    }
+}
+
+impl From<AssetPath<'static>> for SceneSource {
+    fn from(path: AssetPath<'static>) -> Self {
+        SceneSource::Path(path)
+    }
+}
+
+impl From<&'static str> for SceneSource {
+    fn from(path: &'static str) -> Self {
+        SceneSource::Path(path.into())
+    }
+}
+
+impl From<String> for SceneSource {
+    fn from(path: String) -> Self {
+        SceneSource::Path(path.into())
+    }
+}
+
+/// Why the scene transition or spawn failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SceneError {
+    /// The state of the transition is not registered with `init_state`
+    StateNotRegistered(&'static str),
+    /// The scene is spawned by the path, but `AssetPlugin` is not added
+    AssetPluginMissing,
+    /// The scene failed to load from the path
+    LoadFailed(String),
+    /// The root entity of the scene was despawned before the scene was ready
+    RootDespawned(Entity),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::StateNotRegistered(state) => write!(f, "{state} is not registered"),
+            SceneError::AssetPluginMissing => write!(f, "AssetPlugin is not added"),
+            SceneError::LoadFailed(path) => write!(f, "failed to load the scene from {path}"),
+            SceneError::RootDespawned(root) => write!(f, "scene root {root:?} is despawned"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// Transition to the `T` state configured with [`asyn::transition`]
+pub struct Transition<T: FreelyMutableState> {
+    state: T,
+    fade: f32,
+    color: Color,
+    preload: Vec<UntypedHandle>,
+}
+
+impl<T: FreelyMutableState> Transition<T> {
+    /// Fade the screen out and in for `duration` seconds each
+    pub fn with_fade(mut self, duration: f32) -> Self {
+        self.fade = duration;
+        self
+    }
+    /// Color of the fade overlay, black by default
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    /// Keep the screen faded until the `handle` asset and its dependencies are loaded
+    pub fn preload<A: Asset>(mut self, handle: Handle<A>) -> Self {
+        self.preload.push(handle.untyped());
+        self
+    }
+    /// Starts the transition. Fails with [`SceneError::StateNotRegistered`] and removes
+    /// the overlay right away if the `T` state is not registered with `init_state`.
+    pub fn start(self) -> Promise<(), Result<(), SceneError>> {
+        let Transition {
+            state,
+            fade,
+            color,
+            preload,
+        } = self;
+        asyn::fade_out(fade, color)
+            .map(move |_| (state, preload))
+            .then(asyn!(s, overlay => {
+                let (state, preload) = s.value;
+                switch_state(state, preload).map_result(move |result| (overlay, result))
+            }))
+            .map(move |_| fade)
+            .then(asyn!(s, (overlay, result), mut commands: Commands => {
+                match result {
+                    Ok(()) => PromiseResult::Await(asyn::fade_in(overlay, s.value).with_result(Ok(()))),
+                    Err(error) => {
+                        commands.entity(overlay).despawn_recursive();
+                        PromiseResult::Resolve((), Err(error))
+                    }
+                }
+            }))
+    }
+}
+
+impl<T: FreelyMutableState> From<Transition<T>> for PromiseResult<(), Result<(), SceneError>> {
+    fn from(value: Transition<T>) -> Self {
+        PromiseResult::Await(value.start())
+    }
+}
+
+pub struct StatefulTransition<S, T: FreelyMutableState>(S, Transition<T>);
+impl<S: 'static, T: FreelyMutableState> StatefulTransition<S, T> {
+    pub fn with_fade(mut self, duration: f32) -> Self {
+        self.1 = self.1.with_fade(duration);
+        self
+    }
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.1 = self.1.with_color(color);
+        self
+    }
+    pub fn preload<A: Asset>(mut self, handle: Handle<A>) -> Self {
+        self.1 = self.1.preload(handle);
+        self
+    }
+    pub fn start(self) -> Promise<S, Result<(), SceneError>> {
+        self.1.start().map(move |_| self.0)
+    }
+}
+
+impl<S: 'static, T: FreelyMutableState> From<StatefulTransition<S, T>> for PromiseResult<S, Result<(), SceneError>> {
+    fn from(value: StatefulTransition<S, T>) -> Self {
+        PromiseResult::Await(value.start())
+    }
+}
+
+pub struct StatefulAsynScene<S>(S);
+impl<S: 'static> StatefulAsynScene<S> {
+    pub fn transition<T: FreelyMutableState>(self, state: T) -> StatefulTransition<S, T> {
+        StatefulTransition(self.0, asyn::transition(state))
+    }
+}
+
+pub trait SceneOpsExtension<S> {
+    fn scene(self) -> StatefulAsynScene<S>;
+}
+impl<S: 'static> SceneOpsExtension<S> for AsynOps<S> {
+    fn scene(self) -> StatefulAsynScene<S> {
+        StatefulAsynScene(self.0)
+    }
+}
+
+/// Animates the full-screen overlay alpha
+pub struct ScreenFade {
+    promise: PromiseId,
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+}
+pecs_compat::impl_component!(ScreenFade);
+
+fn discard_fade(world: &mut World, id: PromiseId) {
+    if let Some(overlay) = world
+        .query::<(Entity, &ScreenFade)>()
+        .iter(world)
+        .find(|(_, fade)| fade.promise == id)
+        .map(|(entity, _)| entity)
+    {
+        world.despawn(overlay);
+    }
+}
+
+fn process_fades(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut fades: Query<(Entity, &mut ScreenFade, &mut BackgroundColor)>,
+) {
+    for (entity, mut fade, mut color) in fades.iter_mut() {
+        fade.elapsed += time.delta_seconds();
+        let t = if fade.duration > 0. {
+            (fade.elapsed / fade.duration).min(1.)
+        } else {
+            1.
+        };
+        color.0 = pecs_compat::color_with_alpha(color.0, fade.from + (fade.to - fade.from) * t);
+        if t >= 1. {
+            if fade.to > 0. {
+                commands.entity(entity).remove::<ScreenFade>();
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+            commands.add(PromiseCommand::resolve(fade.promise, entity));
+        }
+    }
+}
+
+type WaitCondition = Box<dyn Fn(&World) -> bool + Send + Sync>;
+
+/// Promises waiting for the world condition to become `true`
+#[derive(Default)]
+pub struct Waiters(Vec<(PromiseId, WaitCondition)>);
+impl Resource for Waiters {}
+
+/// Creates promise that sets the next `state` and resolves when
+/// the state is applied and all `preload` assets are loaded
+fn switch_state<T: FreelyMutableState>(state: T, preload: Vec<UntypedHandle>) -> Promise<(), Result<(), SceneError>> {
+    Promise::register(
+        move |world, id| {
+            let Some(mut next) = world.get_resource_mut::<NextState<T>>() else {
+                let state = type_name::<T>();
+                errors::report(world, id, errors::PecsError::StateNotRegistered { state });
+                let error = SceneError::StateNotRegistered(state);
+                promise_resolve::<(), Result<(), SceneError>>(world, id, (), Err(error));
+                return;
+            };
+            next.set(state.clone());
+            world.resource_mut::<Waiters>().0.push((
+                id,
+                Box::new(move |world| {
+                    let applied = world.get_resource::<State<T>>().map(|s| s.get() == &state);
+                    // apps without `AssetPlugin` have nothing to preload
+                    let loaded = preload.is_empty()
+                        || world
+                            .get_resource::<AssetServer>()
+                            .is_some_and(|server| preload.iter().all(|h| server.is_loaded_with_dependencies(h.id())));
+                    applied.unwrap_or(false) && loaded
+                }),
+            ));
+        },
+        |world, id| {
+            world.resource_mut::<Waiters>().0.retain(|(waiter, _)| waiter != &id);
+        },
+    )
+}
+
+fn process_waiters(world: &mut World) {
+    let waiters = mem::take(&mut world.resource_mut::<Waiters>().0);
+    let (ready, pending): (Vec<_>, Vec<_>) = waiters.into_iter().partition(|(_, ready)| ready(world));
+    world.resource_mut::<Waiters>().0.extend(pending);
+    for (id, _) in ready {
+        promise_resolve::<(), Result<(), SceneError>>(world, id, (), Ok(()));
+    }
+}
+
+/// Promises waiting for the spawned scenes along with their root entities
+#[derive(Default)]
+pub struct SceneSpawns(Vec<(PromiseId, Entity)>);
+impl Resource for SceneSpawns {}
+
+fn process_scene_spawns(world: &mut World) {
+    let spawns = mem::take(&mut world.resource_mut::<SceneSpawns>().0);
+    let mut pending = Vec::with_capacity(spawns.len());
+    let mut ready = vec![];
+    let mut failed_roots = vec![];
+    for (id, root) in spawns {
+        let Some(entity) = world.get_entity(root) else {
+            ready.push((id, Err(SceneError::RootDespawned(root))));
+            continue;
+        };
+        if let Some(instance) = entity.get::<SceneInstance>() {
+            if world.resource::<SceneSpawner>().instance_is_ready(**instance) {
+                ready.push((id, Ok(root)));
+            } else {
+                pending.push((id, root));
+            }
+            continue;
+        }
+        let server = world.get_resource::<AssetServer>();
+        let failed = entity.get::<Handle<Scene>>().filter(|handle| {
+            server
+                .and_then(|server| server.get_load_state(handle.id()))
+                .is_some_and(|state| pecs_compat::load_failed(&state))
+        });
+        match failed {
+            Some(handle) => {
+                let path = handle.path().map(|path| path.to_string()).unwrap_or_default();
+                ready.push((id, Err(SceneError::LoadFailed(path))));
+                failed_roots.push(root);
+            }
+            None => pending.push((id, root)),
+        }
+    }
+    world.resource_mut::<SceneSpawns>().0.extend(pending);
+    for root in failed_roots {
+        despawn_with_children_recursive(world, root);
+    }
+    for (id, result) in ready {
+        promise_resolve::<(), Result<Entity, SceneError>>(world, id, (), result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    enum Level {
+        Game,
+    }
+    #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+    impl bevy::state::state::States for Level {}
+    impl FreelyMutableState for Level {}
+
+    #[derive(Default)]
+    struct Outcome(Option<Result<(), SceneError>>);
+    impl Resource for Outcome {}
+
+    #[derive(Default)]
+    struct Spawned(Option<Result<Entity, SceneError>>);
+    impl Resource for Spawned {}
+
+    #[test]
+    fn transition_to_unregistered_state_fails_and_removes_overlay() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Waiters>();
+        world.init_resource::<Outcome>();
+        let transition = asyn::transition(Level::Game)
+            .start()
+            .then(asyn!(_, result, mut outcome: ResMut<Outcome> => {
+                outcome.0 = Some(result);
+            }));
+        promise_register(&mut world, transition);
+        let overlay = world.query_filtered::<Entity, With<ScreenFade>>().single(&world);
+        world.run_system_once(process_fades);
+        let error = SceneError::StateNotRegistered(type_name::<Level>());
+        assert_eq!(world.resource::<Outcome>().0, Some(Err(error)));
+        assert!(world.get_entity(overlay).is_none());
+        debug::assert_no_leaks(&world);
+    }
+
+    #[test]
+    fn spawn_by_path_without_asset_plugin_fails() {
+        let mut world = World::new();
+        world.init_resource::<SceneSpawns>();
+        world.init_resource::<Spawned>();
+        let spawn = asyn::spawn("level.glb#Scene0").then(asyn!(_, result, mut spawned: ResMut<Spawned> => {
+            spawned.0 = Some(result);
+        }));
+        promise_register(&mut world, spawn);
+        assert_eq!(world.resource::<Spawned>().0, Some(Err(SceneError::AssetPluginMissing)));
+        debug::assert_no_leaks(&world);
+    }
+}
@@ -0,0 +1,91 @@
+//! Sharing heavy state between parallel branches without cloning it:
+//! convert the state into `Arc<S>` with [`shared()`][PromiseState::shared],
+//! start branches reading it with [`fork()`][PromiseState::fork] and get the
+//! value back at the join point with [`try_unwrap()`][PromiseState::try_unwrap]
+//! or [`unshared()`][PromiseLikeUnshared::unshared]. Stateful ops and combinators
+//! consume the state, start them with [`by_ref()`][PromiseState::by_ref] to keep
+//! using the state in the handler.
+use super::*;
+
+impl<S: 'static> PromiseState<S> {
+    /// Create new `PromiseState<Arc<S>>` moving the value into the [`Arc`]
+    pub fn shared(self) -> PromiseState<Arc<S>> {
+        self.map(Arc::new)
+    }
+}
+
+impl<S: 'static> PromiseState<Arc<S>> {
+    /// Create new promise from the [`Asyn!`]`[Arc<S> => S2, R]` func with the shared
+    /// state value. The branch state is dropped when the func resolves, so the branch
+    /// could be passed to [`Promise::all()`] or [`Promise::any()`] directly.
+    /// ```ignore
+    /// commands
+    ///     .promise(|| Level::load())
+    ///     .then(asyn!(state => {
+    ///         let state = state.shared();
+    ///         let terrain = state.fork(asyn!(level => level.value.spawn_terrain()));
+    ///         let enemies = state.fork(asyn!(level => level.value.spawn_enemies()));
+    ///         state.all((terrain, enemies))
+    ///     }))
+    ///     .then(asyn!(state, _ => {
+    ///         // both branches are resolved, the state is not shared anymore
+    ///         let level = state.try_unwrap().ok().unwrap();
+    ///         info!("{} is ready", level.value.name);
+    ///     }));
+    /// ```
+    pub fn fork<S2: 'static, R: 'static>(&self, func: Asyn![Arc<S> => S2, R]) -> Promise<(), R> {
+        Promise::new(self.value.clone(), func).map(|_| ())
+    }
+
+    /// Create new state referencing the same value, so the stateful ops and combinators
+    /// could be started without moving the state out of the handler:
+    /// ```ignore
+    /// asyn!(state => {
+    ///     let state = state.shared();
+    ///     let wait = state.by_ref().asyn().timeout(1.0);
+    ///     info!("Waiting with {} entities", state.value.len());
+    ///     wait
+    /// })
+    /// ```
+    pub fn by_ref(&self) -> PromiseState<Arc<S>> {
+        PromiseState::new(self.value.clone())
+    }
+
+    /// Returns the state value if this is the only reference to it,
+    /// otherwise returns the state as is.
+    pub fn try_unwrap(self) -> Result<PromiseState<S>, PromiseState<Arc<S>>> {
+        Arc::try_unwrap(self.value)
+            .map(PromiseState::new)
+            .map_err(PromiseState::new)
+    }
+}
+
+impl<S: 'static + Clone> PromiseState<Arc<S>> {
+    /// Returns the state value, cloning it if there are other references
+    pub fn unwrap_or_clone(self) -> PromiseState<S> {
+        self.map(|value| Arc::try_unwrap(value).unwrap_or_else(|value| (*value).clone()))
+    }
+}
+
+pub trait PromiseLikeShared<S: 'static, R: 'static>
+where
+    Self: Sized + PromiseLikeBase<S, R>,
+{
+    /// Create new [`PromiseLike<Arc<S>, R>`] with the state moved into the [`Arc`]
+    fn shared(self) -> Self::Promise<Arc<S>, R> {
+        self.map(Arc::new)
+    }
+}
+impl<S: 'static, R: 'static, P: PromiseLikeBase<S, R>> PromiseLikeShared<S, R> for P {}
+
+pub trait PromiseLikeUnshared<S: 'static + Clone, R: 'static>
+where
+    Self: Sized + PromiseLikeBase<Arc<S>, R>,
+{
+    /// Create new [`PromiseLike<S, R>`] with the state taken out of the [`Arc`].
+    /// The value is cloned only if some branch still holds a reference to it.
+    fn unshared(self) -> Self::Promise<S, R> {
+        self.map(|value| Arc::try_unwrap(value).unwrap_or_else(|value| (*value).clone()))
+    }
+}
+impl<S: 'static + Clone, R: 'static, P: PromiseLikeBase<Arc<S>, R>> PromiseLikeUnshared<S, R> for P {}
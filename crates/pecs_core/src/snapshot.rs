@@ -0,0 +1,104 @@
+//! Transactional chain segments: save the state with [`snapshot()`][PromiseLikeSnapshot::snapshot]
+//! and restore it with [`rollback_on_error()`][PromiseLikeRollback::rollback_on_error]
+//! if the segment fails.
+use super::*;
+
+/// State wrapper holding the current state value along with the copy
+/// saved by [`snapshot()`][PromiseLikeSnapshot::snapshot]. Derefs to the
+/// current value, so handlers between `snapshot()` and `rollback_on_error()`
+/// work with the state as usual.
+pub struct Snapshot<S> {
+    value: S,
+    saved: S,
+}
+
+impl<S: Clone> Snapshot<S> {
+    pub fn new(value: S) -> Snapshot<S> {
+        let saved = value.clone();
+        Snapshot { value, saved }
+    }
+    /// Replace the saved copy with the current value
+    pub fn commit(&mut self) {
+        self.saved = self.value.clone();
+    }
+}
+
+impl<S> Snapshot<S> {
+    /// The state value saved by the last snapshot or [`commit`][Snapshot::commit]
+    pub fn saved(&self) -> &S {
+        &self.saved
+    }
+    /// Drop the saved copy and return the current value
+    pub fn into_inner(self) -> S {
+        self.value
+    }
+    /// Drop the current value and return the saved copy
+    pub fn rollback(self) -> S {
+        self.saved
+    }
+}
+
+impl<S> std::ops::Deref for Snapshot<S> {
+    type Target = S;
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+impl<S> std::ops::DerefMut for Snapshot<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+pub trait PromiseLikeSnapshot<S: 'static + Clone, R: 'static>
+where
+    Self: Sized + PromiseLikeBase<S, R>,
+{
+    /// Create new [`PromiseLike<Snapshot<S>, R>`] with the copy of the current state
+    /// saved. The next promises in the chain work with the current value, and the saved
+    /// copy could be restored with [`rollback_on_error()`][PromiseLikeRollback::rollback_on_error]
+    /// ```ignore
+    /// commands
+    ///     .promise(|| Wallet { coins: 100 })
+    ///     .snapshot()
+    ///     .then(asyn!(state => {
+    ///         // tentative purchase
+    ///         state.coins -= 30;
+    ///         state.asyn().http().post("https://example.com/buy").send()
+    ///     }))
+    ///     .then(asyn!(state, result => {
+    ///         let confirmed = result.map(|r| r.ok).unwrap_or(false);
+    ///         state.resolve(if confirmed { Ok(()) } else { Err("not confirmed") })
+    ///     }))
+    ///     // coins restored to 100 if the server didn't confirm the purchase
+    ///     .rollback_on_error()
+    ///     .then(asyn!(state => {
+    ///         info!("Coins left: {}", state.coins);
+    ///     }));
+    /// ```
+    fn snapshot(self) -> Self::Promise<Snapshot<S>, R> {
+        self.map(Snapshot::new)
+    }
+}
+impl<S: 'static + Clone, R: 'static, P: PromiseLikeBase<S, R>> PromiseLikeSnapshot<S, R> for P {}
+
+pub trait PromiseLikeRollback<S: 'static, T: 'static, E: 'static>
+where
+    Self: Sized + PromiseLikeBase<Snapshot<S>, Result<T, E>>,
+{
+    /// Create new [`PromiseLike<S, Result<T, E>>`] from the promise started with
+    /// [`snapshot()`][PromiseLikeSnapshot::snapshot]. If the previous promise resolves
+    /// with `Err`, the state is restored to the saved copy, otherwise the current
+    /// state value passes to the next promise. The result passes as is.
+    fn rollback_on_error(self) -> Self::Promise<S, Result<T, E>> {
+        self.then(asyn!(s, r => {
+            let snapshot = s.value;
+            let state = if r.is_err() { snapshot.rollback() } else { snapshot.into_inner() };
+            PromiseResult::Resolve(state, r)
+        }))
+    }
+}
+impl<S: 'static, T: 'static, E: 'static, P: PromiseLikeBase<Snapshot<S>, Result<T, E>>> PromiseLikeRollback<S, T, E>
+    for P
+{
+}
@@ -0,0 +1,121 @@
+//! Chains bound to the [`States`] values: [`Promise::bound_to_state`] discards the chain
+//! when the state is exited, so the menu flows don't resolve during the gameplay:
+//! ```ignore
+//! fn attract_mode(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::timeout(30.)
+//!             .then(asyn!(_, mut next: ResMut<NextState<GameState>> => {
+//!                 next.set(GameState::Attract);
+//!             }))
+//!             .bound_to_state(GameState::Menu),
+//!     );
+//! }
+//!
+//! app.add_systems(OnEnter(GameState::Menu), attract_mode);
+//! ```
+//! The [`OnExit`] system discarding the chains is added to the app schedules when
+//! the first chain is bound to the state value.
+use bevy::utils::HashSet;
+use pecs_compat::FreelyMutableState;
+
+use super::*;
+
+struct Bound {
+    promise: PromiseId,
+    pending: fn(&World, PromiseId) -> bool,
+    discard: fn(&mut World, PromiseId),
+}
+
+/// Pending chains bound to the values of the `T` state
+pub struct StateBindings<T: FreelyMutableState> {
+    bound: HashMap<T, Vec<Bound>>,
+    /// State values with the discarding system added to their [`OnExit`] schedule
+    observed: HashSet<T>,
+}
+impl<T: FreelyMutableState> Resource for StateBindings<T> {}
+
+impl<T: FreelyMutableState> Default for StateBindings<T> {
+    fn default() -> Self {
+        StateBindings {
+            bound: HashMap::default(),
+            observed: HashSet::default(),
+        }
+    }
+}
+
+impl<T: FreelyMutableState> StateBindings<T> {
+    /// Number of the chains bound to the `state`, including the completed
+    /// ones not cleaned up yet
+    pub fn bound(&self, state: &T) -> usize {
+        self.bound.get(state).map(|bound| bound.len()).unwrap_or_default()
+    }
+}
+
+impl<S: 'static, R: 'static> Promise<S, R> {
+    /// Bind the promise to the `state`: the promise is discarded when the `state`
+    /// is exited, see [`states`][crate::states]. Binding the promise created outside
+    /// of the `state` discards it on the next exit of the `state`.
+    pub fn bound_to_state<T: FreelyMutableState>(mut self, state: T) -> Self {
+        let key = state.clone();
+        let register = self.register.take();
+        self.register = Some(Box::new(move |world, id| {
+            observe(world, &state);
+            let mut bound = world
+                .resource_mut::<StateBindings<T>>()
+                .bound
+                .remove(&state)
+                .unwrap_or_default();
+            bound.retain(|bound| (bound.pending)(world, bound.promise));
+            bound.push(Bound {
+                promise: id,
+                pending: promise_is_pending::<S, R>,
+                discard: promise_discard::<S, R>,
+            });
+            world.resource_mut::<StateBindings<T>>().bound.insert(state, bound);
+            if let Some(register) = register {
+                register(world, id)
+            }
+        }));
+        let discard = self.discard.take();
+        self.discard = Some(Box::new(move |world, id| {
+            if let Some(mut bindings) = world.get_resource_mut::<StateBindings<T>>() {
+                if let Some(bound) = bindings.bound.get_mut(&key) {
+                    bound.retain(|bound| bound.promise != id);
+                }
+            }
+            if let Some(discard) = discard {
+                discard(world, id)
+            }
+        }));
+        self
+    }
+}
+
+/// Adds the system discarding the chains bound to the `state` to its [`OnExit`]
+/// schedule, if it is not added yet
+fn observe<T: FreelyMutableState>(world: &mut World, state: &T) {
+    let mut bindings = world.get_resource_or_insert_with(StateBindings::<T>::default);
+    if !bindings.observed.insert(state.clone()) {
+        return;
+    }
+    let label = OnExit(state.clone());
+    let exited = state.clone();
+    let mut schedules = world.resource_mut::<Schedules>();
+    if !schedules.contains(label.clone()) {
+        schedules.insert(Schedule::new(label.clone()));
+    }
+    if let Some(schedule) = schedules.get_mut(label) {
+        schedule.add_systems(move |world: &mut World| discard_bound(world, &exited));
+    }
+}
+
+fn discard_bound<T: FreelyMutableState>(world: &mut World, state: &T) {
+    let Some(bound) = world.resource_mut::<StateBindings<T>>().bound.remove(state) else {
+        return;
+    };
+    for bound in bound {
+        if (bound.pending)(world, bound.promise) {
+            (bound.discard)(world, bound.promise);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Step-by-step flows like tutorials: every step is a promise with a title, the next
+//! step starts when the current one resolves. Skippable steps could be skipped by the
+//! player with [`commands.steps(key).skip_current()`][StepsCommands::skip_current] or
+//! [`skip_all()`][StepsCommands::skip_all], skipped promises are discarded.
+//! ```ignore
+//! fn start_tutorial(mut commands: Commands) {
+//!     commands.add(
+//!         Steps::new("tutorial")
+//!             .step("Move with WASD", wait_for_movement())
+//!             .step("Jump with Space", wait_for_jump())
+//!             .required("Pick the sword", wait_for_pickup())
+//!             .promise()
+//!             .then(asyn!(_, outcomes => {
+//!                 info!("Tutorial is over: {outcomes:?}");
+//!             })),
+//!     );
+//! }
+//!
+//! fn skip_tutorial(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
+//!     if keys.just_pressed(KeyCode::Escape) {
+//!         commands.steps("tutorial").skip_all();
+//!     }
+//! }
+//! ```
+use std::collections::VecDeque;
+
+use super::*;
+
+/// How the step is finished
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// The step promise is resolved
+    Completed(&'static str),
+    /// The step is skipped, its promise is discarded or never started
+    Skipped(&'static str),
+}
+
+/// The running step, see [`PromiseSteps::current`]
+#[derive(Clone, Copy, Debug)]
+pub struct StepInfo {
+    pub title: &'static str,
+    pub skippable: bool,
+    /// Index of the step, starting from 0
+    pub index: usize,
+    /// Number of the steps in the flow
+    pub total: usize,
+}
+
+/// Registers the promise of the step in the flow with the `key`
+type StepRegister = Box<dyn FnOnce(&mut World, &'static str) + Send + Sync>;
+
+struct Step {
+    title: &'static str,
+    skippable: bool,
+    promise: PromiseId,
+    discard: fn(&mut World, PromiseId),
+    register: StepRegister,
+}
+
+struct CurrentStep {
+    title: &'static str,
+    skippable: bool,
+    promise: PromiseId,
+    discard: fn(&mut World, PromiseId),
+}
+
+/// Sequence of promises with titles, see [module docs][self]
+pub struct Steps {
+    key: &'static str,
+    steps: Vec<Step>,
+}
+
+impl Steps {
+    /// Creates empty flow, the `key` is used to skip its steps
+    pub fn new(key: &'static str) -> Self {
+        Steps { key, steps: vec![] }
+    }
+
+    /// Adds the skippable step
+    pub fn step<S: 'static, R: 'static>(self, title: &'static str, promise: Promise<S, R>) -> Self {
+        self.push(title, true, promise)
+    }
+
+    /// Adds the step which can't be skipped
+    pub fn required<S: 'static, R: 'static>(self, title: &'static str, promise: Promise<S, R>) -> Self {
+        self.push(title, false, promise)
+    }
+
+    fn push<S: 'static, R: 'static>(
+        mut self,
+        title: &'static str,
+        skippable: bool,
+        mut promise: Promise<S, R>,
+    ) -> Self {
+        let id = promise.id;
+        self.steps.push(Step {
+            title,
+            skippable,
+            promise: id,
+            discard: promise_discard::<S, R>,
+            register: Box::new(move |world, key| {
+                promise.resolve = Some(Box::new(move |world, _, _| complete(world, key, id)));
+                promise_register::<S, R>(world, promise);
+            }),
+        });
+        self
+    }
+
+    /// Creates promise that runs the steps one by one and resolves with their outcomes
+    /// when the last one is finished. Discarding the promise discards the current step.
+    pub fn promise(self) -> Promise<(), Vec<StepOutcome>> {
+        let Steps { key, steps } = self;
+        Promise::register(
+            move |world, id| {
+                let previous = world.get_resource_or_insert_with(PromiseSteps::default).0.remove(key);
+                if let Some(previous) = previous {
+                    warn!("Steps \"{key}\" are already running, discarding {}", previous.promise);
+                    previous.discard(world);
+                }
+                world.resource_mut::<PromiseSteps>().0.insert(
+                    key,
+                    Running {
+                        promise: id,
+                        total: steps.len(),
+                        steps: steps.into(),
+                        current: None,
+                        outcomes: vec![],
+                        skip_all: false,
+                    },
+                );
+                next(world, key);
+            },
+            move |world, id| {
+                let Some(mut steps) = world.get_resource_mut::<PromiseSteps>() else {
+                    return;
+                };
+                if steps.0.get(key).is_some_and(|running| running.promise == id) {
+                    if let Some(current) = steps.0.remove(key).and_then(|running| running.current) {
+                        (current.discard)(world, current.promise);
+                    }
+                }
+            },
+        )
+    }
+}
+
+struct Running {
+    promise: PromiseId,
+    total: usize,
+    steps: VecDeque<Step>,
+    current: Option<CurrentStep>,
+    outcomes: Vec<StepOutcome>,
+    skip_all: bool,
+}
+
+impl Running {
+    fn discard(self, world: &mut World) {
+        if let Some(current) = self.current {
+            (current.discard)(world, current.promise);
+        }
+        promise_discard::<(), Vec<StepOutcome>>(world, self.promise);
+    }
+}
+
+/// Running steps by their keys
+#[derive(Default)]
+pub struct PromiseSteps(HashMap<&'static str, Running>);
+impl Resource for PromiseSteps {}
+
+impl PromiseSteps {
+    /// The current step of the flow started with the `key`
+    pub fn current(&self, key: &str) -> Option<StepInfo> {
+        let running = self.0.get(key)?;
+        let current = running.current.as_ref()?;
+        Some(StepInfo {
+            title: current.title,
+            skippable: current.skippable,
+            index: running.outcomes.len(),
+            total: running.total,
+        })
+    }
+
+    /// Skip the current step of the flow if it is skippable. With `all`
+    /// the rest of the skippable steps are skipped too.
+    pub fn skip(world: &mut World, key: &'static str, all: bool) {
+        let Some(mut steps) = world.get_resource_mut::<PromiseSteps>() else {
+            return;
+        };
+        let Some(running) = steps.0.get_mut(key) else {
+            return;
+        };
+        running.skip_all |= all;
+        if !running.current.as_ref().is_some_and(|current| current.skippable) {
+            return;
+        }
+        let current = running.current.take().unwrap();
+        running.outcomes.push(StepOutcome::Skipped(current.title));
+        (current.discard)(world, current.promise);
+        next(world, key);
+    }
+}
+
+/// Starts the next step, skipping it if requested, or resolves the flow if there are no steps left
+fn next(world: &mut World, key: &'static str) {
+    let mut steps = world.resource_mut::<PromiseSteps>();
+    let Some(running) = steps.0.get_mut(key) else {
+        return;
+    };
+    let Some(step) = running.steps.pop_front() else {
+        let running = steps.0.remove(key).unwrap();
+        promise_resolve::<(), Vec<StepOutcome>>(world, running.promise, (), running.outcomes);
+        return;
+    };
+    if running.skip_all && step.skippable {
+        running.outcomes.push(StepOutcome::Skipped(step.title));
+        return next(world, key);
+    }
+    // the step could resolve during the registration,
+    // so it should be current before it is registered
+    running.current = Some(CurrentStep {
+        title: step.title,
+        skippable: step.skippable,
+        promise: step.promise,
+        discard: step.discard,
+    });
+    (step.register)(world, key);
+}
+
+fn complete(world: &mut World, key: &'static str, id: PromiseId) {
+    let Some(mut steps) = world.get_resource_mut::<PromiseSteps>() else {
+        return;
+    };
+    let Some(running) = steps.0.get_mut(key) else {
+        return;
+    };
+    if !running.current.as_ref().is_some_and(|current| current.promise == id) {
+        return;
+    }
+    let current = running.current.take().unwrap();
+    running.outcomes.push(StepOutcome::Completed(current.title));
+    next(world, key);
+}
+
+/// Skips the current step, see [`StepsCommands::skip_current`]
+pub struct SkipStepCommand {
+    pub key: &'static str,
+    pub all: bool,
+}
+impl Command for SkipStepCommand {
+    fn apply(self, world: &mut World) {
+        PromiseSteps::skip(world, self.key, self.all);
+    }
+}
+
+pub struct StepsCommands<'w, 's, 'a> {
+    key: &'static str,
+    commands: &'a mut Commands<'w, 's>,
+}
+impl<'w, 's, 'a> StepsCommands<'w, 's, 'a> {
+    /// Skip the current step if it is skippable: its promise is discarded
+    /// and the next step starts
+    pub fn skip_current(&mut self) -> &mut Self {
+        self.commands.add(SkipStepCommand {
+            key: self.key,
+            all: false,
+        });
+        self
+    }
+
+    /// Skip the current step and every skippable step left,
+    /// required steps still have to be completed
+    pub fn skip_all(&mut self) -> &mut Self {
+        self.commands.add(SkipStepCommand {
+            key: self.key,
+            all: true,
+        });
+        self
+    }
+}
+
+pub trait StepsCommandsExtension<'w, 's> {
+    fn steps<'a>(&'a mut self, key: &'static str) -> StepsCommands<'w, 's, 'a>;
+}
+impl<'w, 's> StepsCommandsExtension<'w, 's> for Commands<'w, 's> {
+    fn steps<'a>(&'a mut self, key: &'static str) -> StepsCommands<'w, 's, 'a> {
+        StepsCommands { key, commands: self }
+    }
+}
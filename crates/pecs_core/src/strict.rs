@@ -0,0 +1,105 @@
+//! Strict mode catching leaked flows in debug builds. With [`PromiseStrictLabelsPlugin`]
+//! added, every promise marked with [`Promise::labeled`] is tracked, and the app
+//! panics on exit if some of them are still pending, listing their labels and ages.
+//! Only the labeled promises are checked: the unlabeled chains are never tracked,
+//! label the roots of the flows which must not leak. The check could also run on
+//! state exit:
+//! ```ignore
+//! App::new()
+//!     .add_plugins((DefaultPlugins, PecsPlugin::strict_labels()))
+//!     .add_systems(OnExit(GameState::Battle), strict::check_labeled)
+//!     .run();
+//! ```
+//! In release builds the plugin does nothing.
+use bevy::app::AppExit;
+
+use super::*;
+
+/// Checks the [labeled][Promise::labeled] promises on exit, see [module docs][self]
+pub struct PromiseStrictLabelsPlugin;
+impl Plugin for PromiseStrictLabelsPlugin {
+    fn build(&self, app: &mut App) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        app.init_resource::<StrictLabels>();
+        app.add_systems(Last, (prune_labels, check_on_exit).chain());
+    }
+}
+
+struct TrackedLabel {
+    promise: PromiseId,
+    label: &'static str,
+    signature: fn() -> String,
+    registered: Instant,
+    pending: fn(&World, PromiseId) -> bool,
+}
+
+/// Labeled promises tracked by the strict mode
+#[derive(Default)]
+pub struct StrictLabels {
+    tracked: Vec<TrackedLabel>,
+}
+impl Resource for StrictLabels {}
+
+impl StrictLabels {
+    /// Human-readable list of the labeled promises which are still pending
+    pub fn pending(&self, world: &World) -> Vec<String> {
+        self.tracked
+            .iter()
+            .filter(|t| (t.pending)(world, t.promise))
+            .map(|t| {
+                format!(
+                    "{} \"{}\" {} pending for {:.1}s",
+                    t.promise,
+                    t.label,
+                    (t.signature)(),
+                    t.registered.elapsed().as_secs_f32()
+                )
+            })
+            .collect()
+    }
+}
+
+/// Tracks the labeled promise `id` if the strict mode is enabled
+pub(crate) fn track<S: 'static, R: 'static>(world: &mut World, id: PromiseId, label: &'static str) {
+    let Some(mut labels) = world.get_resource_mut::<StrictLabels>() else {
+        return;
+    };
+    labels.tracked.push(TrackedLabel {
+        promise: id,
+        label,
+        signature: || format!("<{}, {}>", type_name::<S>(), type_name::<R>()),
+        registered: Instant::now(),
+        pending: promise_is_pending::<S, R>,
+    });
+}
+
+/// Panics if some of the labeled promises are still pending.
+/// Could be used as a system, e.g. on state exit.
+pub fn check_labeled(world: &World) {
+    let Some(labels) = world.get_resource::<StrictLabels>() else {
+        return;
+    };
+    let pending = labels.pending(world);
+    if pending.is_empty() {
+        return;
+    }
+    let pending = pending.iter().map(|p| format!("  {p}")).collect::<Vec<_>>().join("\n");
+    panic!("Strict mode: labeled promises are left unresolved:\n{pending}");
+}
+
+fn prune_labels(world: &mut World) {
+    world.resource_scope(|world, mut labels: Mut<StrictLabels>| {
+        labels.tracked.retain(|t| (t.pending)(world, t.promise));
+    });
+}
+
+fn check_on_exit(world: &mut World) {
+    let exiting = world
+        .get_resource::<Events<AppExit>>()
+        .is_some_and(|events| !events.is_empty());
+    if exiting {
+        check_labeled(world);
+    }
+}
@@ -0,0 +1,133 @@
+//! Promises tied to the virtual clock controls: wait for the game to be paused
+//! or resumed with [`Time<Virtual>`], or for the virtual time to reach a milestone.
+//! ```ignore
+//! fn pause_menu(mut commands: Commands) {
+//!     commands.add(asyn::time::paused().then(asyn!(_, _, mut menu: ResMut<Menu> => {
+//!         menu.show();
+//!         asyn::time::resumed()
+//!     })).then(asyn!(_, _, mut menu: ResMut<Menu> => {
+//!         menu.hide();
+//!     })));
+//! }
+//! ```
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(time)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves when the virtual clock is paused,
+    /// immediately if it is already paused.
+    pub fn paused() -> Promise<(), ()> {
+        Promise::register(
+            |world, id| {
+                if virtual_time(world).is_some_and(|time| time.is_paused()) {
+                    promise_resolve::<(), ()>(world, id, (), ());
+                    return;
+                }
+                world.resource_mut::<TimeWaiters>().paused.push(id);
+            },
+            |world, id| {
+                world
+                    .resource_mut::<TimeWaiters>()
+                    .paused
+                    .retain(|waiter| waiter != &id);
+            },
+        )
+    }
+
+    /// Creates promise that resolves when the virtual clock is unpaused,
+    /// immediately if it is not paused.
+    pub fn resumed() -> Promise<(), ()> {
+        Promise::register(
+            |world, id| {
+                if virtual_time(world).is_some_and(|time| !time.is_paused()) {
+                    promise_resolve::<(), ()>(world, id, (), ());
+                    return;
+                }
+                world.resource_mut::<TimeWaiters>().resumed.push(id);
+            },
+            |world, id| {
+                world
+                    .resource_mut::<TimeWaiters>()
+                    .resumed
+                    .retain(|waiter| waiter != &id);
+            },
+        )
+    }
+
+    /// Creates promise that resolves with the elapsed virtual time when it
+    /// reaches `seconds`, immediately if it is already reached.
+    /// ```ignore
+    /// fn challenge(mut commands: Commands) {
+    ///     commands.add(asyn::time::reached(90.).then(asyn!(_, elapsed => {
+    ///         info!("Time is over at {elapsed:.1}s");
+    ///     })));
+    /// }
+    /// ```
+    pub fn reached(seconds: f32) -> Promise<(), f32> {
+        Promise::register(
+            move |world, id| {
+                if let Some(elapsed) = virtual_time(world)
+                    .map(|time| time.elapsed_seconds())
+                    .filter(|elapsed| *elapsed >= seconds)
+                {
+                    promise_resolve::<(), f32>(world, id, (), elapsed);
+                    return;
+                }
+                world.resource_mut::<TimeWaiters>().reached.push((id, seconds));
+            },
+            |world, id| {
+                world
+                    .resource_mut::<TimeWaiters>()
+                    .reached
+                    .retain(|(waiter, _)| waiter != &id);
+            },
+        )
+    }
+}
+
+pub struct PromiseTimePlugin;
+impl Plugin for PromiseTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeWaiters>();
+        app.add_systems(Update, process_time_waiters);
+    }
+}
+
+/// Promises waiting for the virtual clock
+#[derive(Default)]
+pub struct TimeWaiters {
+    paused: Vec<PromiseId>,
+    resumed: Vec<PromiseId>,
+    reached: Vec<(PromiseId, f32)>,
+}
+impl Resource for TimeWaiters {}
+
+fn virtual_time(world: &World) -> Option<&Time<Virtual>> {
+    world.get_resource::<Time<Virtual>>()
+}
+
+fn process_time_waiters(mut commands: Commands, mut waiters: ResMut<TimeWaiters>, time: Option<Res<Time<Virtual>>>) {
+    let Some(time) = time else {
+        return;
+    };
+    let ready = if time.is_paused() {
+        mem::take(&mut waiters.paused)
+    } else {
+        mem::take(&mut waiters.resumed)
+    };
+    for id in ready {
+        commands.add(PromiseCommand::resolve(id, ()));
+    }
+    let elapsed = time.elapsed_seconds();
+    waiters.reached.retain(|(id, seconds)| {
+        if elapsed < *seconds {
+            return true;
+        }
+        commands.add(PromiseCommand::resolve(*id, elapsed));
+        false
+    });
+}
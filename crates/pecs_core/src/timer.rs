@@ -1,37 +1,371 @@
-//! Defers promise resolving for a fixed amount of time
+//! Defers promise resolving for a fixed amount of time. Timers follow [`Time`] by
+//! default, insert [`PromiseTimerClock`] to drive them by another clock, for example
+//! by the simulation tick counter of a lockstep server:
+//! ```ignore
+//! const TICK: f32 = 1. / 30.;
+//!
+//! #[derive(Resource, Default)]
+//! struct SimulationTick(u64);
+//!
+//! struct TickClock;
+//! impl TimerClock for TickClock {
+//!     fn elapsed_seconds(&self, world: &World) -> f32 {
+//!         world.resource::<SimulationTick>().0 as f32 * TICK
+//!     }
+//!     fn delta_seconds(&self, _world: &World) -> f32 {
+//!         TICK
+//!     }
+//! }
+//!
+//! App::new()
+//!     .init_resource::<SimulationTick>()
+//!     .insert_resource(PromiseTimerClock::new(TickClock))
+//!     .add_plugins((MinimalPlugins, PecsPlugin))
+//!     .run();
+//! ```
+//!
+//! Timers created in the same frame with the same duration resolve in the same frame.
+//! Spread large crowds of timers with [`Timeout::with_jitter`] or insert [`TimerJitter`]
+//! to spread every timer, except ones created with [`Timeout::align_to_frame`]:
+//! ```ignore
+//! fn think(mut commands: Commands, agents: Query<Entity, With<Agent>>) {
+//!     for agent in agents.iter() {
+//!         commands.add(
+//!             Timeout::new(1.)
+//!                 .with_jitter(0.0..0.2)
+//!                 .start()
+//!                 .with(agent)
+//!                 .then(asyn!(agent => plan(agent.value))),
+//!         );
+//!     }
+//! }
+//! ```
+//!
+//! The timers created with [`timeout_virtual()`] follow [`Time<Virtual>`] whatever the
+//! timers clock is, so they stop while the game is paused and follow its speed. The
+//! [`timeout_fixed()`] timers follow [`Time<Fixed>`] and resolve during [`FixedUpdate`],
+//! at the exact simulation step.
+//!
+//! [`next_frame()`] and [`frames()`] count the updates instead of the seconds, so the
+//! sequencing doesn't depend on the frame time:
+//! ```ignore
+//! fn blink(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::frames(3)
+//!             .then(asyn!(_, mut sprite: Query<&mut Visibility, With<Cursor>> => {
+//!                 *sprite.single_mut() = Visibility::Hidden;
+//!             }))
+//!             .then(asyn!(s => s.asyn().next_frame()))
+//!             .then(asyn!(_, mut sprite: Query<&mut Visibility, With<Cursor>> => {
+//!                 *sprite.single_mut() = Visibility::Visible;
+//!             })),
+//!     );
+//! }
+//! ```
+use std::{collections::hash_map::RandomState, hash::BuildHasher, ops::Range};
+
 use super::*;
 pub fn timeout(duration: f32) -> Promise<(), ()> {
+    Timeout::new(duration).start()
+}
+
+/// Creates promise that resolves when [`Time<Virtual>`] advances by `duration`
+/// seconds. The timer stops while the virtual clock is paused.
+pub fn timeout_virtual(duration: f32) -> Promise<(), ()> {
     Promise::<(), ()>::register(
         move |world, id| {
-            let time = world.resource::<Time>();
+            let time = world.resource::<Time<Virtual>>();
             let end = time.elapsed_seconds() + duration - time.delta_seconds();
-            world.resource_mut::<Timers>().insert(id, end);
+            world.resource_mut::<Timers<Virtual>>().insert(id, end);
+        },
+        move |world, id| {
+            world.resource_mut::<Timers<Virtual>>().remove(&id);
+        },
+    )
+}
+
+/// Creates promise that resolves during the [`FixedUpdate`] step when [`Time<Fixed>`]
+/// advances by `duration` seconds
+pub fn timeout_fixed(duration: f32) -> Promise<(), ()> {
+    Promise::<(), ()>::register(
+        move |world, id| {
+            let end = world.resource::<Time<Fixed>>().elapsed_seconds() + duration;
+            world.resource_mut::<Timers<Fixed>>().insert(id, end);
         },
         move |world, id| {
-            world.resource_mut::<Timers>().remove(&id);
+            world.resource_mut::<Timers<Fixed>>().remove(&id);
         },
     )
 }
+
+/// Creates promise that resolves on the next update
+pub fn next_frame() -> Promise<(), ()> {
+    frames(1)
+}
+
+/// Creates promise that resolves after `count` updates, resolves immediately if
+/// the `count` is zero
+pub fn frames(count: u32) -> Promise<(), ()> {
+    Promise::<(), ()>::register(
+        move |world, id| {
+            if count == 0 {
+                return promise_resolve::<(), ()>(world, id, (), ());
+            }
+            let mut timers = world.resource_mut::<FrameTimers>();
+            let end = timers.frame + count as u64;
+            timers.waiters.insert(id, end);
+        },
+        move |world, id| {
+            world.resource_mut::<FrameTimers>().waiters.remove(&id);
+        },
+    )
+}
+
+/// Timeout with the resolution time options
+pub struct Timeout {
+    duration: f32,
+    jitter: Option<Range<f32>>,
+    align_to_frame: bool,
+}
+
+impl Timeout {
+    pub fn new(duration: f32) -> Self {
+        Timeout {
+            duration,
+            jitter: None,
+            align_to_frame: false,
+        }
+    }
+
+    /// Ignore [`TimerJitter`], so timers created in the same frame
+    /// with the same duration resolve in the same frame
+    pub fn align_to_frame(mut self) -> Self {
+        self.align_to_frame = true;
+        self
+    }
+
+    /// Delay the resolution by the random amount of seconds from the `jitter`
+    /// range, overrides [`TimerJitter`]
+    pub fn with_jitter(mut self, jitter: Range<f32>) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    pub fn start(self) -> Promise<(), ()> {
+        let Timeout {
+            duration,
+            jitter,
+            align_to_frame,
+        } = self;
+        Promise::<(), ()>::register(
+            move |world, id| {
+                let (elapsed, delta) = now(world);
+                let jitter = match (jitter, align_to_frame) {
+                    (Some(jitter), _) => Some(jitter),
+                    (None, false) => world.get_resource::<TimerJitter>().map(|j| j.0.clone()),
+                    (None, true) => None,
+                };
+                let offset = jitter.map(|jitter| random_in(id, jitter)).unwrap_or_default();
+                let end = elapsed + duration + offset - delta;
+                world.resource_mut::<Timers>().insert(id, end);
+            },
+            move |world, id| {
+                world.resource_mut::<Timers>().remove(&id);
+            },
+        )
+    }
+}
+
+/// Jitter in seconds applied to every timer created without
+/// [`Timeout::align_to_frame`] or [`Timeout::with_jitter`]
+pub struct TimerJitter(pub Range<f32>);
+impl Resource for TimerJitter {}
+
+/// Random value from the `range`, hashing the promise `id` with the random seed
+fn random_in(id: PromiseId, range: Range<f32>) -> f32 {
+    let t = (RandomState::new().hash_one(id) % 10_000) as f32 / 10_000.;
+    range.start + (range.end - range.start) * t
+}
+
+// not generated with `#[asyn_ops]`: the timers are reached right from `AsynOps`
+// (`state.asyn().timeout(1.)`) instead of the `timer()` namespace it generates
 pub trait TimerOpsExtension<S> {
     fn timeout(self, duration: f32) -> Promise<S, ()>;
+    fn timeout_with(self, timeout: Timeout) -> Promise<S, ()>;
+    fn timeout_virtual(self, duration: f32) -> Promise<S, ()>;
+    fn timeout_fixed(self, duration: f32) -> Promise<S, ()>;
+    fn next_frame(self) -> Promise<S, ()>;
+    fn frames(self, count: u32) -> Promise<S, ()>;
 }
 impl<S: 'static> TimerOpsExtension<S> for AsynOps<S> {
     fn timeout(self, duration: f32) -> Promise<S, ()> {
         timeout(duration).map(|_| self.0)
     }
+    fn timeout_with(self, timeout: Timeout) -> Promise<S, ()> {
+        timeout.start().map(|_| self.0)
+    }
+    fn timeout_virtual(self, duration: f32) -> Promise<S, ()> {
+        timeout_virtual(duration).map(|_| self.0)
+    }
+    fn timeout_fixed(self, duration: f32) -> Promise<S, ()> {
+        timeout_fixed(duration).map(|_| self.0)
+    }
+    fn next_frame(self) -> Promise<S, ()> {
+        next_frame().map(|_| self.0)
+    }
+    fn frames(self, count: u32) -> Promise<S, ()> {
+        frames(count).map(|_| self.0)
+    }
+}
+
+/// Source of time for the timers, see [module docs][self]
+pub trait TimerClock: 'static + Send + Sync {
+    /// Seconds passed since the clock is started
+    fn elapsed_seconds(&self, world: &World) -> f32;
+    /// Seconds passed since the previous update
+    fn delta_seconds(&self, world: &World) -> f32;
+}
+
+/// [`TimerClock`] following [`Time`], used if [`PromiseTimerClock`] is not inserted
+pub struct TimeClock;
+impl TimerClock for TimeClock {
+    fn elapsed_seconds(&self, world: &World) -> f32 {
+        world.resource::<Time>().elapsed_seconds()
+    }
+    fn delta_seconds(&self, world: &World) -> f32 {
+        world.resource::<Time>().delta_seconds()
+    }
+}
+
+/// The clock driving the timers
+pub struct PromiseTimerClock(Box<dyn TimerClock>);
+impl Resource for PromiseTimerClock {}
+
+impl PromiseTimerClock {
+    pub fn new<C: TimerClock>(clock: C) -> Self {
+        PromiseTimerClock(Box::new(clock))
+    }
+}
+
+/// Returns elapsed and delta seconds of the timers clock
+pub(crate) fn now(world: &World) -> (f32, f32) {
+    match world.get_resource::<PromiseTimerClock>() {
+        Some(clock) => (clock.0.elapsed_seconds(world), clock.0.delta_seconds(world)),
+        None => (TimeClock.elapsed_seconds(world), TimeClock.delta_seconds(world)),
+    }
+}
+
+/// Pending timers along with the time they resolve at. `Timers` follow the timers
+/// clock, `Timers<Virtual>` and `Timers<Fixed>` follow the [`Time`] of the context.
+#[derive(Deref, DerefMut)]
+pub struct Timers<T = ()>(#[deref] HashMap<PromiseId, f32>, PhantomData<T>);
+impl<T: 'static + Send + Sync> Resource for Timers<T> {}
+
+impl<T> Default for Timers<T> {
+    fn default() -> Self {
+        Timers(HashMap::default(), PhantomData)
+    }
 }
 
-#[derive(Resource, Deref, DerefMut, Default)]
-pub struct Timers(HashMap<PromiseId, f32>);
+/// Skipping the waits in integration tests without replacing the clock:
+/// ```ignore
+/// #[test]
+/// fn intro_finishes() {
+///     let mut app = App::new();
+///     app.add_plugins((MinimalPlugins, PecsPlugin, IntroPlugin));
+///     app.update();
+///     app.world.resource_mut::<Timers>().fire_all();
+///     expect_resolved!(app, "intro", 1);
+/// }
+/// ```
+#[cfg(feature = "test-utils")]
+impl<T> Timers<T> {
+    /// Pending timers along with the clock time they resolve at, sorted by the time
+    pub fn pending(&self) -> Vec<(PromiseId, f32)> {
+        let mut pending: Vec<_> = self.0.iter().map(|(id, end)| (*id, *end)).collect();
+        pending.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        pending
+    }
 
-pub fn process_timers(time: Res<Time>, mut commands: Commands, mut timers: ResMut<Timers>) {
-    let elapsed = time.elapsed_seconds();
-    timers.retain(|promise, end| {
+    /// Make the timer `id` resolve on the next update,
+    /// returns `false` if there is no such pending timer
+    pub fn fire(&mut self, id: PromiseId) -> bool {
+        let Some(end) = self.0.get_mut(&id) else {
+            return false;
+        };
+        *end = f32::NEG_INFINITY;
+        true
+    }
+
+    /// Make every pending timer resolve on the next update
+    pub fn fire_all(&mut self) {
+        for end in self.0.values_mut() {
+            *end = f32::NEG_INFINITY;
+        }
+    }
+}
+
+pub fn process_timers(world: &mut World) {
+    let (elapsed, _) = now(world);
+    resolve_timers::<()>(world, elapsed);
+}
+
+pub fn process_virtual_timers(world: &mut World) {
+    let elapsed = world.resource::<Time<Virtual>>().elapsed_seconds();
+    resolve_timers::<Virtual>(world, elapsed);
+}
+
+pub fn process_fixed_timers(world: &mut World) {
+    let elapsed = world.resource::<Time<Fixed>>().elapsed_seconds();
+    resolve_timers::<Fixed>(world, elapsed);
+}
+
+/// Resolves `Timers<T>` ended by the `elapsed` time
+fn resolve_timers<T: 'static + Send + Sync>(world: &mut World, elapsed: f32) {
+    let mut resolved = vec![];
+    world.resource_mut::<Timers<T>>().retain(|promise, end| {
         if &elapsed >= end {
-            commands.add(PromiseCommand::resolve(*promise, ()));
+            resolved.push(*promise);
             false
         } else {
             true
         }
     });
+    for promise in resolved {
+        promise_resolve::<(), ()>(world, promise, (), ());
+    }
+}
+
+/// Updates counter and the promises waiting for the frame
+#[derive(Default)]
+pub struct FrameTimers {
+    frame: u64,
+    waiters: HashMap<PromiseId, u64>,
+}
+impl Resource for FrameTimers {}
+
+impl FrameTimers {
+    /// Updates passed since the counter is started
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+/// Counts the update and resolves the frame timers
+pub fn process_frame_timers(world: &mut World) {
+    let mut timers = world.resource_mut::<FrameTimers>();
+    timers.frame += 1;
+    let frame = timers.frame;
+    let mut resolved = vec![];
+    timers.waiters.retain(|promise, end| {
+        if frame >= *end {
+            resolved.push(*promise);
+            false
+        } else {
+            true
+        }
+    });
+    for promise in resolved {
+        promise_resolve::<(), ()>(world, promise, (), ());
+    }
 }
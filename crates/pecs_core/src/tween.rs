@@ -0,0 +1,198 @@
+//! Tween promises: animate the entity [`Transform`] and resolve when the animation
+//! completes, so the cutscenes could be scripted as the promise chains:
+//! ```ignore
+//! fn intro(mut commands: Commands, hero: Query<Entity, With<Hero>>) {
+//!     let hero = hero.single();
+//!     commands.add(
+//!         asyn::tween(hero)
+//!             .translation(Vec3::new(200., 0., 0.), 2., EaseFunction::QuadOut)
+//!             .then(asyn!(s => s.asyn().timeout(0.5)))
+//!             .then(asyn!(_ => asyn::scene::fade_out(1., Color::BLACK)))
+//!             .then(asyn!(_, _ => info!("The intro is over"))),
+//!     );
+//! }
+//! ```
+//! Tweens follow [`Time`]. The tween of the despawned entity resolves on the next update.
+use super::*;
+
+/// Creates the tween builder animating the `entity`
+pub fn tween(entity: Entity) -> AsynTween {
+    AsynTween(entity)
+}
+
+/// Easing applied to the tween progress
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EaseFunction {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+}
+
+impl EaseFunction {
+    /// Eased value of the progress `t` in `0..=1` range
+    pub fn sample(self, t: f32) -> f32 {
+        use std::f32::consts::PI;
+        let t = t.clamp(0., 1.);
+        match self {
+            EaseFunction::Linear => t,
+            EaseFunction::QuadIn => t * t,
+            EaseFunction::QuadOut => 1. - (1. - t) * (1. - t),
+            EaseFunction::QuadInOut if t < 0.5 => 2. * t * t,
+            EaseFunction::QuadInOut => 1. - (-2. * t + 2.).powi(2) / 2.,
+            EaseFunction::CubicIn => t * t * t,
+            EaseFunction::CubicOut => 1. - (1. - t).powi(3),
+            EaseFunction::CubicInOut if t < 0.5 => 4. * t * t * t,
+            EaseFunction::CubicInOut => 1. - (-2. * t + 2.).powi(3) / 2.,
+            EaseFunction::SineIn => 1. - (t * PI / 2.).cos(),
+            EaseFunction::SineOut => (t * PI / 2.).sin(),
+            EaseFunction::SineInOut => -((t * PI).cos() - 1.) / 2.,
+        }
+    }
+}
+
+pub struct AsynTween(Entity);
+impl AsynTween {
+    /// Creates promise that moves the entity to the `to` translation
+    /// in `duration` seconds and resolves when it is there
+    pub fn translation(&self, to: Vec3, duration: f32, ease: EaseFunction) -> Promise<(), ()> {
+        start(self.0, duration, ease, move |from| {
+            TweenTarget::Translation(from.translation, to)
+        })
+    }
+
+    /// Creates promise that rotates the entity to the `to` rotation
+    /// in `duration` seconds and resolves when it is rotated
+    pub fn rotation(&self, to: Quat, duration: f32, ease: EaseFunction) -> Promise<(), ()> {
+        start(self.0, duration, ease, move |from| {
+            TweenTarget::Rotation(from.rotation, to)
+        })
+    }
+
+    /// Creates promise that scales the entity to the `to` scale
+    /// in `duration` seconds and resolves when it is scaled
+    pub fn scale(&self, to: Vec3, duration: f32, ease: EaseFunction) -> Promise<(), ()> {
+        start(self.0, duration, ease, move |from| TweenTarget::Scale(from.scale, to))
+    }
+}
+
+pub struct StatefulAsynTween<S>(S, Entity);
+impl<S: 'static> StatefulAsynTween<S> {
+    pub fn translation(self, to: Vec3, duration: f32, ease: EaseFunction) -> Promise<S, ()> {
+        AsynTween(self.1).translation(to, duration, ease).with(self.0)
+    }
+    pub fn rotation(self, to: Quat, duration: f32, ease: EaseFunction) -> Promise<S, ()> {
+        AsynTween(self.1).rotation(to, duration, ease).with(self.0)
+    }
+    pub fn scale(self, to: Vec3, duration: f32, ease: EaseFunction) -> Promise<S, ()> {
+        AsynTween(self.1).scale(to, duration, ease).with(self.0)
+    }
+}
+
+pub trait TweenOpsExtension<S> {
+    fn tween(self, entity: Entity) -> StatefulAsynTween<S>;
+}
+impl<S: 'static> TweenOpsExtension<S> for AsynOps<S> {
+    fn tween(self, entity: Entity) -> StatefulAsynTween<S> {
+        StatefulAsynTween(self.0, entity)
+    }
+}
+
+pub struct PromiseTweenPlugin;
+impl Plugin for PromiseTweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tweens>();
+        app.add_systems(Update, process_tweens);
+    }
+}
+
+/// The animated property with its start and end values
+pub(crate) enum TweenTarget {
+    Translation(Vec3, Vec3),
+    Rotation(Quat, Quat),
+    Scale(Vec3, Vec3),
+}
+
+impl TweenTarget {
+    fn apply(&self, transform: &mut Transform, t: f32) {
+        match self {
+            TweenTarget::Translation(from, to) => transform.translation = from.lerp(*to, t),
+            TweenTarget::Rotation(from, to) => transform.rotation = from.slerp(*to, t),
+            TweenTarget::Scale(from, to) => transform.scale = from.lerp(*to, t),
+        }
+    }
+}
+
+pub(crate) struct Tween {
+    entity: Entity,
+    target: TweenTarget,
+    ease: EaseFunction,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Tweens in progress
+#[derive(Deref, DerefMut, Default)]
+pub(crate) struct Tweens(HashMap<PromiseId, Tween>);
+impl Resource for Tweens {}
+
+/// Creates promise running the tween of the `target` built from the entity transform
+fn start(
+    entity: Entity,
+    duration: f32,
+    ease: EaseFunction,
+    target: impl FnOnce(&Transform) -> TweenTarget + 'static,
+) -> Promise<(), ()> {
+    Promise::register(
+        move |world, id| {
+            let Some(from) = world.get::<Transform>(entity) else {
+                return promise_resolve::<(), ()>(world, id, (), ());
+            };
+            let tween = Tween {
+                entity,
+                target: target(from),
+                ease,
+                duration,
+                elapsed: 0.,
+            };
+            world.resource_mut::<Tweens>().insert(id, tween);
+        },
+        |world, id| {
+            world.resource_mut::<Tweens>().remove(&id);
+        },
+    )
+}
+
+fn process_tweens(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut tweens: ResMut<Tweens>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let delta = time.delta_seconds();
+    tweens.retain(|id, tween| {
+        let Ok(mut transform) = transforms.get_mut(tween.entity) else {
+            commands.add(PromiseCommand::resolve(*id, ()));
+            return false;
+        };
+        tween.elapsed += delta;
+        let t = if tween.duration > 0. {
+            (tween.elapsed / tween.duration).min(1.)
+        } else {
+            1.
+        };
+        tween.target.apply(&mut transform, tween.ease.sample(t));
+        if t < 1. {
+            return true;
+        }
+        commands.add(PromiseCommand::resolve(*id, ()));
+        false
+    });
+}
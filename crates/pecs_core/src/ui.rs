@@ -1,48 +1,163 @@
-use bevy::prelude::*;
+use std::marker::PhantomData;
 
-use crate::{AsynOps, Promise, PromiseCommandsExtension, PromiseId, PromiseLikeBase};
+use crate::bevy::{
+    ecs::component::ComponentId,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
 
+use pecs_macro::{asyn, asyn_ops};
+
+use crate::{Promise, PromiseCommandsExtension, PromiseId, PromiseLikeBase, PromiseResult, PromisesExtension, Repeat};
+
+#[asyn_ops(ui)]
 pub mod asyn {
-    use super::AsynButton;
-    use bevy::prelude::Entity;
+    use super::{AsynButton, AsynMarkedButton, AsynTextInput};
+    use crate::bevy::prelude::{Component, Entity};
+    use crate::Promise;
 
     pub fn button(entity: Entity) -> AsynButton {
         AsynButton(entity)
     }
+
+    /// Await buttons by the marker component `M` instead of the entity. Useful when
+    /// the UI is spawned from scenes or templates and the button entity is not known
+    /// when the chain is constructed:
+    /// ```ignore
+    /// #[derive(Component)]
+    /// struct ConfirmButton;
+    ///
+    /// commands.add(
+    ///     asyn::ui::button_marked::<ConfirmButton>()
+    ///         .pressed()
+    ///         .then(asyn!(_, button => {
+    ///             info!("{button:?} pressed");
+    ///         })),
+    /// );
+    /// ```
+    pub fn button_marked<M: Component>() -> AsynMarkedButton<M> {
+        AsynMarkedButton(super::PhantomData)
+    }
+
+    /// Resolves with the payload of the first pressed button, the rest of the
+    /// buttons stop being awaited:
+    /// ```ignore
+    /// commands.add(
+    ///     asyn::ui::choice([(yes, true), (no, false)]).then(asyn!(_, confirmed => {
+    ///         info!("Confirmed: {confirmed}");
+    ///     })),
+    /// );
+    /// ```
+    pub fn choice<T: 'static, I: IntoIterator<Item = (Entity, T)>>(buttons: I) -> Promise<(), T> {
+        super::choice(buttons)
+    }
+
+    /// Await the [`TextInput`][super::TextInput] of the `entity`:
+    /// ```ignore
+    /// let field = commands.spawn((TextBundle::default(), TextInput::focused())).id();
+    /// commands.add(
+    ///     asyn::ui::text_input(field)
+    ///         .submitted()
+    ///         .then(asyn!(_, typed => {
+    ///             if typed == "DELETE" {
+    ///                 info!("Deletion confirmed");
+    ///             }
+    ///         })),
+    /// );
+    /// ```
+    pub fn text_input(entity: Entity) -> AsynTextInput {
+        AsynTextInput(entity)
+    }
 }
 
 pub struct PromiseUiPlugin;
 impl Plugin for PromiseUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, resolve_buttons);
+        app.add_systems(Update, (resolve_buttons, resolve_marked_buttons, edit_text_inputs));
     }
 }
 
-pub struct StatefulAsynUi<S>(S);
+// `#[asyn_ops]` only generates the promise returning methods,
+// the builder returning ones are written by hand
 impl<S: 'static> StatefulAsynUi<S> {
     pub fn button(self, entity: Entity) -> StatefulAsynButton<S> {
         StatefulAsynButton(self.0, entity)
     }
+    pub fn button_marked<M: Component>(self) -> StatefulAsynMarkedButton<S, M> {
+        StatefulAsynMarkedButton(self.0, PhantomData)
+    }
+    pub fn text_input(self, entity: Entity) -> StatefulAsynTextInput<S> {
+        StatefulAsynTextInput(self.0, entity)
+    }
+}
+
+/// The button interaction awaited by [`AsynButtonIteraction`]
+enum ButtonTrigger {
+    /// The interaction changes to the value
+    Became(Interaction),
+    /// The interaction changes from [`Interaction::Pressed`] to another one
+    Released { pressed: bool },
+    /// The button is pressed twice within `window` seconds
+    DoubleClicked { window: f32, last_press: Option<f32> },
+    /// The interaction changes to any value
+    Changed,
 }
 
-#[derive(Component)]
 pub struct AsynButtonIteraction {
     promise: PromiseId,
-    interaction: Interaction,
+    trigger: ButtonTrigger,
     entity: Entity,
 }
+pecs_compat::impl_component!(AsynButtonIteraction);
 
 pub struct AsynButton(Entity);
 
 impl AsynButton {
     pub fn pressed(&self) -> Promise<(), ()> {
+        self.wait(ButtonTrigger::Became(Interaction::Pressed))
+            .map_result(|_| ())
+    }
+
+    /// Resolves with [`Interaction::Hovered`] when the cursor is over the button
+    pub fn hovered(&self) -> Promise<(), Interaction> {
+        self.wait(ButtonTrigger::Became(Interaction::Hovered))
+    }
+
+    /// Resolves with the new interaction when the pressed button is released: with
+    /// [`Interaction::Hovered`] if the cursor is still over the button, with
+    /// [`Interaction::None`] otherwise
+    pub fn released(&self) -> Promise<(), Interaction> {
+        self.wait(ButtonTrigger::Released { pressed: false })
+    }
+
+    /// Resolves with [`Interaction::Pressed`] when the button is pressed twice
+    /// within `window` seconds of the real time
+    pub fn double_clicked(&self, window: f32) -> Promise<(), Interaction> {
+        self.wait(ButtonTrigger::DoubleClicked {
+            window,
+            last_press: None,
+        })
+    }
+
+    /// Resolves with the new interaction when the interaction changes
+    pub fn interaction_changed(&self) -> Promise<(), Interaction> {
+        self.wait(ButtonTrigger::Changed)
+    }
+
+    fn wait(&self, mut trigger: ButtonTrigger) -> Promise<(), Interaction> {
         let entity = self.0;
         Promise::register(
             move |world, id| {
+                if let ButtonTrigger::Released { pressed } = &mut trigger {
+                    *pressed = world.get::<Interaction>(entity) == Some(&Interaction::Pressed);
+                }
                 world.spawn(AsynButtonIteraction {
                     entity,
                     promise: id,
-                    interaction: Interaction::Pressed,
+                    trigger,
                 });
             },
             move |world, id| {
@@ -58,6 +173,36 @@ impl AsynButton {
             },
         )
     }
+
+    /// Resolves when the button is pressed `times` times
+    pub fn pressed_times(&self, times: usize) -> Promise<(), ()> {
+        Promise::repeat(
+            (self.0, times),
+            asyn!(state => {
+                let (entity, left) = state.value;
+                if left == 0 {
+                    state.resolve(Repeat::Break(()))
+                } else {
+                    PromiseResult::Await(
+                        AsynButton(entity)
+                            .pressed()
+                            .with((entity, left - 1))
+                            .with_result(Repeat::Continue),
+                    )
+                }
+            }),
+        )
+        .with(())
+    }
+}
+
+fn choice<T: 'static, I: IntoIterator<Item = (Entity, T)>>(buttons: I) -> Promise<(), T> {
+    buttons
+        .into_iter()
+        .map(|(entity, payload)| AsynButton(entity).pressed().with_result(payload))
+        .promise()
+        .any()
+        .map_result(|(_, payload)| payload)
 }
 
 pub struct StatefulAsynButton<S>(S, Entity);
@@ -65,30 +210,229 @@ impl<S: 'static> StatefulAsynButton<S> {
     pub fn pressed(self) -> Promise<S, ()> {
         AsynButton(self.1).pressed().with(self.0)
     }
+    pub fn pressed_times(self, times: usize) -> Promise<S, ()> {
+        AsynButton(self.1).pressed_times(times).with(self.0)
+    }
+    pub fn hovered(self) -> Promise<S, Interaction> {
+        AsynButton(self.1).hovered().with(self.0)
+    }
+    pub fn released(self) -> Promise<S, Interaction> {
+        AsynButton(self.1).released().with(self.0)
+    }
+    pub fn double_clicked(self, window: f32) -> Promise<S, Interaction> {
+        AsynButton(self.1).double_clicked(window).with(self.0)
+    }
+    pub fn interaction_changed(self) -> Promise<S, Interaction> {
+        AsynButton(self.1).interaction_changed().with(self.0)
+    }
 }
 
-pub trait UiOpsExtension<S> {
-    fn ui(self) -> StatefulAsynUi<S>;
+pub struct AsynMarkedButtonIteraction {
+    promise: PromiseId,
+    interaction: Interaction,
+    marker: ComponentId,
 }
-impl<S: 'static> UiOpsExtension<S> for AsynOps<S> {
-    fn ui(self) -> StatefulAsynUi<S> {
-        StatefulAsynUi(self.0)
+pecs_compat::impl_component!(AsynMarkedButtonIteraction);
+
+pub struct AsynMarkedButton<M>(PhantomData<M>);
+
+impl<M: Component> AsynMarkedButton<M> {
+    /// Resolves with the pressed button entity bearing the `M` marker
+    pub fn pressed(&self) -> Promise<(), Entity> {
+        Promise::register(
+            move |world, id| {
+                let marker = world.init_component::<M>();
+                world.spawn(AsynMarkedButtonIteraction {
+                    promise: id,
+                    interaction: Interaction::Pressed,
+                    marker,
+                });
+            },
+            move |world, id| {
+                if let Some(despawn) = world
+                    .query::<(Entity, &AsynMarkedButtonIteraction)>()
+                    .iter(world)
+                    .find(|(_, b)| b.promise == id)
+                    .map(|(e, _)| e)
+                {
+                    world.despawn(despawn);
+                }
+            },
+        )
+    }
+}
+
+pub struct StatefulAsynMarkedButton<S, M>(S, PhantomData<M>);
+impl<S: 'static, M: Component> StatefulAsynMarkedButton<S, M> {
+    pub fn pressed(self) -> Promise<S, Entity> {
+        AsynMarkedButton::<M>(PhantomData).pressed().with(self.0)
+    }
+}
+
+/// Single-line text field edited with the keyboard while it is focused. The value is
+/// written to the first section of the entity [`Text`], if there is one. Pressing the
+/// field with the [`Interaction`] focuses it and unfocuses the rest of the fields,
+/// pressing `Enter` submits the value, see [`asyn::ui::text_input()`][asyn::text_input].
+#[derive(Default)]
+pub struct TextInput {
+    pub value: String,
+    pub focused: bool,
+}
+pecs_compat::impl_component!(TextInput);
+
+impl TextInput {
+    /// Empty text field receiving the keyboard input
+    pub fn focused() -> Self {
+        TextInput {
+            value: String::new(),
+            focused: true,
+        }
+    }
+}
+
+pub struct AsynTextInputSubmit {
+    promise: PromiseId,
+    entity: Entity,
+}
+pecs_compat::impl_component!(AsynTextInputSubmit);
+
+pub struct AsynTextInput(Entity);
+
+impl AsynTextInput {
+    /// Resolves with the value of the field when `Enter` is pressed in it
+    pub fn submitted(&self) -> Promise<(), String> {
+        let entity = self.0;
+        Promise::register(
+            move |world, id| {
+                world.spawn(AsynTextInputSubmit { promise: id, entity });
+            },
+            move |world, id| {
+                if let Some(despawn) = world
+                    .query::<(Entity, &AsynTextInputSubmit)>()
+                    .iter(world)
+                    .find(|(_, s)| s.promise == id)
+                    .map(|(e, _)| e)
+                {
+                    world.despawn(despawn);
+                }
+            },
+        )
+    }
+}
+
+pub struct StatefulAsynTextInput<S>(S, Entity);
+impl<S: 'static> StatefulAsynTextInput<S> {
+    pub fn submitted(self) -> Promise<S, String> {
+        AsynTextInput(self.1).submitted().with(self.0)
     }
 }
 
 fn resolve_buttons(
+    time: Res<Time<Real>>,
+    mut commands: Commands,
+    mut buttons: Query<(Entity, &mut AsynButtonIteraction)>,
+    interactions: ChangedButtons,
+) {
+    if interactions.is_empty() {
+        return;
+    }
+    let now = time.elapsed_seconds();
+    for (entity, mut waiter) in buttons.iter_mut() {
+        let Ok((_, interaction)) = interactions.get(waiter.entity) else {
+            continue;
+        };
+        let interaction = *interaction;
+        let triggered = match &mut waiter.trigger {
+            ButtonTrigger::Became(expected) => interaction == *expected,
+            ButtonTrigger::Released { pressed } => {
+                let released = *pressed && interaction != Interaction::Pressed;
+                *pressed = interaction == Interaction::Pressed;
+                released
+            }
+            ButtonTrigger::DoubleClicked { window, last_press } => {
+                if interaction != Interaction::Pressed {
+                    false
+                } else if last_press.is_some_and(|last| now - last <= *window) {
+                    true
+                } else {
+                    *last_press = Some(now);
+                    false
+                }
+            }
+            ButtonTrigger::Changed => true,
+        };
+        if triggered {
+            commands.entity(entity).despawn();
+            commands.promise(waiter.promise).resolve(interaction)
+        }
+    }
+}
+
+type ChangedButtons<'w, 's> = Query<'w, 's, (Entity, &'static Interaction), (Changed<Interaction>, With<Button>)>;
+
+fn resolve_marked_buttons(
+    world: &World,
     mut commands: Commands,
-    buttons: Query<(Entity, &AsynButtonIteraction)>,
-    interactions: Query<(Entity, &Interaction), (Changed<Interaction>, With<Button>)>,
+    buttons: Query<(Entity, &AsynMarkedButtonIteraction)>,
+    interactions: ChangedButtons,
 ) {
-    for (btn, interaction) in interactions.iter() {
-        if let Some((entity, btn)) = buttons
-            .iter()
-            .filter(|(_, b)| b.entity == btn && interaction == &b.interaction)
-            .next()
-        {
+    if interactions.is_empty() {
+        return;
+    }
+    for (entity, waiter) in buttons.iter() {
+        if let Some((btn, _)) = interactions.iter().find(|(btn, interaction)| {
+            *interaction == &waiter.interaction && world.entity(*btn).contains_id(waiter.marker)
+        }) {
             commands.entity(entity).despawn();
-            commands.promise(btn.promise).resolve(())
+            commands.promise(waiter.promise).resolve(btn)
+        }
+    }
+}
+
+type PressedTextInputs<'w, 's> = Query<'w, 's, (Entity, &'static Interaction), (Changed<Interaction>, With<TextInput>)>;
+
+fn edit_text_inputs(
+    mut commands: Commands,
+    mut keyboard: EventReader<KeyboardInput>,
+    mut inputs: Query<(Entity, &mut TextInput, Option<&mut Text>)>,
+    pressed: PressedTextInputs,
+    waiters: Query<(Entity, &AsynTextInputSubmit)>,
+) {
+    if let Some((focus, _)) = pressed.iter().find(|(_, i)| *i == &Interaction::Pressed) {
+        for (entity, mut input, _) in inputs.iter_mut() {
+            input.focused = entity == focus;
+        }
+    }
+    let keys: Vec<_> = keyboard
+        .read()
+        .filter(|key| key.state == ButtonState::Pressed)
+        .map(|key| key.logical_key.clone())
+        .collect();
+    if keys.is_empty() {
+        return;
+    }
+    for (entity, mut input, text) in inputs.iter_mut().filter(|(_, input, _)| input.focused) {
+        let mut submitted = false;
+        for key in keys.iter() {
+            match key {
+                Key::Character(chars) => input.value.push_str(chars),
+                Key::Space => input.value.push(' '),
+                Key::Backspace => {
+                    input.value.pop();
+                }
+                Key::Enter => submitted = true,
+                _ => {}
+            }
+        }
+        if let Some(section) = text.and_then(|text| text.into_inner().sections.first_mut()) {
+            section.value.clone_from(&input.value);
+        }
+        if !submitted {
+            continue;
+        }
+        for (waiter, submit) in waiters.iter().filter(|(_, s)| s.entity == entity) {
+            commands.entity(waiter).despawn();
+            commands.promise(submit.promise).resolve(input.value.clone());
         }
     }
 }
@@ -0,0 +1,93 @@
+//! Startup validation of the app configuration required by `pecs`. The checks run
+//! when the app finishes building plugins and report actionable errors instead of
+//! failing deep inside promise resolution later. Insert [`PecsConfig`] to tune them:
+//! ```ignore
+//! App::new()
+//!     .insert_resource(PecsConfig {
+//!         require_ui: true,
+//!         ..default()
+//!     })
+//!     .add_plugins((DefaultPlugins, PecsPlugin))
+//!     .run();
+//! ```
+use super::*;
+
+/// Configuration of the startup validation
+#[derive(Default)]
+pub struct PecsConfig {
+    /// Check that `UiPlugin` is added: ui promises never resolve without it
+    pub require_ui: bool,
+    /// Panic with the list of problems instead of logging them
+    pub panic_on_error: bool,
+}
+impl Resource for PecsConfig {}
+
+struct PecsInstalled {
+    validated: bool,
+}
+impl Resource for PecsInstalled {}
+
+/// Marks `pecs` as installed into the `app`, returns `false`
+/// if it is already installed
+pub fn install(app: &mut App) -> bool {
+    if pecs_compat::app_world(app).contains_resource::<PecsInstalled>() {
+        return false;
+    }
+    app.insert_resource(PecsInstalled { validated: false });
+    true
+}
+
+/// Returns the list of problems with the `app` configuration
+pub fn validate(app: &App) -> Vec<String> {
+    let world = pecs_compat::app_world(app);
+    let mut problems = vec![];
+    if !world.contains_resource::<Time>() && !world.contains_resource::<timer::PromiseTimerClock>() {
+        problems.push(
+            "`Time` resource is missing, timer promises will never resolve: \
+            add `MinimalPlugins`, `DefaultPlugins` or `TimePlugin`"
+                .to_string(),
+        );
+    }
+    if world.get_resource::<PecsConfig>().is_some_and(|c| c.require_ui) && !app.is_plugin_added::<bevy::ui::UiPlugin>()
+    {
+        problems.push(
+            "`UiPlugin` is not added, ui promises will never resolve: \
+            add `DefaultPlugins` or `UiPlugin`, or disable `PecsConfig::require_ui`"
+                .to_string(),
+        );
+    }
+    if app.get_schedule(Update).is_none() || app.get_schedule(Last).is_none() {
+        problems.push(
+            "`Update` or `Last` schedule is missing, pecs systems will never run: \
+            add `MinimalPlugins`, `DefaultPlugins` or `MainSchedulePlugin`"
+                .to_string(),
+        );
+    }
+    problems
+}
+
+/// Validates the `app` configuration once and reports the problems
+/// according to [`PecsConfig`]
+pub fn report(app: &mut App) {
+    let Some(mut installed) = pecs_compat::app_world_mut(app).get_resource_mut::<PecsInstalled>() else {
+        return;
+    };
+    if installed.validated {
+        return;
+    }
+    installed.validated = true;
+    let problems = validate(app);
+    if problems.is_empty() {
+        return;
+    }
+    let panic_on_error = pecs_compat::app_world(app)
+        .get_resource::<PecsConfig>()
+        .is_some_and(|c| c.panic_on_error);
+    if panic_on_error {
+        let problems = problems.iter().map(|p| format!("  {p}")).collect::<Vec<_>>().join("\n");
+        panic!("Invalid pecs configuration:\n{problems}");
+    }
+    for problem in problems {
+        error!("Invalid pecs configuration: {problem}");
+    }
+}
@@ -0,0 +1,132 @@
+//! Keyed values exchanged between unrelated chains: the consumer awaits
+//! [`asyn::value::wait(key)`][asyn::wait] and the producer provides the value with
+//! [`commands.provide_value(key, value)`][ValueCommandsExtension::provide_value],
+//! neither of them needs to know the other one.
+//! ```ignore
+//! fn show_profile(mut commands: Commands) {
+//!     commands.add(asyn::value::wait::<Profile>("player_profile").then(asyn!(_, profile => {
+//!         info!("Hello, {}", profile.name);
+//!     })));
+//! }
+//!
+//! fn load_profile(mut commands: Commands) {
+//!     commands.add(asyn::http::get("https://example.com/profile").send().then(
+//!         asyn!(_, response, mut commands: Commands => {
+//!             commands.provide_value("player_profile", Profile::parse(response));
+//!         }),
+//!     ));
+//! }
+//! ```
+use super::*;
+
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves with the value of type `T` provided for the `key`.
+    /// Resolves immediately if the value is already provided.
+    pub fn wait<T: 'static + Clone + Send + Sync>(key: &'static str) -> Promise<(), T> {
+        Promise::register(
+            move |world, id| {
+                let mut values = world.get_resource_or_insert_with(PromiseValues::<T>::default);
+                let slot = values.slots.entry(key).or_default();
+                let Some(value) = slot.value.clone() else {
+                    slot.waiters.push(id);
+                    return;
+                };
+                promise_resolve::<(), T>(world, id, (), value);
+            },
+            move |world, id| {
+                if let Some(mut values) = world.get_resource_mut::<PromiseValues<T>>() {
+                    if let Some(slot) = values.slots.get_mut(key) {
+                        slot.waiters.retain(|waiter| waiter != &id);
+                    }
+                }
+            },
+        )
+    }
+}
+
+pub trait ValueOpsExtension<S> {
+    fn wait_value<T: 'static + Clone + Send + Sync>(self, key: &'static str) -> Promise<S, T>;
+}
+impl<S: 'static> ValueOpsExtension<S> for AsynOps<S> {
+    fn wait_value<T: 'static + Clone + Send + Sync>(self, key: &'static str) -> Promise<S, T> {
+        asyn::wait(key).map(|_| self.0)
+    }
+}
+
+struct ValueSlot<T> {
+    value: Option<T>,
+    waiters: Vec<PromiseId>,
+}
+impl<T> Default for ValueSlot<T> {
+    fn default() -> Self {
+        ValueSlot {
+            value: None,
+            waiters: vec![],
+        }
+    }
+}
+
+/// Values of type `T` provided by their keys and promises waiting for them
+pub struct PromiseValues<T: 'static + Send + Sync> {
+    slots: HashMap<&'static str, ValueSlot<T>>,
+}
+impl<T: 'static + Send + Sync> Resource for PromiseValues<T> {}
+impl<T: 'static + Send + Sync> Default for PromiseValues<T> {
+    fn default() -> Self {
+        PromiseValues { slots: HashMap::new() }
+    }
+}
+
+impl<T: 'static + Send + Sync> PromiseValues<T> {
+    /// Value provided for the `key`
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.slots.get(key).and_then(|slot| slot.value.as_ref())
+    }
+}
+
+/// Provides the value for the key, see [`ValueCommandsExtension::provide_value`]
+pub struct ProvideValueCommand<T> {
+    key: &'static str,
+    value: T,
+}
+
+impl<T: 'static + Clone + Send + Sync> Command for ProvideValueCommand<T> {
+    fn apply(self, world: &mut World) {
+        let mut values = world.get_resource_or_insert_with(PromiseValues::<T>::default);
+        let slot = values.slots.entry(self.key).or_default();
+        slot.value = Some(self.value.clone());
+        let waiters = mem::take(&mut slot.waiters);
+        for waiter in waiters {
+            promise_resolve::<(), T>(world, waiter, (), self.value.clone());
+        }
+    }
+}
+
+pub trait ValueCommandsExtension {
+    /// Provide the `value` for the `key`: promises waiting for it with
+    /// [`asyn::value::wait()`][asyn::wait] resolve with the clone of the value.
+    /// The value is kept, so later waiters resolve with it too, until
+    /// it is replaced with the new one or forgotten.
+    fn provide_value<T: 'static + Clone + Send + Sync>(&mut self, key: &'static str, value: T);
+
+    /// Forget the value of type `T` provided for the `key`,
+    /// the next waiters wait for the new value
+    fn forget_value<T: 'static + Send + Sync>(&mut self, key: &'static str);
+}
+
+impl<'w, 's> ValueCommandsExtension for Commands<'w, 's> {
+    fn provide_value<T: 'static + Clone + Send + Sync>(&mut self, key: &'static str, value: T) {
+        self.add(ProvideValueCommand { key, value });
+    }
+    fn forget_value<T: 'static + Send + Sync>(&mut self, key: &'static str) {
+        self.add(move |world: &mut World| {
+            if let Some(mut values) = world.get_resource_mut::<PromiseValues<T>>() {
+                if let Some(slot) = values.slots.get_mut(key) {
+                    slot.value = None;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,127 @@
+//! Opt-in watchdog discarding orphaned chains. Chains bound to an entity with
+//! [`Promise::bound_to_entity`] are discarded when the entity is despawned for
+//! longer than [`Watchdog::grace`] seconds. Add [`PromiseWatchdogPlugin`] to enable it.
+use super::*;
+
+pub struct PromiseWatchdogPlugin {
+    /// Seconds the bound entity may be missing before the chain is discarded
+    pub grace: f32,
+}
+impl Default for PromiseWatchdogPlugin {
+    fn default() -> Self {
+        PromiseWatchdogPlugin { grace: 1. }
+    }
+}
+impl Plugin for PromiseWatchdogPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Watchdog {
+            grace: self.grace,
+            watched: vec![],
+        });
+        app.add_systems(Last, process_watchdog);
+    }
+}
+
+struct Watched {
+    promise: PromiseId,
+    entity: Entity,
+    missing_for: f32,
+    signature: fn() -> String,
+    pending: fn(&World, PromiseId) -> bool,
+    discard: fn(&mut World, PromiseId),
+}
+
+/// Chains watched for orphaning
+pub struct Watchdog {
+    pub grace: f32,
+    watched: Vec<Watched>,
+}
+impl Resource for Watchdog {}
+
+impl Watchdog {
+    /// Number of chains currently watched
+    pub fn len(&self) -> usize {
+        self.watched.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.watched.is_empty()
+    }
+}
+
+impl<S: 'static, R: 'static> Promise<S, R> {
+    /// Bind the promise to the `entity`: if [`PromiseWatchdogPlugin`] is added,
+    /// the promise is discarded when the `entity` is despawned for longer than
+    /// [`Watchdog::grace`] seconds.
+    /// ```ignore
+    /// fn spawn_dialog(mut commands: Commands) {
+    ///     let dialog = commands.spawn(NodeBundle::default()).id();
+    ///     commands.add(
+    ///         Promise::from(dialog)
+    ///             .then(asyn!(state => state.asyn().timeout(5.)))
+    ///             .then(asyn!(state, mut commands: Commands => {
+    ///                 commands.entity(state.value).despawn_recursive();
+    ///             }))
+    ///             .bound_to_entity(dialog),
+    ///     );
+    /// }
+    /// ```
+    pub fn bound_to_entity(mut self, entity: Entity) -> Self {
+        let register = self.register.take();
+        self.register = Some(Box::new(move |world, id| {
+            if let Some(mut watchdog) = world.get_resource_mut::<Watchdog>() {
+                watchdog.watched.push(Watched {
+                    promise: id,
+                    entity,
+                    missing_for: 0.,
+                    signature: || format!("<{}, {}>", type_name::<S>(), type_name::<R>()),
+                    pending: promise_is_pending::<S, R>,
+                    discard: promise_discard::<S, R>,
+                });
+            }
+            if let Some(register) = register {
+                register(world, id)
+            }
+        }));
+        let discard = self.discard.take();
+        self.discard = Some(Box::new(move |world, id| {
+            if let Some(mut watchdog) = world.get_resource_mut::<Watchdog>() {
+                watchdog.watched.retain(|watched| watched.promise != id);
+            }
+            if let Some(discard) = discard {
+                discard(world, id)
+            }
+        }));
+        self
+    }
+}
+
+fn process_watchdog(world: &mut World) {
+    let delta = world.resource::<Time>().delta_seconds();
+    let mut watched = mem::take(&mut world.resource_mut::<Watchdog>().watched);
+    let grace = world.resource::<Watchdog>().grace;
+    let mut reaped = vec![];
+    watched.retain_mut(|w| {
+        if !(w.pending)(world, w.promise) {
+            return false;
+        }
+        if world.get_entity(w.entity).is_some() {
+            w.missing_for = 0.;
+            return true;
+        }
+        w.missing_for += delta;
+        if w.missing_for > grace {
+            reaped.push((w.promise, w.entity, w.signature, w.discard));
+            false
+        } else {
+            true
+        }
+    });
+    world.resource_mut::<Watchdog>().watched.extend(watched);
+    for (promise, entity, signature, discard) in reaped {
+        info!(
+            "Watchdog discarded {promise}{}: {entity:?} is despawned for more than {grace}s",
+            signature()
+        );
+        discard(world, promise);
+    }
+}
@@ -0,0 +1,126 @@
+//! Window lifecycle promises for multi-window apps: wait for windows
+//! to be created or closed and for the primary window to change.
+use bevy::window::{PrimaryWindow, WindowClosed, WindowCreated};
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(window)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves with the entity of the next created window.
+    /// ```ignore
+    /// fn open_panel(mut commands: Commands) {
+    ///     commands.spawn(Window { title: "Inspector".into(), ..default() });
+    ///     commands.add(
+    ///         Promise::start(asyn!(_ => asyn::window::created()))
+    ///             .then(asyn!(_, window => {
+    ///                 info!("Panel opened, waiting for it to close");
+    ///                 asyn::window::closed(window)
+    ///             }))
+    ///             .then(asyn!(_ => {
+    ///                 info!("Panel closed");
+    ///             })),
+    ///     );
+    /// }
+    /// ```
+    pub fn created() -> Promise<(), Entity> {
+        Promise::register(
+            |world, id| {
+                world.resource_mut::<WindowWaiters>().created.push(id);
+            },
+            |world, id| {
+                world
+                    .resource_mut::<WindowWaiters>()
+                    .created
+                    .retain(|waiter| waiter != &id);
+            },
+        )
+    }
+
+    /// Creates promise that resolves when the `window` is closed,
+    /// or immediately if there is no such window.
+    pub fn closed(window: Entity) -> Promise<(), ()> {
+        Promise::register(
+            move |world, id| {
+                if world.get::<Window>(window).is_none() {
+                    promise_resolve::<(), ()>(world, id, (), ());
+                    return;
+                }
+                world.resource_mut::<WindowWaiters>().closed.push((id, window));
+            },
+            |world, id| {
+                world
+                    .resource_mut::<WindowWaiters>()
+                    .closed
+                    .retain(|(waiter, _)| waiter != &id);
+            },
+        )
+    }
+
+    /// Creates promise that resolves with the new primary window entity
+    /// when the [`PrimaryWindow`] marker moves to another window.
+    pub fn primary_changed() -> Promise<(), Entity> {
+        Promise::register(
+            |world, id| {
+                world.resource_mut::<WindowWaiters>().primary.push(id);
+            },
+            |world, id| {
+                world
+                    .resource_mut::<WindowWaiters>()
+                    .primary
+                    .retain(|waiter| waiter != &id);
+            },
+        )
+    }
+}
+
+pub struct PromiseWindowPlugin;
+impl Plugin for PromiseWindowPlugin {
+    fn build(&self, app: &mut App) {
+        // keep working in apps without WindowPlugin
+        app.add_event::<WindowCreated>();
+        app.add_event::<WindowClosed>();
+        app.init_resource::<WindowWaiters>();
+        app.add_systems(Update, process_windows);
+    }
+}
+
+/// Promises waiting for the window events
+#[derive(Default)]
+pub struct WindowWaiters {
+    created: Vec<PromiseId>,
+    closed: Vec<(PromiseId, Entity)>,
+    primary: Vec<PromiseId>,
+}
+impl Resource for WindowWaiters {}
+
+fn process_windows(
+    mut commands: Commands,
+    mut waiters: ResMut<WindowWaiters>,
+    mut created: EventReader<WindowCreated>,
+    mut closed: EventReader<WindowClosed>,
+    primary: Query<Entity, Added<PrimaryWindow>>,
+) {
+    for event in created.read() {
+        for id in waiters.created.drain(..) {
+            commands.add(PromiseCommand::resolve(id, event.window));
+        }
+    }
+    for event in closed.read() {
+        waiters.closed.retain(|(id, window)| {
+            if *window == event.window {
+                commands.add(PromiseCommand::resolve(*id, ()));
+                false
+            } else {
+                true
+            }
+        });
+    }
+    if let Some(window) = primary.iter().next() {
+        for id in waiters.primary.drain(..) {
+            commands.add(PromiseCommand::resolve(id, window));
+        }
+    }
+}
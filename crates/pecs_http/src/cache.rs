@@ -0,0 +1,105 @@
+//! Response caching. [`Request::cached`] resolves with the response stored in the
+//! [`HttpCache`] if it is not older than `ttl` seconds, otherwise it sends the request
+//! and stores the response, so polling loops don't hit the server every iteration:
+//! ```ignore
+//! fn refresh_leaderboard(mut commands: Commands) {
+//!     commands.add(Promise::repeat(
+//!         (),
+//!         asyn!(_ => {
+//!             asyn::http::get("https://example.com/leaderboard")
+//!                 .cached(30.)
+//!                 .send()
+//!                 .then(asyn!(_, response, mut board: ResMut<Leaderboard> => {
+//!                     if let Ok(response) = response {
+//!                         board.update(&response.bytes);
+//!                     }
+//!                     asyn::timeout(5.).with_result(Repeat::Continue)
+//!                 }))
+//!         }),
+//!     ));
+//! }
+//! ```
+//! Responses are keyed by the url and the headers of the request. Only the `GET`
+//! requests with 2xx responses are cached, other requests are sent as usual. Streamed
+//! responses are never cached.
+use std::collections::BTreeMap;
+
+use super::*;
+
+/// The url and the headers of the cached request
+type CacheKey = (String, BTreeMap<String, String>);
+
+/// Responses of the [`cached()`][Request::cached] requests with the real time they expire at
+#[derive(Default)]
+pub struct HttpCache(HashMap<CacheKey, (f32, Response)>);
+impl Resource for HttpCache {}
+
+impl HttpCache {
+    /// Removes the responses of the `url` cached with any headers
+    pub fn invalidate<U: ToString>(&mut self, url: U) {
+        let url = url.to_string();
+        self.0.retain(|(cached, _), _| cached != &url);
+    }
+
+    /// Removes all the cached responses
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn get(&mut self, key: &CacheKey, now: f32) -> Option<Response> {
+        let (expires, response) = self.0.get(key)?;
+        if *expires > now {
+            return Some(response.clone());
+        }
+        self.0.remove(key);
+        None
+    }
+
+    fn insert(&mut self, key: CacheKey, response: Response, expires: f32, now: f32) {
+        self.0.retain(|_, (expires, _)| *expires > now);
+        self.0.insert(key, (expires, response));
+    }
+}
+
+impl Request {
+    /// Resolve with the response cached in the [`HttpCache`] if it is not older than
+    /// `ttl` seconds of real time, see [`cache`][self]
+    pub fn cached(mut self, ttl: f32) -> Self {
+        self.cache = Some(ttl);
+        self
+    }
+}
+
+impl<S: 'static> StatefulRequest<S> {
+    /// Stateful version of [`Request::cached()`]
+    pub fn cached(mut self, ttl: f32) -> Self {
+        self.1 = self.1.cached(ttl);
+        self
+    }
+}
+
+/// Resolves with the cached response of the `request`, sends it on miss
+pub(crate) fn fetch(request: Request, ttl: f32) -> Promise<(), Result<Response, String>> {
+    if request.request.method != "GET" {
+        return request.fetch();
+    }
+    let key = (request.request.url.clone(), request.request.headers.clone());
+    Promise::new(
+        (request, key, ttl),
+        asyn!(s, world: &mut World => {
+            let (request, key, ttl) = s.value;
+            let now = retry::real_time(world);
+            if let Some(response) = world.resource_mut::<HttpCache>().get(&key, now) {
+                return PromiseResult::Resolve((), Ok(response));
+            }
+            PromiseResult::Await(request.fetch().map(move |_| (key, ttl)).then(asyn!(s, response, world: &mut World => {
+                let (key, ttl) = s.value;
+                if let Some(cached) = response.as_ref().ok().filter(|response| response.ok) {
+                    let now = retry::real_time(world);
+                    world.resource_mut::<HttpCache>().insert(key, cached.clone(), now + ttl, now);
+                }
+                PromiseResult::Resolve((), response)
+            })))
+        }),
+    )
+}
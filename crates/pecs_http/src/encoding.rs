@@ -0,0 +1,78 @@
+//! Compressed response bodies. Enable `gzip`, `deflate` or `brotli` features to
+//! advertise the codecs with the `Accept-Encoding` request header and decode
+//! responses with [`ResponseExt::bytes_decoded`] / [`ResponseExt::text_decoded`].
+//! On wasm the browser negotiates and decodes the encoding itself, so the
+//! response body is already decoded.
+use super::*;
+use std::borrow::Cow;
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli"))]
+use std::io::Read;
+
+/// Value of the `Accept-Encoding` header for the enabled codecs,
+/// `None` if there are no codecs enabled.
+pub(crate) fn accept_encoding() -> Option<String> {
+    let codecs: &[&str] = &[
+        #[cfg(feature = "gzip")]
+        "gzip",
+        #[cfg(feature = "deflate")]
+        "deflate",
+        #[cfg(feature = "brotli")]
+        "br",
+    ];
+    if codecs.is_empty() {
+        None
+    } else {
+        Some(codecs.join(", "))
+    }
+}
+
+pub trait ResponseExt {
+    /// Response body decoded according to the `Content-Encoding` header.
+    /// Fails if the encoding is unknown or its feature is not enabled.
+    fn bytes_decoded(&self) -> Result<Cow<'_, [u8]>, String>;
+    /// Response body decoded according to the `Content-Encoding` header
+    /// and interpreted as `utf-8` text.
+    /// ```ignore
+    /// asyn::http::get("https://bevyengine.org").send().then(asyn!(_, result => {
+    ///     let text = result.and_then(|response| response.text_decoded());
+    ///     info!("Bevy main page: {text:?}");
+    /// }))
+    /// ```
+    fn text_decoded(&self) -> Result<String, String>;
+}
+
+impl ResponseExt for Response {
+    fn bytes_decoded(&self) -> Result<Cow<'_, [u8]>, String> {
+        let encoding = self
+            .headers
+            .get("content-encoding")
+            .map(|encoding| encoding.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        match encoding.as_str() {
+            "" | "identity" => Ok(Cow::Borrowed(&self.bytes)),
+            #[cfg(feature = "gzip")]
+            "gzip" | "x-gzip" => read_all(flate2::read::GzDecoder::new(&self.bytes[..])).map(Cow::Owned),
+            #[cfg(feature = "deflate")]
+            // most servers send zlib-wrapped data, but some send raw deflate stream
+            "deflate" => read_all(flate2::read::ZlibDecoder::new(&self.bytes[..]))
+                .or_else(|_| read_all(flate2::read::DeflateDecoder::new(&self.bytes[..])))
+                .map(Cow::Owned),
+            #[cfg(feature = "brotli")]
+            "br" => read_all(brotli_decompressor::Decompressor::new(&self.bytes[..], 4096)).map(Cow::Owned),
+            encoding => Err(format!("Unsupported content encoding: {encoding}")),
+        }
+    }
+    fn text_decoded(&self) -> Result<String, String> {
+        let bytes = self.bytes_decoded()?;
+        String::from_utf8(bytes.into_owned()).map_err(|e| format!("Response is not utf-8 text: {e}"))
+    }
+}
+
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli"))]
+fn read_all<R: Read>(mut reader: R) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![];
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Can't decode response: {e}"))?;
+    Ok(bytes)
+}
@@ -0,0 +1,73 @@
+//! Typed errors of the http requests, for the chains that need to tell the
+//! transport failures from the unexpected responses.
+use std::fmt;
+
+use super::*;
+
+/// The message the timed out requests resolve with, see [`Request::timeout`]
+pub const TIMED_OUT: &str = "Request timed out";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HttpError {
+    /// The request is not completed, the connection failed
+    Request(String),
+    /// The request is aborted with the [`RequestHandle`][handle::RequestHandle]
+    Aborted,
+    /// The response is not received in time, see [`Request::timeout`]
+    Timeout,
+    /// Every attempt of the request failed, see [`Request::retry`]
+    Exhausted { attempts: usize, last: Box<HttpError> },
+    /// The server responded with the non-2xx status
+    Status { status: u16, status_text: String },
+    /// The response body can't be decoded or deserialized
+    Body(String),
+}
+
+impl HttpError {
+    /// Fails with [`HttpError::Status`] if the `response` status is not 2xx
+    pub fn check(response: Response) -> Result<Response, HttpError> {
+        if response.ok {
+            Ok(response)
+        } else {
+            Err(HttpError::Status {
+                status: response.status,
+                status_text: response.status_text,
+            })
+        }
+    }
+
+    /// `true` for the transport failures, timeouts and 5xx responses, the conditions
+    /// [`Request::retry`] retries on
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            HttpError::Status { status, .. } => *status >= 500,
+            HttpError::Request(_) | HttpError::Timeout => true,
+            HttpError::Aborted | HttpError::Body(_) | HttpError::Exhausted { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Request(message) => write!(f, "{message}"),
+            HttpError::Aborted => write!(f, "{}", handle::ABORTED),
+            HttpError::Timeout => write!(f, "{TIMED_OUT}"),
+            HttpError::Exhausted { attempts, last } => write!(f, "Request failed after {attempts} attempts: {last}"),
+            HttpError::Status { status, status_text } => write!(f, "Unexpected status: {status} {status_text}"),
+            HttpError::Body(message) => write!(f, "Invalid response body: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<String> for HttpError {
+    fn from(message: String) -> Self {
+        match message.as_str() {
+            handle::ABORTED => HttpError::Aborted,
+            TIMED_OUT => HttpError::Timeout,
+            _ => HttpError::Request(message),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Query parameters and form bodies. [`Request::query`] appends the url-encoded
+//! parameter to the url, [`Request::form`] and [`Request::multipart`] build the
+//! body and set its `Content-Type` header:
+//! ```ignore
+//! fn submit_score(mut commands: Commands, score: Res<Score>) {
+//!     commands.add(
+//!         asyn::http::post("https://example.com/scores")
+//!             .query("season", "spring 2024")
+//!             .form([("player", "bevy".to_string()), ("score", score.0.to_string())])
+//!             .send()
+//!             .then(asyn!(_, result => info!("Score submitted: {result:?}"))),
+//!     );
+//! }
+//!
+//! fn upload_replay(mut commands: Commands, replay: Res<Replay>) {
+//!     let bytes = replay.to_bytes();
+//!     commands.add(
+//!         asyn::http::post("https://example.com/replays")
+//!             .multipart([
+//!                 Part::text("title", "Final round"),
+//!                 Part::bytes("replay", bytes).file_name("final.replay"),
+//!             ])
+//!             .send()
+//!             .then(asyn!(_, result => info!("Replay uploaded: {result:?}"))),
+//!     );
+//! }
+//! ```
+//! The url and the body are built before the request is sent, so native and wasm
+//! requests are the same.
+use super::*;
+
+/// Field of the [`Request::multipart`] body
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    bytes: Vec<u8>,
+}
+
+impl Part {
+    /// Text field with the `value`
+    pub fn text<N: ToString, V: ToString>(name: N, value: V) -> Self {
+        Self::bytes(name, value.to_string())
+    }
+    /// Binary field with the `bytes`, sent as `application/octet-stream`
+    /// unless the [`content_type()`][Part::content_type] is set
+    pub fn bytes<N: ToString, B: Into<Vec<u8>>>(name: N, bytes: B) -> Self {
+        Self {
+            name: name.to_string(),
+            file_name: None,
+            content_type: None,
+            bytes: bytes.into(),
+        }
+    }
+    /// Send the field as the file with the `file_name`
+    pub fn file_name<F: ToString>(mut self, file_name: F) -> Self {
+        self.file_name = Some(file_name.to_string());
+        self
+    }
+    /// Override the `Content-Type` of the field
+    pub fn content_type<C: ToString>(mut self, content_type: C) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+}
+
+impl Request {
+    /// Append the url-encoded `key=value` parameter to the query of the url
+    pub fn query<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        let url = &mut self.request.url;
+        let fragment = url.find('#').map(|at| url.split_off(at));
+        match url.find('?') {
+            None => url.push('?'),
+            Some(at) if at + 1 < url.len() && !url.ends_with('&') => url.push('&'),
+            Some(_) => {}
+        }
+        url.push_str(&format!("{}={}", encode(&key.to_string()), encode(&value.to_string())));
+        if let Some(fragment) = fragment {
+            url.push_str(&fragment);
+        }
+        self
+    }
+    /// Set the `application/x-www-form-urlencoded` body with the `fields`
+    pub fn form<K: ToString, V: ToString, I: IntoIterator<Item = (K, V)>>(self, fields: I) -> Self {
+        let body = fields
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", encode(&key.to_string()), encode(&value.to_string())))
+            .collect::<Vec<_>>()
+            .join("&");
+        self.header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+    }
+    /// Set the `multipart/form-data` body with the `parts`
+    pub fn multipart<I: IntoIterator<Item = Part>>(self, parts: I) -> Self {
+        let parts: Vec<_> = parts.into_iter().collect();
+        let boundary = boundary(&parts);
+        let mut body = vec![];
+        for part in parts {
+            body.extend(format!("--{boundary}\r\n").into_bytes());
+            let mut disposition = format!("Content-Disposition: form-data; name=\"{}\"", quote(&part.name));
+            if let Some(file_name) = &part.file_name {
+                disposition.push_str(&format!("; filename=\"{}\"", quote(file_name)));
+            }
+            body.extend(format!("{disposition}\r\n").into_bytes());
+            let content_type = match (&part.content_type, &part.file_name) {
+                (Some(content_type), _) => Some(content_type.as_str()),
+                (None, Some(_)) => Some("application/octet-stream"),
+                (None, None) => None,
+            };
+            if let Some(content_type) = content_type {
+                body.extend(format!("Content-Type: {content_type}\r\n").into_bytes());
+            }
+            body.extend(b"\r\n");
+            body.extend(part.bytes);
+            body.extend(b"\r\n");
+        }
+        body.extend(format!("--{boundary}--\r\n").into_bytes());
+        self.header("Content-Type", format!("multipart/form-data; boundary={boundary}"))
+            .body(body)
+    }
+}
+
+impl<S: 'static> StatefulRequest<S> {
+    /// Stateful version of [`Request::query()`]
+    pub fn query<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.1 = self.1.query(key, value);
+        self
+    }
+    /// Stateful version of [`Request::form()`]
+    pub fn form<K: ToString, V: ToString, I: IntoIterator<Item = (K, V)>>(mut self, fields: I) -> Self {
+        self.1 = self.1.form(fields);
+        self
+    }
+    /// Stateful version of [`Request::multipart()`]
+    pub fn multipart<I: IntoIterator<Item = Part>>(mut self, parts: I) -> Self {
+        self.1 = self.1.multipart(parts);
+        self
+    }
+}
+
+/// Percent-encodes everything except the unreserved characters
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Escapes the quoted `Content-Disposition` parameter
+fn quote(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
+
+/// The boundary that doesn't occur in the bodies of the `parts`
+fn boundary(parts: &[Part]) -> String {
+    (0u64..)
+        .map(|attempt| format!("pecs-boundary-{attempt:016x}"))
+        .find(|boundary| {
+            parts.iter().all(|part| {
+                !part
+                    .bytes
+                    .windows(boundary.len())
+                    .any(|window| window == boundary.as_bytes())
+            })
+        })
+        .unwrap()
+}
@@ -0,0 +1,127 @@
+//! Handles of the requests sent with [`send_with_handle()`][crate::Request::send_with_handle].
+//! The handle could be stored anywhere to check the request status or abort it from
+//! unrelated systems. Aborted requests resolve with `Err`, so the chain continues:
+//! ```ignore
+//! #[derive(Resource)]
+//! struct Download(RequestHandle);
+//!
+//! fn start_download(mut commands: Commands) {
+//!     let (request, handle) = asyn::http::get("https://example.com/level.zip").send_with_handle();
+//!     commands.insert_resource(Download(handle));
+//!     commands.add(request.then(asyn!(_, response => {
+//!         match response {
+//!             Ok(response) => info!("Downloaded {} bytes", response.bytes.len()),
+//!             Err(e) => info!("Download failed: {e}"),
+//!         }
+//!     })));
+//! }
+//!
+//! fn cancel_download(mut commands: Commands, download: Res<Download>) {
+//!     download.0.abort(&mut commands);
+//! }
+//! ```
+use std::sync::{Arc, Mutex};
+
+use pecs_compat::Command;
+use pecs_core::promise_resolve;
+
+use super::*;
+
+/// The message the aborted requests resolve with
+pub const ABORTED: &str = "Request aborted";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestStatus {
+    /// The request is not sent yet
+    Waiting,
+    /// The request is sent, waiting for the response
+    InFlight,
+    /// The response is received
+    Completed,
+    /// The request is aborted with the handle or its promise is discarded
+    Aborted,
+}
+
+struct RequestState {
+    status: RequestStatus,
+    promise: Option<PromiseId>,
+    #[cfg(target_arch = "wasm32")]
+    resolver: Option<WasmResolver>,
+}
+
+#[derive(Clone)]
+pub struct RequestHandle(Arc<Mutex<RequestState>>);
+
+impl RequestHandle {
+    pub(crate) fn new() -> Self {
+        RequestHandle(Arc::new(Mutex::new(RequestState {
+            status: RequestStatus::Waiting,
+            promise: None,
+            #[cfg(target_arch = "wasm32")]
+            resolver: None,
+        })))
+    }
+
+    pub fn status(&self) -> RequestStatus {
+        self.0.lock().unwrap().status
+    }
+
+    /// Abort the request: the response is ignored and the request promise
+    /// resolves with [`Err(ABORTED)`][ABORTED]. Requests waiting to be sent
+    /// resolve the same way when their turn comes.
+    pub fn abort(&self, commands: &mut Commands) {
+        commands.add(AbortRequestCommand(self.clone()));
+    }
+
+    /// Marks the request `id` as sent, returns `false` if it is already aborted
+    pub(crate) fn sent(&self, id: PromiseId) -> bool {
+        let mut state = self.0.lock().unwrap();
+        if state.status == RequestStatus::Aborted {
+            return false;
+        }
+        state.status = RequestStatus::InFlight;
+        state.promise = Some(id);
+        true
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn set_resolver(&self, resolver: WasmResolver) {
+        self.0.lock().unwrap().resolver = Some(resolver);
+    }
+
+    pub(crate) fn finish(&self, status: RequestStatus) {
+        let mut state = self.0.lock().unwrap();
+        if state.status != RequestStatus::Aborted {
+            state.status = status;
+        }
+    }
+}
+
+/// Aborts the request, see [`RequestHandle::abort`]
+pub struct AbortRequestCommand(pub RequestHandle);
+impl Command for AbortRequestCommand {
+    fn apply(self, world: &mut World) {
+        let mut state = self.0 .0.lock().unwrap();
+        match state.status {
+            RequestStatus::Waiting => state.status = RequestStatus::Aborted,
+            RequestStatus::InFlight => {
+                let Some(id) = state.promise else {
+                    return;
+                };
+                // the response could be received already and waiting to be resolved
+                #[cfg(not(target_arch = "wasm32"))]
+                if world.resource_mut::<Requests>().remove(&id).is_none() {
+                    return;
+                }
+                #[cfg(target_arch = "wasm32")]
+                if let Some(resolver) = state.resolver.take() {
+                    resolver.discard();
+                }
+                state.status = RequestStatus::Aborted;
+                drop(state);
+                promise_resolve::<(), Result<Response, String>>(world, id, (), Err(ABORTED.to_string()));
+            }
+            RequestStatus::Completed | RequestStatus::Aborted => {}
+        }
+    }
+}
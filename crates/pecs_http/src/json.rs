@@ -0,0 +1,54 @@
+//! Json response bodies, requires the `json` feature. Deserialize the body with
+//! [`JsonResponseExt::json`], or send the request with
+//! [`send_json()`][crate::Request::send_json] to resolve with the deserialized value:
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct Level {
+//!     name: String,
+//! }
+//!
+//! fn load_level(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::http::get("https://example.com/level.json")
+//!             .send_json::<Level>()
+//!             .then(asyn!(_, level => match level {
+//!                 Ok(level) => info!("Loaded level {}", level.name),
+//!                 Err(e) => error!("Can't load the level: {e}"),
+//!             })),
+//!     );
+//! }
+//! ```
+use serde::de::DeserializeOwned;
+
+use super::*;
+use encoding::ResponseExt;
+use error::HttpError;
+
+pub trait JsonResponseExt {
+    /// Response body decoded according to the `Content-Encoding` header
+    /// and deserialized from json
+    fn json<T: DeserializeOwned>(&self) -> Result<T, HttpError>;
+}
+
+impl JsonResponseExt for Response {
+    fn json<T: DeserializeOwned>(&self) -> Result<T, HttpError> {
+        let bytes = self.bytes_decoded().map_err(HttpError::Body)?;
+        serde_json::from_slice(&bytes).map_err(|e| HttpError::Body(e.to_string()))
+    }
+}
+
+impl Request {
+    /// Send the request and resolve with the response body deserialized from json.
+    /// Responses with non-2xx status resolve with [`HttpError::Status`].
+    pub fn send_json<T: 'static + DeserializeOwned>(self) -> Promise<(), Result<T, HttpError>> {
+        self.send_typed()
+            .map_result(|response| -> Result<T, HttpError> { HttpError::check(response?)?.json() })
+    }
+}
+
+impl<S: 'static> StatefulRequest<S> {
+    /// Stateful version of [`Request::send_json()`]
+    pub fn send_json<T: 'static + DeserializeOwned>(self) -> Promise<S, Result<T, HttpError>> {
+        self.1.send_json().map(move |_| self.0)
+    }
+}
@@ -1,30 +1,125 @@
 //! Make `http` requests asyncroniusly via [`ehttp`](https://docs.rs/ehttp/)
 
+use pecs_compat::bevy;
+
 use bevy::prelude::*;
 use bevy::tasks::Task;
 use bevy::utils::HashMap;
 pub use ehttp::Response;
 use futures_lite::future;
-use pecs_core::{AsynOps, Promise, PromiseCommand, PromiseId, PromiseLikeBase, PromiseResult};
+use pecs_macro::asyn;
+use pecs_core::inspect::{chain_label, ChainLabels};
+use pecs_core::{promise_resolve, AsynOps, Promise, PromiseCommand, PromiseId, PromiseLikeBase, PromiseResult};
 
 #[cfg(not(target_arch = "wasm32"))]
 use bevy::tasks::AsyncComputeTaskPool;
-#[cfg(target_arch = "wasm32")]
-use pecs_core::promise_resolve;
+use handle::{RequestHandle, RequestStatus};
+pub use limit::HttpConfig;
 #[cfg(target_arch = "wasm32")]
 use std::cell::Cell;
 #[cfg(target_arch = "wasm32")]
 use std::rc::Rc;
 
-pub struct PromiseHttpPlugin;
+pub mod cache;
+pub mod encoding;
+pub mod error;
+pub mod form;
+pub mod handle;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod limit;
+pub mod net;
+mod retry;
+pub mod stream;
+pub mod upload;
+#[cfg(feature = "websocket")]
+pub mod ws;
+
+/// Adds the http systems. The plugin is added by `PecsPlugin`, add it before
+/// `PecsPlugin` to configure:
+/// ```ignore
+/// App::new()
+///     .add_plugins(PromiseHttpPlugin::default().with_trace_header(TRACE_HEADER))
+///     .add_plugins((DefaultPlugins, PecsPlugin))
+///     .run();
+/// ```
+#[derive(Default)]
+pub struct PromiseHttpPlugin {
+    /// Attach the `<chain-label>/<promise-id>` correlation header to the requests
+    /// sent from the chains marked with [`Promise::labeled`]
+    pub trace_header: Option<&'static str>,
+    /// Limits of the requests in flight, see [`limit`]
+    pub config: HttpConfig,
+    /// Url requested to check the network reachability on native platforms, see [`net`]
+    pub connectivity_probe: Option<&'static str>,
+}
+
+/// The default correlation header, see [`PromiseHttpPlugin::with_trace_header`]
+pub const TRACE_HEADER: &str = "X-Pecs-Trace";
+
+impl PromiseHttpPlugin {
+    /// Attach the `header` with the `<chain-label>/<promise-id>` value to the
+    /// requests sent from the labeled chains, for cross-service debugging
+    pub fn with_trace_header(mut self, header: &'static str) -> Self {
+        self.trace_header = Some(header);
+        self
+    }
+    /// Queue the requests over the `config` limits, see [`limit`]
+    pub fn with_config(mut self, config: HttpConfig) -> Self {
+        self.config = config;
+        self
+    }
+    /// Request the `url` with `HEAD` to check the network reachability while
+    /// there are promises waiting for it, see [`net`]. The network is not probed
+    /// by default.
+    pub fn with_connectivity_probe(mut self, url: &'static str) -> Self {
+        self.connectivity_probe = Some(url);
+        self
+    }
+}
+
+/// The correlation header configured with [`PromiseHttpPlugin::with_trace_header`]
+pub struct TraceHeader(pub &'static str);
+impl Resource for TraceHeader {}
+
 impl Plugin for PromiseHttpPlugin {
     fn build(&self, app: &mut App) {
+        if let Some(header) = self.trace_header {
+            app.insert_resource(TraceHeader(header));
+            app.init_resource::<ChainLabels>();
+        }
+        app.insert_resource(self.config.clone());
+        app.init_resource::<limit::RequestQueue>();
+        app.init_resource::<cache::HttpCache>();
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, limit::process_queue.after(process_requests));
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(Update, limit::process_queue);
         #[cfg(not(target_arch = "wasm32"))]
         app.init_resource::<Requests>();
         #[cfg(not(target_arch = "wasm32"))]
         app.add_systems(Update, process_requests);
-        // Silence unused variable warnings on non-wasm platforms
-        let _ = app;
+        #[cfg(not(target_arch = "wasm32"))]
+        app.init_resource::<upload::Uploads>();
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, upload::process_uploads.after(process_requests));
+        #[cfg(not(target_arch = "wasm32"))]
+        app.init_resource::<stream::Streams>();
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, stream::process_streams);
+        app.init_resource::<retry::RequestTimeouts>();
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, retry::process_timeouts.after(process_requests));
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(Update, retry::process_timeouts);
+        #[cfg(feature = "websocket")]
+        app.init_resource::<ws::Sockets>();
+        #[cfg(feature = "websocket")]
+        app.add_systems(Update, ws::process_sockets);
+        let mut connectivity = net::Connectivity::default();
+        connectivity.probe_url = self.connectivity_probe.map(str::to_string);
+        app.insert_resource(connectivity);
+        app.add_systems(Update, net::process_connectivity);
     }
 }
 
@@ -51,6 +146,11 @@ impl WasmResolver {
         self.0.replace(None);
     }
 
+    /// Returns `true` if the response is not received and the promise is not discarded yet
+    pub fn is_pending(&self) -> bool {
+        self.0.get().is_some()
+    }
+
     pub fn register(&self, world: &mut World, id: PromiseId) {
         self.0.replace(Some((id, world as *mut World)));
     }
@@ -60,59 +160,226 @@ unsafe impl Send for WasmResolver {}
 #[cfg(target_arch = "wasm32")]
 unsafe impl Sync for WasmResolver {}
 
-pub struct Request(ehttp::Request);
+pub struct Request {
+    request: ehttp::Request,
+    when_online: bool,
+    upload_progress: Option<Entity>,
+    handle: Option<RequestHandle>,
+    timeout: Option<f32>,
+    retry: Option<(usize, f32)>,
+    cache: Option<f32>,
+}
+// `ehttp::Request` is not `Clone`
+impl Clone for Request {
+    fn clone(&self) -> Self {
+        Self {
+            request: ehttp::Request {
+                method: self.request.method.clone(),
+                url: self.request.url.clone(),
+                body: self.request.body.clone(),
+                headers: self.request.headers.clone(),
+            },
+            when_online: self.when_online,
+            upload_progress: self.upload_progress,
+            handle: self.handle.clone(),
+            timeout: self.timeout,
+            retry: self.retry,
+            cache: self.cache,
+        }
+    }
+}
+
 impl Request {
     pub(crate) fn new() -> Self {
-        Self(ehttp::Request::get(""))
+        let mut request = ehttp::Request::get("");
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(accept) = encoding::accept_encoding() {
+            request.headers.insert("Accept-Encoding".to_string(), accept);
+        }
+        Self {
+            request,
+            when_online: false,
+            upload_progress: None,
+            handle: None,
+            timeout: None,
+            retry: None,
+            cache: None,
+        }
     }
     pub fn url<U: ToString>(mut self, url: U) -> Self {
-        self.0.url = url.to_string();
+        self.request.url = url.to_string();
         self
     }
     pub fn method<M: ToString>(mut self, method: M) -> Self {
-        self.0.method = method.to_string();
+        self.request.method = method.to_string();
         self
     }
     pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
-        self.0.body = body.into();
+        self.request.body = body.into();
         self
     }
     pub fn header<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
-        self.0.headers.insert(key.to_string(), value.to_string());
+        self.request.headers.insert(key.to_string(), value.to_string());
+        self
+    }
+    /// Wait for the network to become reachable before sending the request,
+    /// see [`net::asyn::online()`]
+    pub fn when_online(mut self) -> Self {
+        self.when_online = true;
+        self
+    }
+    /// Report the body upload progress with the [`UploadProgress`][upload::UploadProgress]
+    /// component of the `entity`
+    pub fn upload_progress(mut self, entity: Entity) -> Self {
+        self.upload_progress = Some(entity);
+        self
+    }
+    /// Resolve with [`Err(TIMED_OUT)`][error::TIMED_OUT] if the response is not received
+    /// in `seconds` of real time. With [`retry()`][Request::retry] the timeout applies
+    /// to every attempt.
+    pub fn timeout(mut self, seconds: f32) -> Self {
+        self.timeout = Some(seconds);
         self
     }
+    /// Send the request again up to `retries` times if it fails, times out or the
+    /// server responds with 5xx status. The delay before the retry starts with `backoff`
+    /// seconds and doubles with every attempt. When every attempt fails, the request
+    /// resolves with [`HttpError::Exhausted`][error::HttpError::Exhausted].
+    pub fn retry(mut self, retries: usize, backoff: f32) -> Self {
+        self.retry = Some((retries, backoff));
+        self
+    }
+    /// Same as [`send()`][Request::send], but returns the [`RequestHandle`] along with
+    /// the promise, so the request could be aborted from other systems
+    pub fn send_with_handle(mut self) -> (Promise<(), Result<Response, String>>, RequestHandle) {
+        let handle = RequestHandle::new();
+        self.handle = Some(handle.clone());
+        (self.send(), handle)
+    }
     pub fn send(self) -> Promise<(), Result<Response, String>> {
+        if self.retry.is_none() {
+            return self.fetch();
+        }
+        self.send_typed()
+            .map_result(|response| response.map_err(|e| e.to_string()))
+    }
+    /// Same as [`send()`][Request::send], but resolves with the typed [`HttpError`][error::HttpError]
+    pub fn send_typed(mut self) -> Promise<(), Result<Response, error::HttpError>> {
+        let Some((retries, backoff)) = self.retry.take() else {
+            return self
+                .fetch()
+                .map_result(|response| response.map_err(error::HttpError::from));
+        };
+        retry::send(self, retries, backoff)
+    }
+    /// Sends the request once
+    fn fetch(mut self) -> Promise<(), Result<Response, String>> {
+        if let Some(ttl) = self.cache.take() {
+            return cache::fetch(self, ttl);
+        }
+        if self.when_online {
+            self.when_online = false;
+            return net::asyn::online().map(move |_| self).then(asyn!(s => s.value.fetch()));
+        }
+        let timeout = self.timeout;
+        let upload = self.upload_progress.map(|entity| (entity, self.request.body.len()));
+        let handle = self.handle.take();
+        let sent_handle = handle.clone();
+        let discarded_handle = handle.clone();
+        let domain = limit::domain(&self.request.url);
         #[cfg(target_arch = "wasm32")]
-        {
+        let promise = {
             let resolver = WasmResolver::new();
             let discarder = resolver.clone();
             Promise::register(
                 move |world, id| {
-                    resolver.register(world, id);
-                    ehttp::fetch(self.0, move |result| {
-                        resolver.resolve(result);
+                    limit::schedule(world, id, domain, move |world, id| {
+                        if sent_handle.as_ref().is_some_and(|handle| !handle.sent(id)) {
+                            let aborted = Err(handle::ABORTED.into());
+                            promise_resolve::<(), Result<Response, String>>(world, id, (), aborted);
+                            return;
+                        }
+                        if let Some(handle) = sent_handle {
+                            handle.set_resolver(resolver.clone());
+                        }
+                        if let Some((entity, total)) = upload {
+                            upload::start(world, id, entity, total);
+                        }
+                        resolver.register(world, id);
+                        if let Some(seconds) = timeout {
+                            retry::watch(world, id, seconds, resolver.clone());
+                        }
+                        let mut request = self.request;
+                        trace(world, id, &mut request);
+                        ehttp::fetch(request, move |result| {
+                            resolver.resolve(result);
+                        });
                     });
                 },
-                move |_world, _id| {
+                move |world, id| {
+                    if let Some(handle) = discarded_handle {
+                        handle.finish(RequestStatus::Aborted);
+                    }
                     discarder.discard();
+                    limit::cancel(world, id);
                 },
             )
-        }
+        };
         #[cfg(not(target_arch = "wasm32"))]
-        {
-            Promise::register(
-                |world, id| {
-                    let task = AsyncComputeTaskPool::get().spawn(async move { ehttp::fetch_blocking(&self.0) });
+        let promise = Promise::register(
+            move |world, id| {
+                limit::schedule(world, id, domain, move |world, id| {
+                    if sent_handle.is_some_and(|handle| !handle.sent(id)) {
+                        promise_resolve::<(), Result<Response, String>>(world, id, (), Err(handle::ABORTED.into()));
+                        return;
+                    }
+                    let sent = upload.map(|(entity, total)| upload::start(world, id, entity, total));
+                    let mut request = self.request;
+                    trace(world, id, &mut request);
+                    let task = AsyncComputeTaskPool::get().spawn(async move {
+                        match sent {
+                            Some(sent) => upload::fetch_blocking(&request, sent),
+                            None => ehttp::fetch_blocking(&request),
+                        }
+                    });
                     world.resource_mut::<Requests>().insert(id, task);
-                },
-                |world, id| {
-                    world.resource_mut::<Requests>().remove(&id);
-                },
-            )
+                    if let Some(seconds) = timeout {
+                        retry::watch(world, id, seconds);
+                    }
+                });
+            },
+            |world, id| {
+                if let Some(handle) = discarded_handle {
+                    handle.finish(RequestStatus::Aborted);
+                }
+                world.resource_mut::<Requests>().remove(&id);
+                limit::cancel(world, id);
+            },
+        );
+        let promise = match handle {
+            Some(handle) => promise.map_result(move |response| {
+                handle.finish(RequestStatus::Completed);
+                response
+            }),
+            None => promise,
+        };
+        match upload {
+            Some((entity, total)) => upload::track(promise, entity, total),
+            None => promise,
         }
     }
 }
 
+/// Attaches the correlation header if the request `id` is sent from the labeled chain
+fn trace(world: &World, id: PromiseId, request: &mut ehttp::Request) {
+    let Some(TraceHeader(header)) = world.get_resource::<TraceHeader>() else {
+        return;
+    };
+    if let Some(label) = chain_label(world, id) {
+        request.headers.insert(header.to_string(), format!("{label}/{id}"));
+    }
+}
+
 pub struct StatefulRequest<S>(S, Request);
 impl<S: 'static> StatefulRequest<S> {
     pub(crate) fn new(state: S) -> Self {
@@ -134,11 +401,36 @@ impl<S: 'static> StatefulRequest<S> {
         self.1 = self.1.body(body);
         self
     }
+    pub fn when_online(mut self) -> Self {
+        self.1 = self.1.when_online();
+        self
+    }
+    pub fn upload_progress(mut self, entity: Entity) -> Self {
+        self.1 = self.1.upload_progress(entity);
+        self
+    }
+    pub fn timeout(mut self, seconds: f32) -> Self {
+        self.1 = self.1.timeout(seconds);
+        self
+    }
+    pub fn retry(mut self, retries: usize, backoff: f32) -> Self {
+        self.1 = self.1.retry(retries, backoff);
+        self
+    }
+    pub fn send_typed(self) -> Promise<S, Result<ehttp::Response, error::HttpError>> {
+        self.1.send_typed().map(move |_| self.0)
+    }
     pub fn send(self) -> Promise<S, Result<ehttp::Response, String>> {
         self.1.send().map(move |_| self.0)
     }
+    pub fn send_with_handle(self) -> (Promise<S, Result<ehttp::Response, String>>, RequestHandle) {
+        let (promise, handle) = self.1.send_with_handle();
+        (promise.map(move |_| self.0), handle)
+    }
 }
 
+// not generated with `#[asyn_ops]`: `asyn::http` functions return the `Request`
+// builder instead of promises, so the attribute has nothing to generate
 pub struct Http<S>(S);
 
 impl<S: 'static> Http<S> {
@@ -160,8 +452,9 @@ impl<S> HttpOpsExtension<S> for AsynOps<S> {
         Http(self.0)
     }
 }
-#[derive(Resource, Deref, DerefMut, Default)]
+#[derive(Deref, DerefMut, Default)]
 pub struct Requests(HashMap<PromiseId, Task<Result<Response, String>>>);
+impl Resource for Requests {}
 
 pub fn process_requests(mut requests: ResMut<Requests>, mut commands: Commands) {
     requests.retain(|promise, mut task| {
@@ -0,0 +1,163 @@
+//! Limits of the requests in flight. Requests over the [`HttpConfig`] limits wait in
+//! the queue and start in order as the running requests complete:
+//! ```ignore
+//! App::new()
+//!     .add_plugins(
+//!         PromiseHttpPlugin::default().with_config(
+//!             HttpConfig::default()
+//!                 .with_max_concurrent(8)
+//!                 .with_rate_limit("api.example.com", 10, 1.0),
+//!         ),
+//!     )
+//!     .add_plugins((DefaultPlugins, PecsPlugin))
+//!     .run();
+//! ```
+//! Queued requests are discarded or aborted with the [`RequestHandle`] the same way as
+//! the running ones. [`timeout()`][Request::timeout] counts from the moment the request
+//! starts. Streamed responses and WebSockets are not limited.
+use std::collections::VecDeque;
+
+use pecs_core::promise_is_pending;
+
+use super::*;
+
+/// Limits of the requests, inserted by [`PromiseHttpPlugin`], could be changed at runtime
+#[derive(Clone, Debug, Default)]
+pub struct HttpConfig {
+    /// Max requests in flight, unlimited if `None`
+    pub max_concurrent: Option<usize>,
+    /// Rate limits of the requests keyed by the lowercase domain of the url
+    pub rate_limits: HashMap<String, RateLimit>,
+}
+impl Resource for HttpConfig {}
+
+impl HttpConfig {
+    /// Keep at most `requests` in flight
+    pub fn with_max_concurrent(mut self, requests: usize) -> Self {
+        self.max_concurrent = Some(requests);
+        self
+    }
+    /// Start at most `requests` to the `domain` in any `seconds` of real time
+    pub fn with_rate_limit<D: ToString>(mut self, domain: D, requests: usize, seconds: f32) -> Self {
+        let domain = domain.to_string().to_ascii_lowercase();
+        self.rate_limits.insert(domain, RateLimit { requests, seconds });
+        self
+    }
+}
+
+/// At most `requests` started in any `seconds` of real time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub requests: usize,
+    pub seconds: f32,
+}
+
+type StartRequest = Box<dyn FnOnce(&mut World, PromiseId) + Send + Sync>;
+
+/// Requests waiting for their turn, the requests in flight and the start times
+/// of the rate limited requests by domain
+#[derive(Default)]
+pub(crate) struct RequestQueue {
+    queued: VecDeque<(PromiseId, String, StartRequest)>,
+    in_flight: Vec<PromiseId>,
+    started: HashMap<String, VecDeque<f32>>,
+}
+impl Resource for RequestQueue {}
+
+impl RequestQueue {
+    /// Returns `true` if the request to the `domain` could start `now`
+    fn allows(&mut self, config: &HttpConfig, domain: &str, now: f32) -> bool {
+        if config.max_concurrent.is_some_and(|max| self.in_flight.len() >= max) {
+            return false;
+        }
+        let Some(limit) = config.rate_limits.get(domain) else {
+            return true;
+        };
+        let started = self.started.entry(domain.to_string()).or_default();
+        while started.front().is_some_and(|start| now - start >= limit.seconds) {
+            started.pop_front();
+        }
+        started.len() < limit.requests
+    }
+
+    fn start(&mut self, config: &HttpConfig, id: PromiseId, domain: &str, now: f32) {
+        self.in_flight.push(id);
+        if config.rate_limits.contains_key(domain) {
+            self.started.entry(domain.to_string()).or_default().push_back(now);
+        }
+    }
+}
+
+/// The host of the `url` without the credentials and the port, lowercased
+pub(crate) fn domain(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next(),
+        None => host.split(':').next(),
+    };
+    host.unwrap_or_default().to_ascii_lowercase()
+}
+
+/// Runs `start` for the request `id` to the `domain` if the limits allow,
+/// queues it otherwise
+pub(crate) fn schedule<F>(world: &mut World, id: PromiseId, domain: String, start: F)
+where
+    F: 'static + FnOnce(&mut World, PromiseId) + Send + Sync,
+{
+    let now = retry::real_time(world);
+    let default = HttpConfig::default();
+    let started = world.resource_scope(|world, mut queue: Mut<RequestQueue>| {
+        let config = world.get_resource::<HttpConfig>().unwrap_or(&default);
+        // the earlier requests to the domain go first
+        let waiting = queue.queued.iter().any(|(_, queued, _)| queued == &domain);
+        if waiting || !queue.allows(config, &domain, now) {
+            queue.queued.push_back((id, domain, Box::new(start)));
+            return None;
+        }
+        queue.start(config, id, &domain, now);
+        Some(start)
+    });
+    if let Some(start) = started {
+        start(world, id);
+    }
+}
+
+/// Removes the discarded request `id` from the queue and frees its slot
+pub(crate) fn cancel(world: &mut World, id: PromiseId) {
+    let mut queue = world.resource_mut::<RequestQueue>();
+    queue.queued.retain(|(queued, _, _)| queued != &id);
+    queue.in_flight.retain(|in_flight| in_flight != &id);
+}
+
+/// Frees the slots of the completed requests and starts the queued ones
+pub(crate) fn process_queue(world: &mut World) {
+    let now = retry::real_time(world);
+    let default = HttpConfig::default();
+    let ready = world.resource_scope(|world, mut queue: Mut<RequestQueue>| {
+        queue
+            .in_flight
+            .retain(|id| promise_is_pending::<(), Result<Response, String>>(world, *id));
+        if queue.queued.is_empty() {
+            return vec![];
+        }
+        let config = world.get_resource::<HttpConfig>().unwrap_or(&default);
+        let mut ready = vec![];
+        let mut index = 0;
+        while index < queue.queued.len() {
+            let domain = queue.queued[index].1.clone();
+            if queue.allows(config, &domain, now) {
+                let (id, _, start) = queue.queued.remove(index).unwrap();
+                queue.start(config, id, &domain, now);
+                ready.push((id, start));
+            } else {
+                index += 1;
+            }
+        }
+        ready
+    });
+    for (id, start) in ready {
+        start(world, id);
+    }
+}
@@ -0,0 +1,158 @@
+//! Network reachability: wait for the connection to come back with
+//! [`asyn::net::online()`][asyn::online] or to drop with [`asyn::net::offline()`][asyn::offline].
+//! On wasm the status follows `navigator.onLine`. Native platforms probe the network only
+//! when the probe url is configured with [`PromiseHttpPlugin::with_connectivity_probe`]:
+//! [`Connectivity::probe_url`] is requested every [`Connectivity::probe_interval`] seconds
+//! while there are promises waiting for the status to change. Without the probe url the
+//! network is always considered reachable.
+//!
+//! Requests sent with [`when_online()`][Request::when_online] and
+//! [`retry()`][Request::retry] mark the network as unreachable when an attempt fails with
+//! [`HttpError::Request`][error::HttpError::Request], so the next attempt waits for the
+//! probe to report the network is back instead of failing again.
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+#[asyn_ops(net)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that resolves when the network becomes reachable,
+    /// or immediately if it is reachable already.
+    /// ```ignore
+    /// Promise::start(asyn!(_ => {
+    ///     asyn::net::online()
+    /// }))
+    /// .then(asyn!(_ => {
+    ///     asyn::http::get("https://bevyengine.org").send()
+    /// }))
+    /// ```
+    pub fn online() -> Promise<(), ()> {
+        wait_for(true)
+    }
+
+    /// Creates promise that resolves when the network becomes unreachable,
+    /// or immediately if it is unreachable already.
+    pub fn offline() -> Promise<(), ()> {
+        wait_for(false)
+    }
+}
+
+fn wait_for(online: bool) -> Promise<(), ()> {
+    Promise::register(
+        move |world, id| {
+            if world.resource::<Connectivity>().online == online {
+                pecs_core::promise_resolve::<(), ()>(world, id, (), ());
+            } else {
+                world.resource_mut::<Connectivity>().waiters.push((id, online));
+            }
+        },
+        |world, id| {
+            world
+                .resource_mut::<Connectivity>()
+                .waiters
+                .retain(|(waiter, _)| waiter != &id);
+        },
+    )
+}
+
+/// Current network reachability and promises waiting for it to change
+pub struct Connectivity {
+    online: bool,
+    /// Url requested with `HEAD` to check the network on native platforms, the network
+    /// is not probed if `None`. Any response, including errors like `404`, means the
+    /// network is reachable.
+    pub probe_url: Option<String>,
+    /// Seconds between probes on native platforms
+    pub probe_interval: f32,
+    elapsed: f32,
+    #[cfg(not(target_arch = "wasm32"))]
+    probe: Option<Task<bool>>,
+    waiters: Vec<(PromiseId, bool)>,
+}
+impl Resource for Connectivity {}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Connectivity {
+            online: true,
+            probe_url: None,
+            probe_interval: 5.,
+            elapsed: 5.,
+            #[cfg(not(target_arch = "wasm32"))]
+            probe: None,
+            waiters: vec![],
+        }
+    }
+}
+
+impl Connectivity {
+    /// Last known network reachability. Optimistically `true` until
+    /// the first probe completes.
+    pub fn is_online(&self) -> bool {
+        self.online
+    }
+    /// Marks the network as unreachable until the next probe, does nothing if
+    /// the network is not probed
+    pub(crate) fn suspect(&mut self, commands: &mut Commands) {
+        if self.probe_url.is_some() {
+            self.set_online(false, commands);
+        }
+    }
+    fn set_online(&mut self, online: bool, commands: &mut Commands) {
+        if self.online != online {
+            info!("Network is {}", if online { "online" } else { "offline" });
+        }
+        self.online = online;
+        self.waiters.retain(|(id, wait_for)| {
+            if *wait_for == online {
+                commands.add(PromiseCommand::resolve(*id, ()));
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn process_connectivity(time: Res<Time>, mut connectivity: ResMut<Connectivity>, mut commands: Commands) {
+    if let Some(online) = connectivity
+        .probe
+        .as_mut()
+        .and_then(|task| future::block_on(future::poll_once(task)))
+    {
+        connectivity.probe = None;
+        connectivity.set_online(online, &mut commands);
+    }
+    // the network is probed only while somebody waits for it to change,
+    // the first waiter starts the probe immediately
+    let url = match &connectivity.probe_url {
+        Some(url) if !connectivity.waiters.is_empty() => url.clone(),
+        _ => {
+            connectivity.elapsed = connectivity.probe_interval;
+            return;
+        }
+    };
+    connectivity.elapsed += time.delta_seconds();
+    if connectivity.probe.is_none() && connectivity.elapsed >= connectivity.probe_interval {
+        connectivity.elapsed = 0.;
+        let request = ehttp::Request {
+            method: "HEAD".to_string(),
+            ..ehttp::Request::get(url)
+        };
+        connectivity.probe =
+            Some(AsyncComputeTaskPool::get().spawn(async move { ehttp::fetch_blocking(&request).is_ok() }));
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn process_connectivity(mut connectivity: ResMut<Connectivity>, mut commands: Commands) {
+    let online = web_sys::window()
+        .map(|window| window.navigator().on_line())
+        .unwrap_or(true);
+    if online != connectivity.online {
+        connectivity.set_online(online, &mut commands);
+    }
+}
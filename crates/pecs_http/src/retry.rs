@@ -0,0 +1,113 @@
+//! Timeouts and retries of the requests, see [`Request::timeout`] and [`Request::retry`]
+use error::{HttpError, TIMED_OUT};
+use pecs_core::{promise_resolve, timer, Repeat};
+
+use super::*;
+
+/// The deadline in seconds of real time and the request promise
+#[cfg(not(target_arch = "wasm32"))]
+type Deadline = (f32, PromiseId);
+/// The deadline in seconds of real time, the request promise and its resolver
+#[cfg(target_arch = "wasm32")]
+type Deadline = (f32, PromiseId, WasmResolver);
+
+/// Requests sent with the timeout
+#[derive(Default)]
+pub(crate) struct RequestTimeouts(Vec<Deadline>);
+impl Resource for RequestTimeouts {}
+
+pub(crate) fn real_time(world: &World) -> f32 {
+    world
+        .get_resource::<Time<Real>>()
+        .map(|time| time.elapsed_seconds())
+        .unwrap_or_default()
+}
+
+/// Resolves the request `id` with [`Err(TIMED_OUT)`][TIMED_OUT] if it is
+/// not completed in `seconds`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn watch(world: &mut World, id: PromiseId, seconds: f32) {
+    let deadline = real_time(world) + seconds;
+    world.resource_mut::<RequestTimeouts>().0.push((deadline, id));
+}
+
+/// Resolves the request `id` with [`Err(TIMED_OUT)`][TIMED_OUT] if it is
+/// not completed in `seconds`
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn watch(world: &mut World, id: PromiseId, seconds: f32, resolver: WasmResolver) {
+    let deadline = real_time(world) + seconds;
+    world.resource_mut::<RequestTimeouts>().0.push((deadline, id, resolver));
+}
+
+pub(crate) fn process_timeouts(world: &mut World) {
+    let now = real_time(world);
+    let mut timeouts = world.resource_mut::<RequestTimeouts>();
+    if timeouts.0.iter().all(|timeout| timeout.0 > now) {
+        return;
+    }
+    let (expired, pending): (Vec<_>, Vec<_>) = timeouts.0.drain(..).partition(|timeout| timeout.0 <= now);
+    timeouts.0 = pending;
+    // completed and discarded requests are not tracked anymore, the same way as aborted ones
+    #[cfg(not(target_arch = "wasm32"))]
+    for (_, id) in expired {
+        if world.resource_mut::<Requests>().remove(&id).is_some() {
+            promise_resolve::<(), Result<Response, String>>(world, id, (), Err(TIMED_OUT.to_string()));
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    for (_, id, resolver) in expired {
+        if resolver.is_pending() {
+            resolver.discard();
+            promise_resolve::<(), Result<Response, String>>(world, id, (), Err(TIMED_OUT.to_string()));
+        }
+    }
+}
+
+/// State of the retry loop
+struct Attempts {
+    request: Request,
+    retries: usize,
+    backoff: f32,
+    attempt: usize,
+}
+
+/// Sends the `request` up to `retries + 1` times until it succeeds
+pub(crate) fn send(request: Request, retries: usize, backoff: f32) -> Promise<(), Result<Response, HttpError>> {
+    let attempts = Attempts {
+        request,
+        retries,
+        backoff,
+        attempt: 0,
+    };
+    Promise::repeat(
+        attempts,
+        asyn!(s => {
+            let response = s.value.request.clone().fetch();
+            response.map(move |_| s.value).then(asyn!(
+                s, response, mut connectivity: ResMut<net::Connectivity>, mut commands: Commands
+            => {
+                let error = match response.map_err(HttpError::from).and_then(HttpError::check) {
+                    Err(error) if error.is_retryable() => error,
+                    result => return PromiseResult::Resolve(s.value, Repeat::Break(result)),
+                };
+                // the network is likely down, the next attempt waits for it to come back
+                if s.value.request.when_online && matches!(error, HttpError::Request(_)) {
+                    connectivity.suspect(&mut commands);
+                }
+                let Attempts { retries, backoff, attempt, .. } = s.value;
+                if attempt >= retries {
+                    let exhausted = HttpError::Exhausted {
+                        attempts: attempt + 1,
+                        last: Box::new(error),
+                    };
+                    return PromiseResult::Resolve(s.value, Repeat::Break(Err(exhausted)));
+                }
+                let delay = backoff * 2f32.powi(attempt as i32);
+                let mut state = s.value;
+                state.attempt += 1;
+                PromiseResult::Await(timer::timeout(delay).map(move |_| state).with_result(Repeat::Continue))
+            }))
+        }),
+    )
+    .map(|_| ())
+}
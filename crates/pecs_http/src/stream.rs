@@ -0,0 +1,275 @@
+//! Streaming downloads. [`Request::stream`] passes the response body to the
+//! `on_chunk` callback chunk by chunk instead of collecting it into
+//! [`Response::bytes`], and resolves with the [`StreamInfo`] when the body is read:
+//! ```ignore
+//! fn download_pack(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::http::get("https://example.com/assets.pack")
+//!             .stream(asyn!(_, chunk, mut pack: ResMut<PackWriter> => {
+//!                 pack.write(&chunk);
+//!             }))
+//!             .then(asyn!(_, result => match result {
+//!                 Ok(info) => info!("Downloaded {} bytes", info.bytes),
+//!                 Err(e) => error!("Can't download the pack: {e}"),
+//!             })),
+//!     );
+//! }
+//! ```
+//! Native requests read the body in chunks of up to 64 KiB, reading pauses while
+//! the callback falls behind. On wasm the body is received as a whole and passed
+//! to the callback as a single chunk.
+use std::collections::BTreeMap;
+
+use error::HttpError;
+use pecs_core::{promise_register, Asyn, PromiseState, Repeat};
+
+use super::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crossbeam_channel::{bounded, Receiver, Sender, TryRecvError};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Read;
+
+#[cfg(not(target_arch = "wasm32"))]
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Chunks read ahead of the callback, also the limit of chunks delivered per frame
+#[cfg(not(target_arch = "wasm32"))]
+const BUFFERED_CHUNKS: usize = 16;
+
+/// Completion metadata of the streamed response
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// The final url after the redirects
+    pub url: String,
+    pub status: u16,
+    pub status_text: String,
+    pub headers: BTreeMap<String, String>,
+    /// Total size of the chunks passed to the callback
+    pub bytes: usize,
+}
+
+impl Request {
+    /// Send the request and run `on_chunk` with every chunk of the response body, see
+    /// [`stream`][self]. Resolves with the [`StreamInfo`] when the whole body is passed
+    /// to the callback. Responses with non-2xx status resolve with [`HttpError::Status`]
+    /// without reading the body. Promises returned by `on_chunk` are not awaited, they
+    /// run detached.
+    ///
+    /// Streamed chunks are not decoded, set the `Accept-Encoding` header explicitly to
+    /// receive the compressed body. [`timeout()`][Request::timeout], [`retry()`][Request::retry],
+    /// the [`RequestHandle`] and the upload progress only apply to the wasm requests.
+    pub fn stream(self, on_chunk: Asyn![(), Vec<u8> => (), ()]) -> Promise<(), Result<StreamInfo, HttpError>> {
+        stream(self, on_chunk)
+    }
+}
+
+impl<S: 'static> StatefulRequest<S> {
+    /// Stateful version of [`Request::stream()`]
+    pub fn stream(self, on_chunk: Asyn![(), Vec<u8> => (), ()]) -> Promise<S, Result<StreamInfo, HttpError>> {
+        self.1.stream(on_chunk).map(move |_| self.0)
+    }
+}
+
+/// Runs the `on_chunk` callback with the `chunk`, detaching the returned promise
+fn run_chunk(world: &mut World, on_chunk: &Asyn![(), Vec<u8> => (), ()], chunk: Vec<u8>) {
+    let result: PromiseResult<(), ()> = on_chunk.run((PromiseState::new(()), chunk), world).into();
+    if let PromiseResult::Await(promise) = result {
+        promise_register::<(), ()>(world, promise);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn stream(request: Request, on_chunk: Asyn![(), Vec<u8> => (), ()]) -> Promise<(), Result<StreamInfo, HttpError>> {
+    request
+        .send_typed()
+        .map(move |_| on_chunk)
+        .then(asyn!(s, response, world: &mut World => {
+            let response = match response.and_then(HttpError::check) {
+                Ok(response) => response,
+                Err(error) => return PromiseResult::Resolve((), Err(error)),
+            };
+            let info = StreamInfo {
+                url: response.url,
+                status: response.status,
+                status_text: response.status_text,
+                headers: response.headers,
+                bytes: response.bytes.len(),
+            };
+            if !response.bytes.is_empty() {
+                run_chunk(world, &s.value, response.bytes);
+            }
+            PromiseResult::Resolve((), Ok(info))
+        }))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn stream(mut request: Request, on_chunk: Asyn![(), Vec<u8> => (), ()]) -> Promise<(), Result<StreamInfo, HttpError>> {
+    if request.when_online {
+        request.when_online = false;
+        return net::asyn::online().map(move |_| (request, on_chunk)).then(asyn!(s => {
+            let (request, on_chunk) = s.value;
+            stream(request, on_chunk)
+        }));
+    }
+    if encoding::accept_encoding() == request.request.headers.get("Accept-Encoding").cloned() {
+        request.request.headers.remove("Accept-Encoding");
+    }
+    open(request.request).map(move |_| on_chunk).then(asyn!(s, key => {
+        Promise::repeat(
+            (key, s.value),
+            asyn!(s => {
+                let key = s.value.0;
+                next(key).map(move |_| s.value).then(asyn!(s, message, world: &mut World => {
+                    match message {
+                        StreamMessage::Chunk(chunk) => {
+                            run_chunk(world, &s.value.1, chunk);
+                            s.resolve(Repeat::<Result<StreamInfo, HttpError>>::Continue)
+                        }
+                        StreamMessage::Done(result) => s.resolve(Repeat::Break(result)),
+                    }
+                }))
+            }),
+        )
+        .map(|_| ())
+    }))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum StreamMessage {
+    Chunk(Vec<u8>),
+    Done(Result<StreamInfo, HttpError>),
+}
+
+/// The body reader of the native stream and the promise waiting for its next message
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct Stream {
+    receiver: Receiver<StreamMessage>,
+    waiter: Option<PromiseId>,
+    _task: Task<()>,
+}
+
+/// Native streams in progress, keyed by the promise that opened the stream
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deref, DerefMut, Default)]
+pub(crate) struct Streams(HashMap<PromiseId, Stream>);
+#[cfg(not(target_arch = "wasm32"))]
+impl Resource for Streams {}
+
+/// Sends the `request` and resolves with the key of the opened stream
+#[cfg(not(target_arch = "wasm32"))]
+fn open(mut request: ehttp::Request) -> Promise<(), PromiseId> {
+    Promise::register(
+        move |world, id| {
+            trace(world, id, &mut request);
+            let (sender, receiver) = bounded(BUFFERED_CHUNKS);
+            let task = AsyncComputeTaskPool::get().spawn(async move {
+                let result = read_blocking(&request, &sender);
+                sender.send(StreamMessage::Done(result)).ok();
+            });
+            let stream = Stream {
+                receiver,
+                waiter: None,
+                _task: task,
+            };
+            world.resource_mut::<Streams>().insert(id, stream);
+            promise_resolve::<(), PromiseId>(world, id, (), id);
+        },
+        |world, id| {
+            world.resource_mut::<Streams>().remove(&id);
+        },
+    )
+}
+
+/// Creates promise that resolves with the next message of the stream `key`.
+/// Discarding it closes the stream.
+#[cfg(not(target_arch = "wasm32"))]
+fn next(key: PromiseId) -> Promise<(), StreamMessage> {
+    Promise::register(
+        move |world, id| {
+            if let Some(stream) = world.resource_mut::<Streams>().get_mut(&key) {
+                stream.waiter = Some(id);
+                return;
+            }
+            promise_resolve::<(), StreamMessage>(world, id, (), StreamMessage::Done(Err(HttpError::Aborted)));
+        },
+        move |world, _| {
+            world.resource_mut::<Streams>().remove(&key);
+        },
+    )
+}
+
+/// Sends the request and passes the body to the `sender` in chunks
+#[cfg(not(target_arch = "wasm32"))]
+fn read_blocking(request: &ehttp::Request, sender: &Sender<StreamMessage>) -> Result<StreamInfo, HttpError> {
+    let mut req = ureq::request(&request.method, &request.url);
+    for (key, value) in request.headers.clone() {
+        req = req.set(&key, &value);
+    }
+    let resp = match req.send_bytes(&request.body) {
+        Ok(resp) => resp,
+        Err(ureq::Error::Status(status, resp)) => {
+            return Err(HttpError::Status {
+                status,
+                status_text: resp.status_text().to_owned(),
+            })
+        }
+        Err(ureq::Error::Transport(err)) => return Err(HttpError::Request(err.to_string())),
+    };
+    let mut info = StreamInfo {
+        url: resp.get_url().to_owned(),
+        status: resp.status(),
+        status_text: resp.status_text().to_owned(),
+        headers: Default::default(),
+        bytes: 0,
+    };
+    for key in resp.headers_names() {
+        if let Some(value) = resp.header(&key) {
+            info.headers.insert(key.to_string(), value.to_string());
+        }
+    }
+    let mut reader = resp.into_reader();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| HttpError::Request(format!("Failed to read response body: {e}")))?;
+        if read == 0 {
+            return Ok(info);
+        }
+        info.bytes += read;
+        // the stream is closed, stop reading
+        if sender.send(StreamMessage::Chunk(buffer[..read].to_vec())).is_err() {
+            return Err(HttpError::Aborted);
+        }
+    }
+}
+
+/// Delivers the received messages to the waiting streams
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn process_streams(world: &mut World) {
+    let keys: Vec<_> = world.resource::<Streams>().keys().copied().collect();
+    for key in keys {
+        // the waiter is registered again by the stream loop when the message is handled
+        for _ in 0..BUFFERED_CHUNKS {
+            let mut streams = world.resource_mut::<Streams>();
+            let Some(stream) = streams.get_mut(&key) else {
+                break;
+            };
+            let Some(waiter) = stream.waiter else {
+                break;
+            };
+            let message = match stream.receiver.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    StreamMessage::Done(Err(HttpError::Request("Response stream closed".to_string())))
+                }
+            };
+            stream.waiter = None;
+            if matches!(message, StreamMessage::Done(_)) {
+                streams.remove(&key);
+            }
+            promise_resolve::<(), StreamMessage>(world, waiter, (), message);
+        }
+    }
+}
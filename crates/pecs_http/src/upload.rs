@@ -0,0 +1,153 @@
+//! Upload progress reporting. Requests created with
+//! [`upload_progress(entity)`][crate::Request::upload_progress] keep the
+//! [`UploadProgress`] component of the `entity` updated while the body is sent.
+//! Native requests report the bytes written to the connection in chunks. On
+//! wasm the browser doesn't expose the progress to `fetch`, the component is
+//! updated when the request starts and when it completes.
+use super::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{Cursor, Read};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// Bytes of the request body sent to the server
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UploadProgress {
+    pub sent: usize,
+    pub total: usize,
+}
+pecs_compat::impl_component!(UploadProgress);
+
+impl UploadProgress {
+    /// Sent part of the body in `0.0..=1.0` range
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.
+        } else {
+            self.sent as f32 / self.total as f32
+        }
+    }
+}
+
+fn set_progress(world: &mut World, entity: Entity, sent: usize, total: usize) {
+    if let Some(mut entity) = world.get_entity_mut(entity) {
+        entity.insert(UploadProgress { sent, total });
+    }
+}
+
+/// Marks the upload of the request `id` as started
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn start(world: &mut World, id: PromiseId, entity: Entity, total: usize) -> Arc<AtomicUsize> {
+    set_progress(world, entity, 0, total);
+    let sent = Arc::new(AtomicUsize::new(0));
+    world.resource_mut::<Uploads>().insert(id, (entity, sent.clone()));
+    sent
+}
+
+/// Marks the upload of the request `id` as started
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn start(world: &mut World, _id: PromiseId, entity: Entity, total: usize) {
+    set_progress(world, entity, 0, total);
+}
+
+/// Reports the whole body as sent when the `request` receives the response
+pub(crate) fn track(
+    request: Promise<(), Result<Response, String>>,
+    entity: Entity,
+    total: usize,
+) -> Promise<(), Result<Response, String>> {
+    request
+        .map(move |_| (entity, total))
+        .then(asyn!(s, response, mut commands: Commands => {
+            let (entity, total) = s.value;
+            if response.is_ok() {
+                commands.add(move |world: &mut World| set_progress(world, entity, total, total));
+            }
+            PromiseResult::Resolve((), response)
+        }))
+}
+
+/// Native uploads in progress: the target entity and the counter updated by the request task
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deref, DerefMut, Default)]
+pub struct Uploads(HashMap<PromiseId, (Entity, Arc<AtomicUsize>)>);
+#[cfg(not(target_arch = "wasm32"))]
+impl Resource for Uploads {}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn process_uploads(
+    mut uploads: ResMut<Uploads>,
+    requests: Res<Requests>,
+    mut progress: Query<&mut UploadProgress>,
+) {
+    uploads.retain(|promise, (entity, sent)| {
+        if !requests.contains_key(promise) {
+            return false;
+        }
+        let sent = sent.load(Ordering::Relaxed);
+        if let Ok(mut progress) = progress.get_mut(*entity) {
+            if progress.sent != sent {
+                progress.sent = sent;
+            }
+        }
+        true
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct CountingReader {
+    body: Cursor<Vec<u8>>,
+    sent: Arc<AtomicUsize>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(CHUNK_SIZE);
+        let read = self.body.read(&mut buf[..len])?;
+        self.sent.fetch_add(read, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+/// Same as [`ehttp::fetch_blocking`], but sends the body in chunks counting sent bytes
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn fetch_blocking(request: &ehttp::Request, sent: Arc<AtomicUsize>) -> Result<Response, String> {
+    let mut req = ureq::request(&request.method, &request.url);
+    for (key, value) in request.headers.clone() {
+        req = req.set(&key, &value);
+    }
+    let req = req.set("Content-Length", &request.body.len().to_string());
+    let body = CountingReader {
+        body: Cursor::new(request.body.clone()),
+        sent,
+    };
+    let (ok, resp) = match req.send(body) {
+        Ok(resp) => (true, resp),
+        Err(ureq::Error::Status(_, resp)) => (false, resp),
+        Err(ureq::Error::Transport(err)) => return Err(err.to_string()),
+    };
+    let mut response = Response {
+        url: resp.get_url().to_owned(),
+        ok,
+        status: resp.status(),
+        status_text: resp.status_text().to_owned(),
+        headers: Default::default(),
+        bytes: vec![],
+    };
+    for key in resp.headers_names() {
+        if let Some(value) = resp.header(&key) {
+            response.headers.insert(key.to_string(), value.to_string());
+        }
+    }
+    resp.into_reader()
+        .read_to_end(&mut response.bytes)
+        .map_err(|e| format!("Failed to read response body: {e}"))?;
+    Ok(response)
+}
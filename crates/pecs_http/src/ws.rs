@@ -0,0 +1,215 @@
+//! WebSocket connections via [`ewebsock`](https://docs.rs/ewebsock/), requires the
+//! `websocket` feature. [`asyn::ws::connect()`][asyn::connect] resolves with the
+//! [`WebSocket`] handle when the connection is opened, its [`recv()`][WebSocket::recv]
+//! and [`send()`][WebSocket::send] return promises, so the protocol could be written
+//! as the promise chain:
+//! ```ignore
+//! #[derive(Resource)]
+//! struct Lobby(WebSocket);
+//!
+//! fn join_lobby(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::ws::connect("wss://example.com/lobby")
+//!             .then_ok(asyn!(_, socket, mut commands: Commands => {
+//!                 commands.insert_resource(Lobby(socket));
+//!                 socket.send(WsMessage::Text("join".into())).with_result(socket)
+//!             }))
+//!             .then_ok(asyn!(_, socket => socket.recv()))
+//!             .then(asyn!(_, reply => info!("Lobby replied with {reply:?}"))),
+//!     );
+//! }
+//! ```
+//! Messages received while nobody waits for them are queued until the next
+//! [`recv()`][WebSocket::recv]. The connection stays open until it is closed with
+//! [`close()`][WebSocket::close] or by the server.
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+
+use crossbeam_channel::{unbounded, Receiver};
+pub use ewebsock::WsMessage;
+use ewebsock::{WsEvent, WsSender};
+use pecs_core::promise_resolve;
+use pecs_macro::asyn_ops;
+
+use super::*;
+
+/// The message the pending and new operations of the closed connection fail with
+pub const CLOSED: &str = "WebSocket closed";
+
+#[asyn_ops(ws)]
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that opens the WebSocket connection to the `url` and resolves
+    /// with its [`WebSocket`] handle, or with the error if the connection fails.
+    pub fn connect<U: ToString>(url: U) -> Promise<(), Result<WebSocket, String>> {
+        let url = url.to_string();
+        Promise::register(
+            move |world, id| {
+                let (events_sender, events) = unbounded();
+                let on_event: ewebsock::EventHandler = Box::new(move |event| match events_sender.send(event) {
+                    Ok(()) => ControlFlow::Continue(()),
+                    Err(_) => ControlFlow::Break(()),
+                });
+                match ewebsock::ws_connect(url, ewebsock::Options::default(), on_event) {
+                    Ok(sender) => {
+                        let socket = Socket {
+                            sender,
+                            events,
+                            connecting: Some(id),
+                            inbox: VecDeque::new(),
+                            waiters: VecDeque::new(),
+                            closed: None,
+                        };
+                        world.resource_mut::<Sockets>().insert(id, socket);
+                    }
+                    Err(error) => promise_resolve::<(), Result<WebSocket, String>>(world, id, (), Err(error)),
+                }
+            },
+            |world, id| {
+                if let Some(mut socket) = world.resource_mut::<Sockets>().remove(&id) {
+                    socket.sender.close();
+                }
+            },
+        )
+    }
+}
+
+/// Handle of the connection opened with [`asyn::ws::connect()`][asyn::connect]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WebSocket(PromiseId);
+
+impl WebSocket {
+    /// Returns `true` if the connection is open and not closed yet
+    pub fn is_open(&self, world: &World) -> bool {
+        world
+            .get_resource::<Sockets>()
+            .and_then(|sockets| sockets.get(&self.0))
+            .is_some_and(|socket| socket.connecting.is_none() && socket.closed.is_none())
+    }
+
+    /// Creates promise that sends the `message` and resolves with `Ok(())`, or with
+    /// [`Err(CLOSED)`][CLOSED] if the connection is closed. Messages are sent in the
+    /// order the promises are started.
+    pub fn send(&self, message: WsMessage) -> Promise<(), Result<(), String>> {
+        let key = self.0;
+        Promise::register(
+            move |world, id| {
+                let result = match world.resource_mut::<Sockets>().get_mut(&key) {
+                    Some(socket) if socket.closed.is_none() => {
+                        socket.sender.send(message);
+                        Ok(())
+                    }
+                    _ => Err(CLOSED.to_string()),
+                };
+                promise_resolve::<(), Result<(), String>>(world, id, (), result);
+            },
+            |_, _| {},
+        )
+    }
+
+    /// Creates promise that resolves with the next received message, or with the
+    /// error when the connection is closed and there are no queued messages left.
+    pub fn recv(&self) -> Promise<(), Result<WsMessage, String>> {
+        let key = self.0;
+        Promise::register(
+            move |world, id| {
+                let result = match world.resource_mut::<Sockets>().get_mut(&key) {
+                    Some(socket) => match (socket.inbox.pop_front(), &socket.closed) {
+                        (Some(message), _) => Ok(message),
+                        (None, Some(reason)) => Err(reason.clone()),
+                        (None, None) => {
+                            socket.waiters.push_back(id);
+                            return;
+                        }
+                    },
+                    None => Err(CLOSED.to_string()),
+                };
+                promise_resolve::<(), Result<WsMessage, String>>(world, id, (), result);
+            },
+            move |world, id| {
+                if let Some(socket) = world.resource_mut::<Sockets>().get_mut(&key) {
+                    socket.waiters.retain(|waiter| waiter != &id);
+                }
+            },
+        )
+    }
+
+    /// Creates promise that closes the connection and resolves when it is closed.
+    /// Pending [`recv()`][WebSocket::recv] promises resolve with [`Err(CLOSED)`][CLOSED].
+    pub fn close(&self) -> Promise<(), ()> {
+        let key = self.0;
+        Promise::register(
+            move |world, id| {
+                let socket = world.resource_mut::<Sockets>().remove(&key);
+                if let Some(mut socket) = socket {
+                    socket.sender.close();
+                    for waiter in socket.waiters {
+                        promise_resolve::<(), Result<WsMessage, String>>(world, waiter, (), Err(CLOSED.to_string()));
+                    }
+                }
+                promise_resolve::<(), ()>(world, id, (), ());
+            },
+            |_, _| {},
+        )
+    }
+}
+
+/// The open connection, its received messages and promises waiting for them
+struct Socket {
+    sender: WsSender,
+    events: Receiver<WsEvent>,
+    /// The connect promise waiting for the connection to open
+    connecting: Option<PromiseId>,
+    inbox: VecDeque<WsMessage>,
+    waiters: VecDeque<PromiseId>,
+    /// The reason the connection is closed with
+    closed: Option<String>,
+}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for Socket {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for Socket {}
+
+/// Connections opened with [`asyn::ws::connect()`][asyn::connect], keyed by the connect promise
+#[derive(Deref, DerefMut, Default)]
+pub(crate) struct Sockets(HashMap<PromiseId, Socket>);
+impl Resource for Sockets {}
+
+pub(crate) fn process_sockets(mut sockets: ResMut<Sockets>, mut commands: Commands) {
+    for (key, socket) in sockets.iter_mut() {
+        while let Ok(event) = socket.events.try_recv() {
+            match event {
+                WsEvent::Opened => {
+                    if let Some(id) = socket.connecting.take() {
+                        commands.add(PromiseCommand::resolve(id, Ok::<_, String>(WebSocket(*key))));
+                    }
+                }
+                // keep-alive is handled by the connection itself
+                WsEvent::Message(WsMessage::Ping(_) | WsMessage::Pong(_)) => {}
+                WsEvent::Message(message) => socket.inbox.push_back(message),
+                WsEvent::Error(error) => {
+                    socket.closed.get_or_insert(error);
+                }
+                WsEvent::Closed => {
+                    socket.closed.get_or_insert_with(|| CLOSED.to_string());
+                }
+            }
+        }
+        while !socket.inbox.is_empty() && !socket.waiters.is_empty() {
+            let (waiter, message) = (socket.waiters.pop_front().unwrap(), socket.inbox.pop_front().unwrap());
+            commands.add(PromiseCommand::resolve(waiter, Ok::<_, String>(message)));
+        }
+        let Some(reason) = &socket.closed else {
+            continue;
+        };
+        if let Some(id) = socket.connecting.take() {
+            commands.add(PromiseCommand::resolve(id, Err::<WebSocket, _>(reason.clone())));
+        }
+        for waiter in socket.waiters.drain(..) {
+            commands.add(PromiseCommand::resolve(waiter, Err::<WsMessage, _>(reason.clone())));
+        }
+    }
+    // closed connections are kept until the queued messages are received
+    sockets.retain(|_, socket| socket.closed.is_none() || !socket.inbox.is_empty());
+}
@@ -33,6 +33,148 @@ pub fn impl_all_promises(input: proc_macro::TokenStream) -> proc_macro::TokenStr
     proc_macro::TokenStream::from(impl_all_promises_internal(num))
 }
 
+#[proc_macro]
+pub fn impl_any_ok_promises(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let num = syn::parse_macro_input!(input as LitInt);
+    let num = match num.base10_parse::<u8>() {
+        Ok(n) => n,
+        Err(e) => return proc_macro::TokenStream::from(e.to_compile_error()),
+    };
+    proc_macro::TokenStream::from(impl_any_ok_promises_internal(num))
+}
+
+#[proc_macro]
+pub fn impl_all_settled_promises(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let num = syn::parse_macro_input!(input as LitInt);
+    let num = match num.base10_parse::<u8>() {
+        Ok(n) => n,
+        Err(e) => return proc_macro::TokenStream::from(e.to_compile_error()),
+    };
+    proc_macro::TokenStream::from(impl_all_settled_promises_internal(num))
+}
+
+#[proc_macro_attribute]
+/// Generates the stateful counterpart of the stateless `asyn` module:
+/// every `pub fn` returning `Promise<(), R>` becomes a method of
+/// `StatefulAsyn{Name}<S>` returning `Promise<S, R>`, reachable via
+/// `{Name}OpsExtension` implemented for `AsynOps<S>`. The functions can't
+/// have the generic named `S`, it is taken by the state:
+/// ```ignore
+/// #[asyn_ops(window)]
+/// pub mod asyn {
+///     pub fn created() -> Promise<(), Entity> { ... }
+/// }
+/// // now both `asyn::window::created()` and
+/// // `state.asyn().window().created()` are available
+/// ```
+pub fn asyn_ops(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ctx = Context::new();
+    let name = syn::parse_macro_input!(attr as syn::Ident);
+    let module = syn::parse_macro_input!(item as syn::ItemMod);
+    proc_macro::TokenStream::from(build_asyn_ops(&ctx, &name, &module))
+}
+
+fn build_asyn_ops(ctx: &Context, name: &syn::Ident, module: &syn::ItemMod) -> TokenStream {
+    let core = ctx.core_path();
+    let module_name = &module.ident;
+    let camel: String = name
+        .to_string()
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .concat();
+    let stateful = format_ident!("StatefulAsyn{}", camel);
+    let extension = format_ident!("{}OpsExtension", camel);
+    let mut methods = quote! {};
+    for item in module.content.iter().flat_map(|(_, items)| items) {
+        let syn::Item::Fn(func) = item else { continue };
+        if !matches!(func.vis, syn::Visibility::Public(_)) {
+            continue;
+        }
+        let Some(result) = stateless_result(&func.sig.output) else {
+            continue;
+        };
+        let ident = &func.sig.ident;
+        let generics = &func.sig.generics;
+        if let Some(param) = generics.type_params().find(|param| param.ident == "S") {
+            return syn::Error::new_spanned(param, "asyn_ops: the generic `S` is reserved for the state, rename it")
+                .to_compile_error();
+        }
+        let where_clause = &generics.where_clause;
+        let (_, type_generics, _) = generics.split_for_impl();
+        let turbofish = type_generics.as_turbofish();
+        let inputs = &func.sig.inputs;
+        let mut args = vec![];
+        for input in inputs.iter() {
+            match input {
+                syn::FnArg::Typed(PatType { pat, .. }) => match pat.as_ref() {
+                    Pat::Ident(arg) => args.push(arg.ident.clone()),
+                    pat => {
+                        return syn::Error::new_spanned(pat, "asyn_ops: only plain identifiers allowed as arguments")
+                            .to_compile_error()
+                    }
+                },
+                syn::FnArg::Receiver(receiver) => {
+                    return syn::Error::new_spanned(receiver, "asyn_ops: functions can't take self").to_compile_error()
+                }
+            }
+        }
+        let doc = format!(" Stateful version of [`{module_name}::{ident}()`]");
+        methods = quote! {
+            #methods
+            #[doc = #doc]
+            pub fn #ident #generics (self, #inputs) -> #core::Promise<S, #result> #where_clause {
+                #core::PromiseLikeBase::map(#module_name::#ident #turbofish (#(#args),*), move |_| self.0)
+            }
+        };
+    }
+    quote! {
+        #module
+
+        pub struct #stateful<S>(S);
+        impl<S: 'static> #stateful<S> {
+            #methods
+        }
+
+        pub trait #extension<S> {
+            fn #name(self) -> #stateful<S>;
+        }
+        impl<S: 'static> #extension<S> for #core::AsynOps<S> {
+            fn #name(self) -> #stateful<S> {
+                #stateful(self.0)
+            }
+        }
+    }
+}
+
+/// Returns `R` if the function returns `Promise<(), R>`
+fn stateless_result(output: &syn::ReturnType) -> Option<&syn::Type> {
+    let syn::ReturnType::Type(_, ty) = output else { return None };
+    let syn::Type::Path(path) = ty.as_ref() else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Promise" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let mut args = args.args.iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(syn::GenericArgument::Type(syn::Type::Tuple(state))), Some(syn::GenericArgument::Type(result)), None)
+            if state.elems.is_empty() =>
+        {
+            Some(result)
+        }
+        _ => None,
+    }
+}
+
 struct AsynFunc {
     force_loop: bool,
     state: Option<Pat>,
@@ -120,9 +262,27 @@ impl syn::parse::Parse for AsynFunc {
     }
 }
 
+/// Returns `true` if the arg type is `&mut World`
+fn is_world_arg(arg: &syn::FnArg) -> bool {
+    let syn::FnArg::Typed(arg) = arg else {
+        return false;
+    };
+    let syn::Type::Reference(reference) = arg.ty.as_ref() else {
+        return false;
+    };
+    let syn::Type::Path(path) = reference.elem.as_ref() else {
+        return false;
+    };
+    reference.mutability.is_some() && path.path.segments.last().is_some_and(|s| s.ident == "World")
+}
+
 impl AsynFunc {
     fn build_function(&self, ctx: &Context) -> TokenStream {
         let core = ctx.core_path();
+        let exclusive = self.system_args.iter().any(is_world_arg);
+        if exclusive && self.system_args.len() > 1 {
+            panic!("&mut World can't be combined with other system args in asyn! func")
+        }
         let mut pats = quote! {};
         let mut types = quote! {};
         let mut asyn_spec = quote! {};
@@ -141,12 +301,11 @@ impl AsynFunc {
             "_".to_string()
         };
         let state_str = state_str.trim();
-        let mutable =
-            if false || state_str.starts_with("_") || state_str.starts_with("mut ") || state_str.starts_with("(") {
-                quote! {}
-            } else {
-                quote! { mut }
-            };
+        let mutable = if state_str.starts_with('_') || state_str.starts_with("mut ") || state_str.starts_with('(') {
+            quote! {}
+        } else {
+            quote! { mut }
+        };
 
         if self.force_loop {
             asyn_spec = quote!(::<#core::PromiseState<_>, #core::PromiseResult<_, #core::Loop<_>>, _>);
@@ -159,14 +318,21 @@ impl AsynFunc {
             _ => panic!("Invlid state/result arguments"),
         };
         let body = &self.body;
+        if exclusive {
+            let world = &self.system_args[0];
+            return quote! {
+                #core::Asyn #asyn_spec ::new_exclusive(|#core::bevy::prelude::In(#input), #world| {
+                    #body
+                })
+            };
+        }
         quote! {
-            #core::Asyn #asyn_spec {
-                marker: ::core::marker::PhantomData::<(#types)>,
-                body: |::bevy::prelude::In(#input), params: ::bevy::ecs::system::StaticSystemParam<(#types)>| {
+            #core::Asyn #asyn_spec ::new(
+                |#core::bevy::prelude::In(#input), params: #core::bevy::ecs::system::StaticSystemParam<(#types)>| {
                     let (#pats) = params.into_inner();
                     #body
                 }
-            }
+            )
         }
     }
 }
@@ -200,6 +366,8 @@ impl Context {
         // in future, macro may be used from inside the workspace
         if pkg.trim() == "pecs_core" {
             context.core_path = quote! { crate };
+        } else if pkg.trim() == "pecs_http" {
+            context.core_path = quote! { ::pecs_core };
         } else {
             context.core_path = quote! { ::pecs::core };
             context.is_interal = false;
@@ -261,6 +429,9 @@ fn impl_any_promises_internal_for(elements: u8) -> TokenStream {
             if local != idx {
                 local_discards = quote! {
                     #local_discards
+                    if losers == AnyLosers::Reject {
+                        errors::report(world, #id, PecsError::AnyLoserRejected { winner: any_id });
+                    }
                     promise_discard::<(), #r>(world, #id);
                 }
             }
@@ -272,18 +443,24 @@ fn impl_any_promises_internal_for(elements: u8) -> TokenStream {
         }
         register = quote! {
             #register
-            promise_register(world, #p.with((any_id, #promise_id_targets))
+            promise_register(world, #p.with((any_id, losers, #promise_id_targets))
                 .then(Asyn::<_, _, ()>::new(|In((s, r)), _| {
-                    let (any_id, #promise_id_targets) = s.value.clone();
+                    let (any_id, losers, #promise_id_targets) = s.value.clone();
                     Promise::<(), ()>::register(
-                        move |world, _id| {
-                            #local_discards
-                            promise_resolve::<(), (#type_result)>(
-                                world,
-                                any_id,
-                                (),
-                                (#local_value),
-                            );
+                        move |world, id| {
+                            // detached losers resolve after the winner
+                            if promise_is_pending::<(), (#type_result)>(world, any_id) {
+                                if losers != AnyLosers::Detach {
+                                    #local_discards
+                                }
+                                promise_resolve::<(), (#type_result)>(
+                                    world,
+                                    any_id,
+                                    (),
+                                    (#local_value),
+                                );
+                            }
+                            promise_resolve::<(), ()>(world, id, (), ());
                         },
                         |_, _| {}
                     )
@@ -297,6 +474,11 @@ fn impl_any_promises_internal_for(elements: u8) -> TokenStream {
             // type Items = (#type_items);
             type Result = (#type_result);
             fn register(self) -> Promise<(), Self::Result> {
+                self.register_with(AnyLosers::Cancel)
+            }
+        }
+        impl<#in_generics> AnyWithPromises for (#for_args) {
+            fn register_with(self, losers: AnyLosers) -> Promise<(), Self::Result> {
                 let (#promise_idents) = self;
                 let (#promise_id_targets) = (#promise_id_sources);
                 Promise::register(
@@ -311,6 +493,147 @@ fn impl_any_promises_internal_for(elements: u8) -> TokenStream {
     }
 }
 
+fn impl_any_ok_promises_internal(elements: u8) -> TokenStream {
+    let mut result = quote! {};
+    for num_elements in 1..elements {
+        let im = impl_any_ok_promises_internal_for(num_elements);
+        result = quote! {
+            #result
+            #im
+        }
+    }
+    result
+}
+
+/// The tuple is registered as the `Vec` of uniform promises: the successful result is
+/// placed into its slot of the `Option` tuple, the error is written into its slot later
+fn impl_any_ok_promises_internal_for(elements: u8) -> TokenStream {
+    let mut in_generics = quote! {};
+    let mut for_args = quote! {};
+    let mut ok_type = quote! {};
+    let mut err_slots_type = quote! {};
+    let mut err_type = quote! {};
+    let mut promise_idents = quote! {};
+    let mut err_unwraps = quote! {};
+    for idx in 0..elements + 1 {
+        let c = if idx == 0 { quote!() } else { quote!(,) };
+        let t = format_ident!("T{idx}");
+        let e = format_ident!("E{idx}");
+        let p = format_ident!("p{idx}");
+        let i = syn::Index::from(idx as usize);
+        in_generics = quote!(#in_generics #c #t: 'static, #e: 'static);
+        for_args = quote!(#for_args #c Promise<(), Result<#t, #e>>);
+        ok_type = quote!(#ok_type #c Option<#t>);
+        err_slots_type = quote!(#err_slots_type #c Option<#e>);
+        err_type = quote!(#err_type #c #e);
+        promise_idents = quote!(#promise_idents #c #p);
+        err_unwraps = quote!(#err_unwraps #c errors.#i.unwrap());
+    }
+    let mut promises = quote! {};
+    for idx in 0..elements + 1 {
+        let p = format_ident!("p{idx}");
+        let i = syn::Index::from(idx as usize);
+        promises = quote! {
+            #promises
+            #p.map_result(|r| match r {
+                Ok(value) => {
+                    let mut ok = <(#ok_type)>::default();
+                    ok.#i = Some(value);
+                    Ok(ok)
+                }
+                Err(error) => {
+                    let write: Box<dyn FnOnce(&mut (#err_slots_type))> = Box::new(move |errors| errors.#i = Some(error));
+                    Err(write)
+                }
+            }),
+        }
+    }
+
+    quote! {
+        impl<#in_generics> AnyOkPromises for (#for_args) {
+            type Result = Result<(#ok_type), (#err_type)>;
+            fn register(self) -> Promise<(), Self::Result> {
+                let (#promise_idents) = self;
+                let promises = vec![
+                    #promises
+                ];
+                AnyOkPromises::register(promises).map_result(|r| match r {
+                    Ok(((), ok)) => Ok(ok),
+                    Err(writes) => {
+                        let mut errors = <(#err_slots_type)>::default();
+                        for ((), write) in writes {
+                            write(&mut errors);
+                        }
+                        Err((#err_unwraps))
+                    }
+                })
+            }
+        }
+    }
+}
+
+fn impl_all_settled_promises_internal(elements: u8) -> TokenStream {
+    let mut result = quote! {};
+    for num_elements in 1..elements {
+        let im = impl_all_settled_promises_internal_for(num_elements);
+        result = quote! {
+            #result
+            #im
+        }
+    }
+    result
+}
+
+/// The tuple is registered as the `Vec` of uniform promises: the result is written
+/// into its slot of the `Settled` tuple, the slots of the discarded promises stay default
+fn impl_all_settled_promises_internal_for(elements: u8) -> TokenStream {
+    let mut in_generics = quote! {};
+    let mut for_args = quote! {};
+    let mut type_result = quote! {};
+    let mut promise_idents = quote! {};
+    for idx in 0..elements + 1 {
+        let c = if idx == 0 { quote!() } else { quote!(,) };
+        let r = format_ident!("R{idx}");
+        let p = format_ident!("p{idx}");
+        in_generics = quote!(#in_generics #c #r: 'static);
+        for_args = quote!(#for_args #c Promise<(), #r>);
+        type_result = quote!(#type_result #c Settled<#r>);
+        promise_idents = quote!(#promise_idents #c #p);
+    }
+    let mut promises = quote! {};
+    for idx in 0..elements + 1 {
+        let p = format_ident!("p{idx}");
+        let i = syn::Index::from(idx as usize);
+        promises = quote! {
+            #promises
+            #p.map_result(|r| -> Box<dyn FnOnce(&mut (#type_result))> {
+                Box::new(move |outcomes| outcomes.#i = Settled::Resolved(r))
+            }),
+        }
+    }
+
+    quote! {
+        impl<#in_generics> AllSettledPromises for (#for_args) {
+            type Result = (#type_result);
+            fn register(self) -> Promise<(), Self::Result> {
+                let (#promise_idents) = self;
+                let promises = vec![
+                    #promises
+                ];
+                AllSettledPromises::register(promises).map_result(|settled| {
+                    let mut outcomes = <(#type_result)>::default();
+                    for outcome in settled {
+                        if let Settled::Resolved(((), write)) = outcome {
+                            write(&mut outcomes);
+                        }
+                    }
+                    outcomes
+                })
+            }
+        }
+    }
+}
+
 fn impl_all_promises_internal(elements: u8) -> TokenStream {
     let mut result = quote! {};
     for num_elements in 1..elements {
@@ -363,7 +686,10 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
         };
         discards = quote! {
             #discards
-            promise_discard::<(), #r>(world, #id);
+            // the promises resolved so far are not registered anymore
+            if promise_is_pending::<(), #r>(world, #id) {
+                promise_discard::<(), #r>(world, #id);
+            }
         };
         if_all_passed = quote! {
             #if_all_passed
@@ -378,12 +704,21 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
             #register
             promise_register(world, #p.with((any_id, #v, #promise_id_targets))
                 .then(Asyn::<_, _, ()>::new(|In((s, r)), _| {
-                    let (any_id, mut value, #promise_id_targets) = s.value.clone();
+                    let (any_id, value, #promise_id_targets) = s.value.clone();
                     Promise::<(), ()>::register(
-                        move |world, _id| {
-                            value.get_mut().#i = Some(r);
-                            if { value.is_valid() && { let value = value.get_ref(); true #if_all_passed }} {
-                                let (#value_names) = value.get();
+                        move |world, id| {
+                            let values = {
+                                let mut slot = value.lock().unwrap();
+                                if let Some(value) = slot.as_mut() {
+                                    value.#i = Some(r);
+                                }
+                                match slot.as_ref() {
+                                    Some(value) if true #if_all_passed => slot.take(),
+                                    _ => None,
+                                }
+                            };
+                            if let Some((#value_names)) = values {
+                                debug::slot_freed(world, any_id);
                                 promise_resolve::<(), (#type_result)>(
                                     world,
                                     any_id,
@@ -391,6 +726,7 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
                                     (#value_unwraps),
                                 );
                             }
+                            promise_resolve::<(), ()>(world, id, (), ());
                         },
                         move |world, _| {
                             #discards
@@ -407,13 +743,18 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
             fn register(self) -> Promise<(), Self::Result> {
                 let (#promise_idents) = self;
                 let (#promise_id_targets) = (#promise_id_sources);
-                let value = MutPtr::<(#value_type)>::new((#value_defaults));
+                let value: AllSlot<(#value_type)> = Arc::new(Mutex::new(Some((#value_defaults))));
                 #value_clones
+                let slot = value;
                 Promise::register(
                     move |world, any_id| {
+                        debug::slot_allocated::<(#value_type)>(world, any_id);
                         #register
-                    }, move|world, _id|{
+                    }, move|world, any_id|{
                         #discards
+                        // drop the results of the promises resolved so far
+                        slot.lock().unwrap().take();
+                        debug::slot_freed(world, any_id);
                     }
                 )
             }
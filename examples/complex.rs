@@ -99,10 +99,7 @@ fn setup(mut commands: Commands) {
             info!("Requesting all");
             ["https://google.com", "https://bevyengine.org", "https://github.com"]
                 .iter()
-                .map(|url| {
-                    info!("  {url}");
-                    url
-                })
+                .inspect(|url| info!("  {url}"))
                 .map(|url| asyn::http::get(url).send().with(*url))
                 .promise()
                 .all()
@@ -176,6 +173,6 @@ pub fn rand() -> f32 {
     let pid = std::process::id();
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     (epoch, pid).hash(&mut hasher);
-    let seed = hasher.finish() as u64;
+    let seed = hasher.finish();
     (seed as f32) / u64::MAX as f32
 }
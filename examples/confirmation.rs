@@ -167,7 +167,7 @@ impl GameState {
                                     TextStyle {
                                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                         font_size: 40.0,
-                                        color: COLOR_DARK.into(),
+                                        color: COLOR_DARK,
                                     },
                                 ));
                                 let mut buttons = layout.spawn(NodeBundle {
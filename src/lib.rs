@@ -258,47 +258,157 @@
 
 /// All you need is `use pecs::prelude::*`
 pub mod prelude {
-    // structs
     #[doc(inline)]
-    pub use pecs_core::Promise;
+    pub use crate::preludes::core::*;
+    #[doc(inline)]
+    pub use crate::preludes::http::*;
+    #[doc(inline)]
+    pub use crate::preludes::timer::*;
+    #[doc(inline)]
+    pub use crate::preludes::ui::*;
+
+    // structs
     #[doc(inline)]
-    pub use pecs_core::PromiseCommand;
+    pub use pecs_core::scene::SceneError;
     #[doc(inline)]
-    pub use pecs_core::PromiseId;
+    pub use pecs_core::steps::Steps;
     #[doc(inline)]
-    pub use pecs_core::Repeat;
+    pub use pecs_core::tween::EaseFunction;
 
     // traits
     #[doc(inline)]
-    pub use pecs_core::timer::TimerOpsExtension;
+    pub use pecs_core::assets::AssetsOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::compute::ComputeOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::entity::EntityOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::event::EventOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::external::PromiseRemote;
+    #[doc(inline)]
+    pub use pecs_core::external::PromiseSender;
+    #[doc(inline)]
+    pub use pecs_core::fs::FsOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::group::PromiseGroupsCommandsExtension;
+    #[doc(inline)]
+    pub use pecs_core::input::InputOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::iter::IterOpsExtension;
     #[doc(inline)]
-    pub use pecs_core::ui::UiOpsExtension;
+    pub use pecs_core::mailbox::MailboxCommandsExtension;
     #[doc(inline)]
-    pub use pecs_core::PromiseCommandsExtension;
+    pub use pecs_core::mailbox::MailboxOpsExtension;
+    #[doc(inline)]
+    #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+    pub use pecs_core::observe::ObserveOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::platform::PlatformOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::render::RenderOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::resource::ResourceOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::scene::SceneOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::steps::StepsCommandsExtension;
+    #[doc(inline)]
+    pub use pecs_core::tween::TweenOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::debug::PecsDebugPlugin;
+    #[doc(inline)]
+    pub use pecs_core::diagnostics::PromiseDiagnosticsPlugin;
+    #[doc(inline)]
+    pub use pecs_core::watchdog::PromiseWatchdogPlugin;
+    #[doc(inline)]
+    pub use pecs_core::value::ValueCommandsExtension;
+    #[doc(inline)]
+    pub use pecs_core::value::ValueOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::window::WindowOpsExtension;
+    #[doc(inline)]
+    #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+    pub use pecs_core::remote::PromiseRemotePlugin;
+
     #[doc(inline)]
-    pub use pecs_core::PromiseLike;
+    pub use pecs_core::arena::PromiseArena;
     #[doc(inline)]
-    pub use pecs_core::PromiseLikeBase;
+    pub use pecs_core::errors::PecsErrorEvent;
     #[doc(inline)]
-    pub use pecs_core::PromisesExtension;
+    pub use pecs_core::group::GroupProgress;
     #[doc(inline)]
-    pub use pecs_http::HttpOpsExtension;
+    pub use pecs_core::inspect::PromiseInspector;
 
     // macros
     #[doc(inline)]
-    pub use pecs_core::Asyn;
+    pub use pecs_core::{expect_discarded, expect_pending, expect_resolved};
     #[doc(inline)]
     pub use pecs_macro::asyn;
 
-    use bevy::prelude::*;
+    use pecs_compat::bevy::prelude::*;
+    /// Adds `pecs` systems and resources. The app configuration is validated when
+    /// plugins are built, see [`PecsConfig`][pecs_core::validation::PecsConfig].
     pub struct PecsPlugin;
     impl Plugin for PecsPlugin {
         fn build(&self, app: &mut App) {
+            if !pecs_core::validation::install(app) {
+                warn!("PecsPlugin is added more than once, ignoring");
+                return;
+            }
+            app.init_resource::<pecs_core::validation::PecsConfig>();
+            app.add_event::<pecs_core::errors::PecsErrorEvent>();
             app.init_resource::<pecs_core::timer::Timers>();
             app.add_systems(Update, pecs_core::timer::process_timers);
+            app.init_resource::<pecs_core::timer::Timers<Virtual>>();
+            app.add_systems(Update, pecs_core::timer::process_virtual_timers);
+            app.init_resource::<pecs_core::timer::Timers<Fixed>>();
+            app.add_systems(FixedUpdate, pecs_core::timer::process_fixed_timers);
+            app.init_resource::<pecs_core::timer::FrameTimers>();
+            app.add_systems(First, pecs_core::timer::process_frame_timers);
+            app.init_resource::<pecs_core::PecsSystems>();
+            app.add_systems(Last, pecs_core::evict_unused_systems);
 
-            app.add_plugins(pecs_http::PromiseHttpPlugin);
-            app.add_plugins(pecs_core::ui::PromiseUiPlugin);
+            add_once(app, pecs_http::PromiseHttpPlugin::default());
+            add_once(app, pecs_core::ui::PromiseUiPlugin);
+            add_once(app, pecs_core::scene::PromiseScenePlugin);
+            add_once(app, pecs_core::window::PromiseWindowPlugin);
+            add_once(app, pecs_core::render::PromiseRenderPlugin);
+            add_once(app, pecs_core::time::PromiseTimePlugin);
+            add_once(app, pecs_core::tween::PromiseTweenPlugin);
+            add_once(app, pecs_core::platform::PromisePlatformPlugin);
+            add_once(app, pecs_core::resource::PromiseResourcePlugin);
+            add_once(app, pecs_core::entity::PromiseEntityPlugin);
+            add_once(app, pecs_core::input::PromiseInputPlugin);
+            add_once(app, pecs_core::assets::PromiseAssetsPlugin);
+            add_once(app, pecs_core::progress::PromiseProgressPlugin);
+            add_once(app, pecs_core::external::PromiseExternalPlugin);
+            add_once(app, pecs_core::event::PromiseEventPlugin);
+            add_once(app, pecs_core::group::PromiseGroupsPlugin);
+            #[cfg(all(feature = "io", not(target_arch = "wasm32")))]
+            add_once(app, pecs_core::io::PromiseIoPlugin);
+        }
+        fn finish(&self, app: &mut App) {
+            pecs_core::validation::report(app);
+        }
+        fn is_unique(&self) -> bool {
+            false
+        }
+    }
+
+    impl PecsPlugin {
+        /// [`PecsPlugin`] with the [strict mode](pecs_core::strict) enabled: in debug
+        /// builds the app panics on exit if labeled promises are left unresolved.
+        /// Unlabeled promises are not tracked.
+        pub fn strict_labels() -> (PecsPlugin, pecs_core::strict::PromiseStrictLabelsPlugin) {
+            (PecsPlugin, pecs_core::strict::PromiseStrictLabelsPlugin)
+        }
+    }
+
+    /// Adds the `plugin` unless it is already added by the user
+    fn add_once<P: Plugin>(app: &mut App, plugin: P) {
+        if !app.is_plugin_added::<P>() {
+            app.add_plugins(plugin);
         }
     }
 
@@ -307,17 +417,194 @@ pub mod prelude {
         #[doc(inline)]
         pub use pecs_core::app;
         #[doc(inline)]
+        pub use pecs_core::assets::asyn as assets;
+        #[doc(inline)]
+        pub use pecs_core::compute::compute;
+        #[doc(inline)]
+        pub use pecs_core::entity::entity;
+        #[doc(inline)]
+        pub use pecs_core::event::asyn as event;
+        #[doc(inline)]
+        pub use pecs_core::external::asyn as external;
+        #[doc(inline)]
+        pub use pecs_core::fs::asyn as fs;
+        #[doc(inline)]
+        pub use pecs_core::input::asyn as input;
+        #[doc(inline)]
+        pub use pecs_core::iter::asyn as iter;
+        #[doc(inline)]
+        #[cfg(all(feature = "io", not(target_arch = "wasm32")))]
+        pub use pecs_core::io::asyn as io;
+        #[doc(inline)]
+        pub use pecs_core::mailbox;
+        #[doc(inline)]
+        #[cfg(all(feature = "bevy_0_14", not(feature = "bevy_0_13")))]
+        pub use pecs_core::observe::observe;
+        #[doc(inline)]
+        pub use pecs_core::platform::asyn as platform;
+        #[doc(inline)]
+        #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+        pub use pecs_core::remote::asyn as remote;
+        #[doc(inline)]
+        pub use pecs_core::render::asyn as render;
+        #[doc(inline)]
+        pub use pecs_core::resource::asyn as resource;
+        #[doc(inline)]
+        pub use pecs_core::scene::asyn as scene;
+        #[doc(inline)]
+        pub use pecs_core::time::asyn as time;
+        #[doc(inline)]
         pub use pecs_core::timer::timeout;
         #[doc(inline)]
+        pub use pecs_core::timer::{frames, next_frame, timeout_fixed, timeout_virtual};
+        #[doc(inline)]
+        pub use pecs_core::tween::tween;
+        #[doc(inline)]
         pub use pecs_core::ui::asyn as ui;
         #[doc(inline)]
+        pub use pecs_core::value::asyn as value;
+        #[doc(inline)]
+        pub use pecs_core::window::asyn as window;
+        #[doc(inline)]
         pub use pecs_http::asyn as http;
+        #[doc(inline)]
+        pub use pecs_http::net::asyn as net;
+        #[doc(inline)]
+        #[cfg(feature = "websocket")]
+        pub use pecs_http::ws::asyn as ws;
+    }
+}
+
+/// Granular preludes for projects using only the part of `pecs`: extension traits
+/// of the unused operations are not imported, so they don't take part in the method
+/// resolution on [`AsynOps`][core::AsynOps]. [`prelude`] re-exports all of them:
+/// ```rust
+/// use pecs::preludes::core::*;
+/// use pecs::preludes::timer::*;
+/// ```
+/// The preludes are kept outside of [`prelude`], so `use pecs::prelude::*`
+/// doesn't shadow the `core` crate or the `http` crate with the modules.
+pub mod preludes {
+    /// Promises, chaining and combinators with the [`asyn!`][crate::prelude::asyn!] macro and [`PecsPlugin`]
+    pub mod core {
+        #[doc(inline)]
+        pub use pecs_core::AnyLosers;
+        #[doc(inline)]
+        pub use pecs_core::Fold;
+        #[doc(inline)]
+        pub use pecs_core::Promise;
+        #[doc(inline)]
+        pub use pecs_core::PromiseBatch;
+        #[doc(inline)]
+        pub use pecs_core::PromiseCommand;
+        #[doc(inline)]
+        pub use pecs_core::PromiseId;
+        #[doc(inline)]
+        pub use pecs_core::Repeat;
+        #[doc(inline)]
+        pub use pecs_core::RepeatHandle;
+        #[doc(inline)]
+        pub use pecs_core::Settled;
+        #[doc(inline)]
+        pub use pecs_core::snapshot::PromiseLikeRollback;
+        #[doc(inline)]
+        pub use pecs_core::snapshot::PromiseLikeSnapshot;
+        #[doc(inline)]
+        pub use pecs_core::shared::PromiseLikeShared;
+        #[doc(inline)]
+        pub use pecs_core::shared::PromiseLikeUnshared;
+        #[doc(inline)]
+        pub use pecs_core::PecsSystems;
+        #[doc(inline)]
+        pub use pecs_core::validation::PecsConfig;
+        #[doc(inline)]
+        pub use pecs_core::PromiseBatchCommandsExtension;
+        #[doc(inline)]
+        pub use pecs_core::PromiseCommandsExtension;
+        #[doc(inline)]
+        pub use pecs_core::PromiseLike;
+        #[doc(inline)]
+        pub use pecs_core::PromiseLikeBase;
+        #[doc(inline)]
+        pub use pecs_core::PromiseLikeOption;
+        #[doc(inline)]
+        pub use pecs_core::PromiseLikeResult;
+        #[doc(inline)]
+        pub use pecs_core::handle::PromiseHandle;
+        #[doc(inline)]
+        pub use pecs_core::progress::PromiseProgress;
+        #[doc(inline)]
+        pub use pecs_core::PromisesExtension;
+        #[doc(inline)]
+        pub use pecs_core::completion::Completion;
+        #[doc(inline)]
+        pub use pecs_core::Asyn;
+        #[doc(inline)]
+        pub use crate::prelude::{asyn, PecsPlugin};
+    }
+
+    /// HTTP requests: `state.asyn().http()`, `state.asyn().net()` and `state.asyn().ws()` operations
+    pub mod http {
+        #[doc(inline)]
+        pub use pecs_http::cache::HttpCache;
+        #[doc(inline)]
+        pub use pecs_http::encoding::ResponseExt;
+        #[doc(inline)]
+        #[cfg(feature = "json")]
+        pub use pecs_http::json::JsonResponseExt;
+        #[doc(inline)]
+        pub use pecs_http::net::NetOpsExtension;
+        #[doc(inline)]
+        pub use pecs_http::HttpOpsExtension;
+        #[doc(inline)]
+        pub use pecs_http::error::HttpError;
+        #[doc(inline)]
+        pub use pecs_http::form::Part;
+        #[doc(inline)]
+        pub use pecs_http::handle::RequestHandle;
+        #[doc(inline)]
+        pub use pecs_http::limit::HttpConfig;
+        #[doc(inline)]
+        pub use pecs_http::stream::StreamInfo;
+        #[doc(inline)]
+        pub use pecs_http::upload::UploadProgress;
+        #[doc(inline)]
+        #[cfg(feature = "websocket")]
+        pub use pecs_http::ws::{WebSocket, WsMessage, WsOpsExtension};
+    }
+
+    /// Timers and virtual clock: `state.asyn().timeout()` and `state.asyn().time()` operations
+    pub mod timer {
+        #[doc(inline)]
+        pub use pecs_core::timer::PromiseTimerClock;
+        #[doc(inline)]
+        pub use pecs_core::timer::Timeout;
+        #[doc(inline)]
+        pub use pecs_core::timer::TimerJitter;
+        #[doc(inline)]
+        pub use pecs_core::time::TimeOpsExtension;
+        #[doc(inline)]
+        pub use pecs_core::timer::TimerClock;
+        #[doc(inline)]
+        pub use pecs_core::timer::TimerOpsExtension;
+    }
+
+    /// UI operations: `state.asyn().ui()`
+    pub mod ui {
+        #[doc(inline)]
+        pub use pecs_core::ui::TextInput;
+        #[doc(inline)]
+        pub use pecs_core::ui::UiOpsExtension;
     }
 }
 
 #[doc(inline)]
 pub use pecs_core as core;
 #[doc(inline)]
+pub use pecs_core::debug;
+#[doc(inline)]
+pub use pecs_core::states;
+#[doc(inline)]
 pub use pecs_core::timer;
 #[doc(inline)]
 pub use pecs_http as http;